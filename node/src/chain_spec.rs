@@ -0,0 +1,34 @@
+//! Chain specifications for the PolkaAds node.
+
+use polkaads_runtime::WASM_BINARY;
+use sc_service::ChainType;
+
+/// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
+pub type ChainSpec = sc_service::GenericChainSpec;
+
+/// Single-authority development chain built from the runtime's `development` preset.
+pub fn development_chain_spec() -> Result<ChainSpec, String> {
+	Ok(ChainSpec::builder(
+		WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?,
+		None,
+	)
+	.with_name("PolkaAds Development")
+	.with_id("polkaads_dev")
+	.with_chain_type(ChainType::Development)
+	.with_genesis_config_preset_name(sp_genesis_builder::DEV_RUNTIME_PRESET)
+	.build())
+}
+
+/// Local testnet with Alice and Bob as authorities, built from the runtime's
+/// `local_testnet` preset.
+pub fn local_chain_spec() -> Result<ChainSpec, String> {
+	Ok(ChainSpec::builder(
+		WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?,
+		None,
+	)
+	.with_name("PolkaAds Local Testnet")
+	.with_id("polkaads_local_testnet")
+	.with_chain_type(ChainType::Local)
+	.with_genesis_config_preset_name(sp_genesis_builder::LOCAL_TESTNET_RUNTIME_PRESET)
+	.build())
+}
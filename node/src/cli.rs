@@ -0,0 +1,88 @@
+//! Command line interface of the PolkaAds node.
+
+use sc_cli::RunCmd;
+use std::str::FromStr;
+
+/// Block sealing used instead of Aura and GRANDPA in development mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Sealing {
+	/// Seal a block as soon as a transaction enters the pool.
+	Instant,
+	/// Seal blocks only when requested through `engine_createBlock`.
+	Manual,
+	/// Seal a block every given number of milliseconds, at least one.
+	Interval(u64),
+}
+
+impl FromStr for Sealing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"instant" => Ok(Self::Instant),
+			"manual" => Ok(Self::Manual),
+			millis => match millis.parse() {
+				Ok(millis) if millis > 0 => Ok(Self::Interval(millis)),
+				_ => Err(format!(
+					"invalid sealing `{millis}`, expected `instant`, `manual` or a positive number \
+					 of milliseconds"
+				)),
+			},
+		}
+	}
+}
+
+/// PolkaAds node command line arguments.
+#[derive(Debug, clap::Parser)]
+pub struct Cli {
+	/// Subcommand to execute instead of running the node.
+	#[command(subcommand)]
+	pub subcommand: Option<Subcommand>,
+
+	/// Options of the `run` command used when no subcommand is given.
+	#[clap(flatten)]
+	pub run: RunCmd,
+
+	/// Replace Aura and GRANDPA with manual sealing: `instant`, `manual` or a positive
+	/// block time in milliseconds. Blocks are finalized as soon as they are sealed and can
+	/// also be requested with the `engine_createBlock` RPC.
+	#[arg(long)]
+	pub sealing: Option<Sealing>,
+}
+
+/// Node subcommands.
+#[derive(Debug, clap::Subcommand)]
+#[allow(clippy::large_enum_variant)]
+pub enum Subcommand {
+	/// Key management cli utilities
+	#[command(subcommand)]
+	Key(sc_cli::KeySubcommand),
+
+	/// Sub-commands concerned with benchmarking.
+	#[command(subcommand)]
+	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
+
+	/// Build a chain specification.
+	BuildSpec(sc_cli::BuildSpecCmd),
+
+	/// Validate blocks.
+	CheckBlock(sc_cli::CheckBlockCmd),
+
+	/// Export blocks.
+	ExportBlocks(sc_cli::ExportBlocksCmd),
+
+	/// Export the state of a given block into a chain spec.
+	ExportState(sc_cli::ExportStateCmd),
+
+	/// Import blocks.
+	ImportBlocks(sc_cli::ImportBlocksCmd),
+
+	/// Remove the whole chain.
+	PurgeChain(sc_cli::PurgeChainCmd),
+
+	/// Revert the chain to a previous state.
+	Revert(sc_cli::RevertCmd),
+
+	/// Db meta columns information.
+	ChainInfo(sc_cli::ChainInfoCmd),
+}
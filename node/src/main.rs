@@ -1,41 +1,13 @@
 //! PolkaAds Node - Main entry point
-//! 
-//! This is a simplified node implementation. For production use,
-//! consider using the full Substrate node template structure.
 
 #![warn(missing_docs)]
 
+mod chain_spec;
+mod cli;
+mod command;
+mod rpc;
+mod service;
+
 fn main() -> sc_cli::Result<()> {
-	// For now, this is a placeholder. The full node implementation
-	// requires extensive setup with chain spec, RPC, service, etc.
-	// 
-	// To run the node, you can use:
-	// cargo run --release --bin polkaads-node -- --dev
-	//
-	// However, for a complete implementation, you would need:
-	// - chain_spec.rs (chain specification)
-	// - service.rs (service builder)
-	// - rpc.rs (RPC configuration)
-	// - cli.rs (command line interface)
-	//
-	// This is a complex setup that typically requires 1000+ lines of code.
-	// For development, you can use the Substrate node template as a base.
-	
-	eprintln!("PolkaAds Node");
-	eprintln!("==============");
-	eprintln!("");
-	eprintln!("Note: Full node implementation requires additional setup.");
-	eprintln!("For now, use the runtime directly or integrate with a Substrate node template.");
-	eprintln!("");
-	eprintln!("To build the runtime:");
-	eprintln!("  cd runtime && cargo build --release");
-	eprintln!("");
-	eprintln!("To use with Substrate node template:");
-	eprintln!("  1. Copy this runtime to a Substrate node template");
-	eprintln!("  2. Update the template's runtime to use polkaads-runtime");
-	eprintln!("  3. Build and run the node");
-	
-	Ok(())
+	command::run()
 }
-
-
@@ -0,0 +1,477 @@
+//! `ads_*` and `sponsorship_*` RPC methods over the `PolkaAdsApi` runtime API.
+//!
+//! Results are plain JSON: names, descriptions and CIDs are decoded as UTF-8 and balances
+//! are decimal strings, as they can exceed the integer precision of JavaScript numbers.
+
+use std::sync::Arc;
+
+use codec::Decode;
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::{ErrorObject, ErrorObjectOwned},
+};
+use pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi;
+use polkaads_runtime::{opaque::Block, AccountId, Balance, BlockNumber, Hash};
+use polkaads_runtime_api::{
+	AdInfo, AdMetricsInfo, AdvertiserInfo, CreativeInfo, ModerationInfo,
+	PolkaAdsApi as PolkaAdsRuntimeApi, SponsorshipInfo, MAX_PAGE_SIZE,
+};
+use serde::Serialize;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::traits::Block as BlockT;
+
+/// An ad.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ad {
+	pub ad_id: u32,
+	pub advertiser: AccountId,
+	pub name: String,
+	pub description: String,
+	pub ipfs_cid: String,
+	pub creative_version: u32,
+	/// Length of the creative in milliseconds, zero if unknown
+	pub creative_duration: u32,
+	pub spot_id: u32,
+	pub funding: String,
+	pub remaining_budget: String,
+	pub views: u64,
+	pub active: bool,
+	pub starts_at: BlockNumber,
+	pub ends_at: Option<BlockNumber>,
+	/// Block from which the unspent budget of the deactivated ad can be withdrawn
+	pub budget_unlocks_at: Option<BlockNumber>,
+	/// `pending`, `approved`, `rejected` or `appealed`
+	pub moderation: &'static str,
+	/// Reason given by the moderator for a rejected or appealed ad
+	pub rejection_reason: Option<String>,
+}
+
+impl From<AdInfo<AccountId, Balance, BlockNumber>> for Ad {
+	fn from(ad: AdInfo<AccountId, Balance, BlockNumber>) -> Self {
+		let (moderation, rejection_reason) = match ad.moderation {
+			ModerationInfo::Pending => ("pending", None),
+			ModerationInfo::Approved => ("approved", None),
+			ModerationInfo::Rejected { reason } => ("rejected", Some(text(reason))),
+			ModerationInfo::Appealed { reason } => ("appealed", Some(text(reason))),
+		};
+		Self {
+			ad_id: ad.ad_id,
+			advertiser: ad.advertiser,
+			name: text(ad.name),
+			description: text(ad.description),
+			ipfs_cid: text(ad.ipfs_cid),
+			creative_version: ad.creative_version,
+			creative_duration: ad.creative_duration,
+			spot_id: ad.spot_id,
+			funding: ad.funding.to_string(),
+			remaining_budget: ad.remaining_budget.to_string(),
+			views: ad.views,
+			active: ad.active,
+			starts_at: ad.starts_at,
+			ends_at: ad.ends_at,
+			budget_unlocks_at: ad.budget_unlocks_at,
+			moderation,
+			rejection_reason,
+		}
+	}
+}
+
+/// A previous creative of an ad.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Creative {
+	pub version: u32,
+	pub ipfs_cid: String,
+	pub replaced_at: BlockNumber,
+}
+
+impl From<CreativeInfo<BlockNumber>> for Creative {
+	fn from(creative: CreativeInfo<BlockNumber>) -> Self {
+		Self {
+			version: creative.version,
+			ipfs_cid: text(creative.ipfs_cid),
+			replaced_at: creative.replaced_at,
+		}
+	}
+}
+
+/// A registered advertiser.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Advertiser {
+	pub account_id: AccountId,
+	pub name: String,
+	pub registration_block: BlockNumber,
+	pub deposit: String,
+	pub active: bool,
+	pub total_funded: String,
+	pub total_ads: u32,
+	pub active_ads: u32,
+	pub needs_top_up: bool,
+	pub suspended_until: Option<BlockNumber>,
+}
+
+impl From<AdvertiserInfo<AccountId, Balance, BlockNumber>> for Advertiser {
+	fn from(profile: AdvertiserInfo<AccountId, Balance, BlockNumber>) -> Self {
+		Self {
+			account_id: profile.account_id,
+			name: text(profile.name),
+			registration_block: profile.registration_block,
+			deposit: profile.deposit.to_string(),
+			active: profile.active,
+			total_funded: profile.total_funded.to_string(),
+			total_ads: profile.total_ads,
+			active_ads: profile.active_ads,
+			needs_top_up: profile.needs_top_up,
+			suspended_until: profile.suspended_until,
+		}
+	}
+}
+
+/// Performance metrics of an ad.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdMetrics {
+	pub total_views: u64,
+	pub billable_views: u64,
+	pub rejected_views: u64,
+	pub total_clicks: u64,
+	pub billable_clicks: u64,
+	pub rejected_clicks: u64,
+	pub unique_viewers: u64,
+	pub attested_views: u64,
+}
+
+impl From<AdMetricsInfo> for AdMetrics {
+	fn from(metrics: AdMetricsInfo) -> Self {
+		Self {
+			total_views: metrics.total_views,
+			billable_views: metrics.billable_views,
+			rejected_views: metrics.rejected_views,
+			total_clicks: metrics.total_clicks,
+			billable_clicks: metrics.billable_clicks,
+			rejected_clicks: metrics.rejected_clicks,
+			unique_viewers: metrics.unique_viewers,
+			attested_views: metrics.attested_views,
+		}
+	}
+}
+
+/// A sponsorship request.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sponsorship {
+	pub request_id: u32,
+	pub user: AccountId,
+	pub ad_id: u32,
+	pub fee_amount: String,
+	pub verified: bool,
+	pub sponsored: bool,
+	pub verified_by: Option<AccountId>,
+	pub requested_at: BlockNumber,
+}
+
+impl From<SponsorshipInfo<AccountId, Balance, BlockNumber>> for Sponsorship {
+	fn from(request: SponsorshipInfo<AccountId, Balance, BlockNumber>) -> Self {
+		Self {
+			request_id: request.request_id,
+			user: request.user,
+			ad_id: request.ad_id,
+			fee_amount: request.fee_amount.to_string(),
+			verified: request.verified,
+			sponsored: request.sponsored,
+			verified_by: request.verified_by,
+			requested_at: request.requested_at,
+		}
+	}
+}
+
+/// Whether an ad can sponsor the fee of an extrinsic.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SponsorshipEstimate {
+	pub ad_id: u32,
+	/// Fee of the extrinsic without tip
+	pub fee: String,
+	/// `fee_amount` to request with `sponsor_transaction`: the fee, raised to
+	/// `MinSponsorshipAmount`
+	pub fee_amount: String,
+	/// Largest fee a sponsorship can pay, `MaxSponsoredFee`
+	pub max_sponsored_fee: String,
+	/// Remaining budget of the ad, `None` if the ad doesn't exist
+	pub remaining_budget: Option<String>,
+	pub ad_active: bool,
+	/// `fee_amount` is at most `MaxSponsoredFee`, and the ad is active and its remaining
+	/// budget covers `fee_amount`, as `sponsor_transaction` requires
+	pub sponsorable: bool,
+}
+
+#[rpc(server)]
+pub trait AdsApi<BlockHash> {
+	/// Active ads with an id of at least `start`, at most `limit` (default and maximum 100).
+	#[method(name = "ads_activeAds")]
+	fn active_ads(
+		&self,
+		start: Option<u32>,
+		limit: Option<u32>,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<Ad>>;
+
+	/// A single ad, active or not.
+	#[method(name = "ads_adById")]
+	fn ad_by_id(&self, ad_id: u32, at: Option<BlockHash>) -> RpcResult<Option<Ad>>;
+
+	/// Previous creatives of an ad, oldest first.
+	#[method(name = "ads_creativeHistory")]
+	fn creative_history(&self, ad_id: u32, at: Option<BlockHash>) -> RpcResult<Vec<Creative>>;
+
+	/// Ads of an advertiser with an id of at least `start`, at most `limit` (default and
+	/// maximum 100).
+	#[method(name = "ads_advertiserAds")]
+	fn advertiser_ads(
+		&self,
+		account: AccountId,
+		start: Option<u32>,
+		limit: Option<u32>,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<Ad>>;
+
+	/// Ads waiting for a moderation decision with an id of at least `start`, at most `limit`
+	/// (default and maximum 100).
+	#[method(name = "ads_underReview")]
+	fn ads_under_review(
+		&self,
+		start: Option<u32>,
+		limit: Option<u32>,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<Ad>>;
+
+	/// Profile of a registered advertiser.
+	#[method(name = "ads_advertiser")]
+	fn advertiser(&self, account: AccountId, at: Option<BlockHash>) -> RpcResult<Option<Advertiser>>;
+
+	/// Views, clicks and unique viewers of an ad.
+	#[method(name = "ads_metrics")]
+	fn metrics(&self, ad_id: u32, at: Option<BlockHash>) -> RpcResult<AdMetrics>;
+
+	/// A sponsorship request by id.
+	#[method(name = "sponsorship_request")]
+	fn sponsorship_request(
+		&self,
+		request_id: u32,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<Sponsorship>>;
+
+	/// Fee of a SCALE encoded extrinsic and whether `ad_id` can sponsor it.
+	#[method(name = "sponsorship_estimate")]
+	fn sponsorship_estimate(
+		&self,
+		extrinsic: Bytes,
+		ad_id: u32,
+		at: Option<BlockHash>,
+	) -> RpcResult<SponsorshipEstimate>;
+}
+
+/// Implementation of [`AdsApiServer`].
+pub struct Ads<C> {
+	client: Arc<C>,
+}
+
+impl<C> Ads<C> {
+	/// Create new `Ads` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client }
+	}
+}
+
+/// Error codes of the ads RPC.
+pub enum Error {
+	/// The runtime API call failed.
+	RuntimeError,
+	/// The extrinsic could not be decoded.
+	DecodeError,
+}
+
+impl From<Error> for i32 {
+	fn from(e: Error) -> i32 {
+		match e {
+			Error::RuntimeError => 1,
+			Error::DecodeError => 2,
+		}
+	}
+}
+
+impl<C> AdsApiServer<Hash> for Ads<C>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: PolkaAdsRuntimeApi<Block, AccountId, Balance, BlockNumber>,
+	C::Api: TransactionPaymentRuntimeApi<Block, Balance>,
+{
+	fn active_ads(
+		&self,
+		start: Option<u32>,
+		limit: Option<u32>,
+		at: Option<Hash>,
+	) -> RpcResult<Vec<Ad>> {
+		let ads = self
+			.client
+			.runtime_api()
+			.active_ads(
+				self.at(at),
+				start.unwrap_or_default(),
+				limit.unwrap_or(MAX_PAGE_SIZE),
+			)
+			.map_err(runtime_error)?;
+
+		Ok(ads.into_iter().map(Into::into).collect())
+	}
+
+	fn ad_by_id(&self, ad_id: u32, at: Option<Hash>) -> RpcResult<Option<Ad>> {
+		let ad = self.client.runtime_api().ad(self.at(at), ad_id).map_err(runtime_error)?;
+
+		Ok(ad.map(Into::into))
+	}
+
+	fn creative_history(&self, ad_id: u32, at: Option<Hash>) -> RpcResult<Vec<Creative>> {
+		let history = self
+			.client
+			.runtime_api()
+			.creative_history(self.at(at), ad_id)
+			.map_err(runtime_error)?;
+
+		Ok(history.into_iter().map(Into::into).collect())
+	}
+
+	fn advertiser_ads(
+		&self,
+		account: AccountId,
+		start: Option<u32>,
+		limit: Option<u32>,
+		at: Option<Hash>,
+	) -> RpcResult<Vec<Ad>> {
+		let ads = self
+			.client
+			.runtime_api()
+			.advertiser_ads(
+				self.at(at),
+				account,
+				start.unwrap_or_default(),
+				limit.unwrap_or(MAX_PAGE_SIZE),
+			)
+			.map_err(runtime_error)?;
+
+		Ok(ads.into_iter().map(Into::into).collect())
+	}
+
+	fn ads_under_review(
+		&self,
+		start: Option<u32>,
+		limit: Option<u32>,
+		at: Option<Hash>,
+	) -> RpcResult<Vec<Ad>> {
+		let ads = self
+			.client
+			.runtime_api()
+			.ads_under_review(
+				self.at(at),
+				start.unwrap_or_default(),
+				limit.unwrap_or(MAX_PAGE_SIZE),
+			)
+			.map_err(runtime_error)?;
+
+		Ok(ads.into_iter().map(Into::into).collect())
+	}
+
+	fn advertiser(&self, account: AccountId, at: Option<Hash>) -> RpcResult<Option<Advertiser>> {
+		let profile = self
+			.client
+			.runtime_api()
+			.advertiser(self.at(at), account)
+			.map_err(runtime_error)?;
+
+		Ok(profile.map(Into::into))
+	}
+
+	fn metrics(&self, ad_id: u32, at: Option<Hash>) -> RpcResult<AdMetrics> {
+		let metrics =
+			self.client.runtime_api().ad_metrics(self.at(at), ad_id).map_err(runtime_error)?;
+
+		Ok(metrics.into())
+	}
+
+	fn sponsorship_request(
+		&self,
+		request_id: u32,
+		at: Option<Hash>,
+	) -> RpcResult<Option<Sponsorship>> {
+		let request = self
+			.client
+			.runtime_api()
+			.sponsorship(self.at(at), request_id)
+			.map_err(runtime_error)?;
+
+		Ok(request.map(Into::into))
+	}
+
+	fn sponsorship_estimate(
+		&self,
+		extrinsic: Bytes,
+		ad_id: u32,
+		at: Option<Hash>,
+	) -> RpcResult<SponsorshipEstimate> {
+		let api = self.client.runtime_api();
+		let at = self.at(at);
+
+		let len = extrinsic.len() as u32;
+		let uxt = <Block as BlockT>::Extrinsic::decode(&mut &*extrinsic).map_err(|e| {
+			ErrorObject::owned(
+				Error::DecodeError.into(),
+				"Unable to decode the extrinsic",
+				Some(format!("{:?}", e)),
+			)
+		})?;
+
+		let fee = api.query_info(at, uxt, len).map_err(runtime_error)?.partial_fee;
+		let (min_fee_amount, max_sponsored_fee) =
+			api.sponsored_fee_bounds(at).map_err(runtime_error)?;
+		let fee_amount = fee.max(min_fee_amount);
+		let ad = api.ad(at, ad_id).map_err(runtime_error)?;
+		let ad_active = ad.as_ref().map_or(false, |ad| ad.active);
+		let remaining_budget = ad.map(|ad| ad.remaining_budget);
+
+		Ok(SponsorshipEstimate {
+			ad_id,
+			fee: fee.to_string(),
+			fee_amount: fee_amount.to_string(),
+			max_sponsored_fee: max_sponsored_fee.to_string(),
+			remaining_budget: remaining_budget.map(|budget| budget.to_string()),
+			ad_active,
+			sponsorable: fee_amount <= max_sponsored_fee &&
+				ad_active && remaining_budget.map_or(false, |budget| budget >= fee_amount),
+		})
+	}
+}
+
+impl<C: HeaderBackend<Block>> Ads<C> {
+	/// The given block, or the best block if none is given.
+	fn at(&self, at: Option<Hash>) -> Hash {
+		at.unwrap_or_else(|| self.client.info().best_hash)
+	}
+}
+
+/// Decode bytes stored on chain for display, replacing invalid UTF-8.
+fn text(bytes: Vec<u8>) -> String {
+	String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn runtime_error(err: impl std::fmt::Debug) -> ErrorObjectOwned {
+	ErrorObject::owned(
+		Error::RuntimeError.into(),
+		"Unable to query the ad marketplace",
+		Some(format!("{:?}", err)),
+	)
+}
@@ -0,0 +1,357 @@
+//! Benchmarking setup for pallet-ad-tracking
+
+use super::*;
+
+use codec::Encode;
+use frame_benchmarking::v2::*;
+use frame_support::traits::{Currency, EnsureOrigin, Get, Hooks};
+use frame_system::{pallet_prelude::BlockNumberFor, RawOrigin};
+use pallet_ads::AdLookup;
+use sp_core::{crypto::KeyTypeId, sr25519};
+use sp_runtime::traits::{Hash, One, Saturating, Zero};
+use sp_std::vec::Vec;
+
+type RuntimeOriginOf<T> = <T as frame_system::Config>::RuntimeOrigin;
+
+const KEY_TYPE: KeyTypeId = KeyTypeId(*b"pads");
+
+fn client_key_origin<T: Config>() -> Result<RuntimeOriginOf<T>, BenchmarkError> {
+	T::ClientKeyOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)
+}
+
+/// Register a new sr25519 client key.
+fn client_key<T: Config>() -> sr25519::Public {
+	let public = sp_io::crypto::sr25519_generate(KEY_TYPE, None);
+	ClientKeys::<T>::insert(ClientKey::Sr25519(public), frame_system::Pallet::<T>::block_number());
+	public
+}
+
+/// A funded account registered as aggregator.
+fn aggregator<T: Config>() -> Result<T::AccountId, BenchmarkError> {
+	let origin =
+		T::AggregatorOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+	let who = T::AggregatorOrigin::ensure_origin(origin.clone())
+		.map_err(|_| BenchmarkError::Weightless)?;
+	T::Currency::make_free_balance_be(&who, T::AggregatorBond::get().saturating_mul(10u32.into()));
+	Pallet::<T>::register_aggregator(origin)?;
+	Ok(who)
+}
+
+/// Create `n` ads and return their ids.
+fn create_ads<T: Config>(n: u32) -> Vec<u32> {
+	(0..n).map(|_| T::BenchmarkHelper::create_ad()).collect()
+}
+
+/// One view of each of `ad_ids` and the rest of `leaf_count` views of the first one.
+fn batch_views<T: Config>(
+	ad_ids: &[u32],
+	leaf_count: u64,
+) -> Result<BoundedVec<(u32, u64), T::MaxAdsPerBatch>, BenchmarkError> {
+	let mut views: Vec<(u32, u64)> = ad_ids.iter().map(|ad_id| (*ad_id, 1)).collect();
+	views[0].1 = leaf_count.saturating_sub(ad_ids.len() as u64 - 1);
+	views.try_into().map_err(|_| BenchmarkError::Weightless)
+}
+
+/// Submit a batch of `leaf_count` views of `ad_ids` with Merkle root `root` for period zero
+/// and return its id.
+fn pending_batch<T: Config>(
+	aggregator: &T::AccountId,
+	ad_ids: &[u32],
+	leaf_count: u64,
+	root: T::Hash,
+) -> Result<u32, BenchmarkError> {
+	frame_system::Pallet::<T>::set_block_number(Pallet::<T>::batch_period());
+	let batch_id = NextBatchId::<T>::get();
+	Pallet::<T>::submit_batch(
+		RawOrigin::Signed(aggregator.clone()).into(),
+		Zero::zero(),
+		root,
+		batch_views::<T>(ad_ids, leaf_count)?,
+	)?;
+	Ok(batch_id)
+}
+
+/// Attestation by `public` of a view of `ad_id` by `viewer` played from `started_at` to now,
+/// expiring at the current block.
+fn attest<T: Config>(
+	public: sr25519::Public,
+	viewer: &T::AccountId,
+	ad_id: u32,
+	started_at: T::Moment,
+) -> SignedAttestationOf<T> {
+	let attestation = Attestation {
+		ad_id,
+		creative_version: T::Ads::creative_version(ad_id).expect("the ad exists; qed"),
+		started_at,
+		ended_at: pallet_timestamp::Pallet::<T>::get(),
+		nonce: 0,
+		expires_at: frame_system::Pallet::<T>::block_number(),
+	};
+	let message = (ATTESTATION_CONTEXT, viewer, &attestation).encode();
+	let signature = sp_io::crypto::sr25519_sign(KEY_TYPE, &public, &message)
+		.expect("the key is in the keystore; qed");
+	SignedAttestation {
+		attestation,
+		key: ClientKey::Sr25519(public),
+		signature: ClientSignature::Sr25519(signature),
+	}
+}
+
+/// Attested view of `ad_id` at block zero by a funded viewer, too short to be valid.
+fn view_leaf<T: Config>(ad_id: u32) -> ViewLeaf<T> {
+	let viewer: T::AccountId = whitelisted_caller();
+	T::Currency::make_free_balance_be(&viewer, T::AggregatorBond::get());
+	let public = sp_io::crypto::sr25519_generate(KEY_TYPE, None);
+	ClientKeys::<T>::insert(ClientKey::Sr25519(public), BlockNumberFor::<T>::zero());
+	let attestation = attest::<T>(public, &viewer, ad_id, pallet_timestamp::Pallet::<T>::get());
+	ViewLeaf { viewer, block: Zero::zero(), attestation }
+}
+
+#[benchmarks]
+mod benchmarks {
+	use super::*;
+
+	/// First, attested view of the ad by the caller, which also counts a unique viewer.
+	#[benchmark]
+	fn record_view() {
+		let caller: T::AccountId = whitelisted_caller();
+		let ad_id = T::BenchmarkHelper::create_ad();
+		let view_id = NextViewId::<T>::get();
+		let attestation =
+			attest::<T>(client_key::<T>(), &caller, ad_id, pallet_timestamp::Pallet::<T>::get());
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), ad_id, Some(attestation));
+
+		assert!(ViewAttestations::<T>::contains_key(view_id));
+	}
+
+	/// Completion of a view attested only at completion.
+	#[benchmark]
+	fn complete_view() {
+		let caller: T::AccountId = whitelisted_caller();
+		let ad_id = T::BenchmarkHelper::create_ad();
+		let view_id = NextViewId::<T>::get();
+		Pallet::<T>::record_view(RawOrigin::Signed(caller.clone()).into(), ad_id, None)
+			.expect("the ad can be viewed; qed");
+		let started_at = pallet_timestamp::Pallet::<T>::get();
+		pallet_timestamp::Pallet::<T>::set_timestamp(
+			started_at.saturating_add(Pallet::<T>::min_watch_duration(ad_id)),
+		);
+		let attestation = attest::<T>(client_key::<T>(), &caller, ad_id, started_at);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), view_id, Some(attestation));
+
+		assert!(LastCompletedView::<T>::contains_key(&caller, ad_id));
+		assert_eq!(AttestedViews::<T>::get(ad_id), 1);
+	}
+
+	#[benchmark]
+	fn record_click() {
+		let caller: T::AccountId = whitelisted_caller();
+		let ad_id = T::BenchmarkHelper::create_ad();
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), ad_id);
+
+		assert_eq!(ClickRecords::<T>::get(ad_id), 1);
+	}
+
+	#[benchmark]
+	fn register_aggregator() -> Result<(), BenchmarkError> {
+		let origin =
+			T::AggregatorOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		let who = T::AggregatorOrigin::ensure_origin(origin.clone())
+			.map_err(|_| BenchmarkError::Weightless)?;
+		let balance = T::AggregatorBond::get().saturating_mul(10u32.into());
+		T::Currency::make_free_balance_be(&who, balance);
+
+		#[extrinsic_call]
+		_(origin as RuntimeOriginOf<T>);
+
+		assert!(Aggregators::<T>::contains_key(&who));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn deregister_aggregator() -> Result<(), BenchmarkError> {
+		let who = aggregator::<T>()?;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(who.clone()));
+
+		assert!(!Aggregators::<T>::contains_key(&who));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn submit_batch(n: Linear<1, { T::MaxAdsPerBatch::get() }>) -> Result<(), BenchmarkError> {
+		let who = aggregator::<T>()?;
+		let views = batch_views::<T>(&create_ads::<T>(n), n as u64)?;
+		frame_system::Pallet::<T>::set_block_number(Pallet::<T>::batch_period());
+		let batch_id = NextBatchId::<T>::get();
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(who), Zero::zero(), T::Hash::default(), views);
+
+		assert!(Batches::<T>::contains_key(batch_id));
+		Ok(())
+	}
+
+	/// Challenge of a full depth proof of a too short, attested view of the last ad of a batch
+	/// of `n` ads, the last leaf of the batch.
+	#[benchmark]
+	fn challenge_batch(n: Linear<1, { T::MaxAdsPerBatch::get() }>) -> Result<(), BenchmarkError> {
+		let who = aggregator::<T>()?;
+		let ad_ids = create_ads::<T>(n);
+		let depth = T::MaxProofLength::get().min(63);
+		let proof: Vec<T::Hash> = (0..depth).map(|level| T::Hashing::hash_of(&level)).collect();
+		let leaf = view_leaf::<T>(ad_ids[ad_ids.len() - 1]);
+		let index = (1u64 << depth) - 1;
+		let root = Pallet::<T>::merkle_root(Pallet::<T>::leaf_hash(&leaf), index, index + 1, &proof)
+			.expect("the last of a power of two leaves has a sibling on every level; qed");
+		let batch_id = pending_batch::<T>(&who, &ad_ids, index + 1, root)?;
+		let proof: BoundedVec<_, _> = proof.try_into().map_err(|_| BenchmarkError::Weightless)?;
+		let challenger: T::AccountId = account("challenger", 0, 0);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(challenger), batch_id, leaf, index, proof);
+
+		assert!(!Batches::<T>::contains_key(batch_id));
+		Ok(())
+	}
+
+	/// Challenge of full depth proofs of the last two leaves of a batch of `n` ads, the same
+	/// view.
+	#[benchmark]
+	fn challenge_duplicate(
+		n: Linear<1, { T::MaxAdsPerBatch::get() }>,
+	) -> Result<(), BenchmarkError> {
+		let who = aggregator::<T>()?;
+		let ad_ids = create_ads::<T>(n);
+		let depth = T::MaxProofLength::get().clamp(1, 63);
+		let leaf = view_leaf::<T>(ad_ids[ad_ids.len() - 1]);
+		let hash = Pallet::<T>::leaf_hash(&leaf);
+		let proof: Vec<T::Hash> = [hash]
+			.into_iter()
+			.chain((1..depth).map(|level| T::Hashing::hash_of(&level)))
+			.collect();
+		let index = (1u64 << depth) - 1;
+		let root = Pallet::<T>::merkle_root(hash, index, index + 1, &proof)
+			.expect("the last of a power of two leaves has a sibling on every level; qed");
+		let batch_id = pending_batch::<T>(&who, &ad_ids, index + 1, root)?;
+		let proof: BoundedVec<_, _> = proof.try_into().map_err(|_| BenchmarkError::Weightless)?;
+		let first = LeafProof { leaf: leaf.clone(), index: index - 1, proof: proof.clone() };
+		let second = LeafProof { leaf, index, proof };
+		let challenger: T::AccountId = account("challenger", 0, 0);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(challenger), batch_id, first, second);
+
+		assert!(!Batches::<T>::contains_key(batch_id));
+		Ok(())
+	}
+
+	/// Challenge of full depth proofs of `l` views of a viewer in one block, the first leaves
+	/// of a batch of `n` ads whose leaves are all the same view.
+	#[benchmark]
+	fn challenge_rate_limit(
+		n: Linear<1, { T::MaxAdsPerBatch::get() }>,
+		l: Linear<
+			{ T::MaxInteractionsPerBlock::get().min(T::MaxViewsPerAccountPerDay::get()) + 1 },
+			{ MaxLeavesPerChallenge::<T>::get() },
+		>,
+	) -> Result<(), BenchmarkError> {
+		let who = aggregator::<T>()?;
+		let ad_ids = create_ads::<T>(n);
+		let depth = T::MaxProofLength::get().min(63);
+		let leaf_count = 1u64 << depth;
+		if leaf_count < l as u64 {
+			return Err(BenchmarkError::Weightless);
+		}
+		let leaf = view_leaf::<T>(ad_ids[0]);
+		let mut proof: Vec<T::Hash> = Vec::new();
+		let mut node = Pallet::<T>::leaf_hash(&leaf);
+		for _ in 0..depth {
+			proof.push(node);
+			node = T::Hashing::hash_of(&(MERKLE_NODE_PREFIX, node, node));
+		}
+		let batch_id = pending_batch::<T>(&who, &ad_ids, leaf_count, node)?;
+		let proof: BoundedVec<_, _> = proof.try_into().map_err(|_| BenchmarkError::Weightless)?;
+		let leaves: BoundedVec<_, _> = (0..l as u64)
+			.map(|index| LeafProof { leaf: leaf.clone(), index, proof: proof.clone() })
+			.collect::<Vec<_>>()
+			.try_into()
+			.map_err(|_| BenchmarkError::Weightless)?;
+		let challenger: T::AccountId = account("challenger", 0, 0);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(challenger), batch_id, leaves);
+
+		assert!(!Batches::<T>::contains_key(batch_id));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn finalize_batch() -> Result<(), BenchmarkError> {
+		let who = aggregator::<T>()?;
+		let batch_id = pending_batch::<T>(&who, &create_ads::<T>(1), 1, T::Hash::default())?;
+		frame_system::Pallet::<T>::set_block_number(
+			frame_system::Pallet::<T>::block_number().saturating_add(T::ChallengePeriod::get()),
+		);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(who.clone()), batch_id);
+
+		assert!(!Batches::<T>::contains_key(batch_id));
+		assert_eq!(Aggregators::<T>::get(&who).map(|info| info.pending_batches), Some(0));
+		Ok(())
+	}
+
+	/// Pruning the nonces of `n` attestations that expired at the previous block.
+	#[benchmark]
+	fn prune_attestation_nonces(n: Linear<0, { T::MaxAttestationsPerBlock::get() }>) {
+		let expired = BlockNumberFor::<T>::one();
+		let key = ClientKey::Sr25519(client_key::<T>());
+		for nonce in 0..n {
+			UsedAttestationNonces::<T>::insert(expired, (key.clone(), u64::from(nonce)), ());
+		}
+		AttestationsExpiringAt::<T>::insert(expired, n);
+		let now = expired.saturating_add(One::one());
+
+		#[block]
+		{
+			Pallet::<T>::on_initialize(now);
+		}
+
+		assert!(!AttestationsExpiringAt::<T>::contains_key(expired));
+		assert_eq!(UsedAttestationNonces::<T>::iter_prefix(expired).count(), 0);
+	}
+
+	#[benchmark]
+	fn register_client_key() -> Result<(), BenchmarkError> {
+		let origin = client_key_origin::<T>()?;
+		let key = ClientKey::Sr25519(sp_io::crypto::sr25519_generate(KEY_TYPE, None));
+
+		#[extrinsic_call]
+		_(origin as RuntimeOriginOf<T>, key.clone());
+
+		assert!(ClientKeys::<T>::contains_key(&key));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn revoke_client_key() -> Result<(), BenchmarkError> {
+		let origin = client_key_origin::<T>()?;
+		let key = ClientKey::Sr25519(client_key::<T>());
+
+		#[extrinsic_call]
+		_(origin as RuntimeOriginOf<T>, key.clone());
+
+		assert!(!ClientKeys::<T>::contains_key(&key));
+		Ok(())
+	}
+
+	impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
+}
@@ -2,40 +2,353 @@
 //!
 //! Ad performance tracking pallet for PolkaAds.
 //! Tracks ad views, clicks, and emits verification events.
+//!
+//! Views can carry an attestation from a registered client key, such as the snap or a
+//! publisher SDK. It is signed over the SCALE encoding of
+//! `(ATTESTATION_CONTEXT, viewer, Attestation)`, so views played outside an approved
+//! player can be told apart.
+//!
+//! Bonded aggregators can instead submit the views of a period as one batch: a view count per
+//! ad and the Merkle root of the batch's [`ViewLeaf`]s, each attested by a client key for its
+//! viewer. Until its challenge period is over, anyone can prove an invalid or duplicate leaf,
+//! or more views of a viewer than the rate limits of `record_view` allow, against the root to
+//! slash the aggregator's bond and revert the batch's metrics.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub use pallet::*;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod migrations;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+pub mod weights;
+pub use weights::*;
+
+/// Sets up state owned by other pallets that the benchmarks of this pallet depend on.
+#[cfg(feature = "runtime-benchmarks")]
+pub trait BenchmarkHelper {
+	/// Create an ad that can be viewed and clicked and return its id.
+	fn create_ad() -> u32;
+}
+
+/// Read access to completed ad views for other pallets.
+pub trait CompletedViewProvider<AccountId, BlockNumber> {
+	/// Take the block at which `who` most recently completed a view of `ad_id`, if any, so
+	/// that the view can't be used again.
+	fn take_completed_view(who: &AccountId, ad_id: u32) -> Option<BlockNumber>;
+}
+
 #[frame_support::pallet]
 pub mod pallet {
-	use frame_support::pallet_prelude::*;
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{Currency, Imbalance, OnUnbalanced, ReservableCurrency},
+	};
 	use frame_system::pallet_prelude::*;
+	use pallet_ads::AdLookup;
+	use sp_core::{ed25519, sr25519};
+	use sp_runtime::{
+		traits::{Hash, One, Saturating, Verify, Zero},
+		Perbill,
+	};
+	use crate::WeightInfo;
+
+	/// The in-code storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(3);
+
+	/// Domain separator of the messages signed by client keys.
+	pub const ATTESTATION_CONTEXT: &[u8] = b"polkaads:view-attestation";
+
+	/// Prefix of the hashes of batch leaves.
+	pub const MERKLE_LEAF_PREFIX: u8 = 0;
+
+	/// Prefix of the hashes of interior nodes of batch Merkle trees.
+	pub const MERKLE_NODE_PREFIX: u8 = 1;
 
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::config]
-	pub trait Config: frame_system::Config {
+	pub trait Config: frame_system::Config + pallet_timestamp::Config {
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// Ads whose views and clicks can be recorded
+		type Ads: AdLookup;
+
+		/// Minimum time between the start and the completion of a view, in milliseconds
+		#[pallet::constant]
+		type MinWatchDuration: Get<Self::Moment>;
+
+		/// Share of the length of the creative a view must last to be completed, if longer
+		/// than `MinWatchDuration`
+		#[pallet::constant]
+		type MinWatchRatio: Get<Perbill>;
+
+		/// Blocks an account must wait between two billable views of the same ad
+		#[pallet::constant]
+		type ViewCooldown: Get<BlockNumberFor<Self>>;
+
+		/// Blocks an account must wait between two billable clicks of the same ad
+		#[pallet::constant]
+		type ClickCooldown: Get<BlockNumberFor<Self>>;
+
+		/// Maximum billable views and clicks of an account in one block
+		#[pallet::constant]
+		type MaxInteractionsPerBlock: Get<u32>;
+
+		/// Maximum billable views of an account in one day
+		#[pallet::constant]
+		type MaxViewsPerAccountPerDay: Get<u32>;
+
+		/// Length of a day in blocks
+		#[pallet::constant]
+		type BlocksPerDay: Get<BlockNumberFor<Self>>;
+
+		/// Origin that registers and revokes client keys
+		type ClientKeyOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Blocks after the block it is used in that an attestation can expire at most, which
+		/// bounds how long its nonce is kept
+		#[pallet::constant]
+		type MaxAttestationLifetime: Get<BlockNumberFor<Self>>;
+
+		/// Maximum attestations expiring at the same block
+		#[pallet::constant]
+		type MaxAttestationsPerBlock: Get<u32>;
+
+		/// Currency in which aggregator bonds are reserved
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// Origin of accounts that may register as aggregators
+		type AggregatorOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
+
+		/// Bond reserved from each aggregator and slashed for an invalid batch
+		#[pallet::constant]
+		type AggregatorBond: Get<BalanceOf<Self>>;
+
+		/// Length in blocks of the periods batches are submitted for
+		#[pallet::constant]
+		type BatchPeriod: Get<BlockNumberFor<Self>>;
+
+		/// Blocks after its submission during which a batch can be challenged
+		#[pallet::constant]
+		type ChallengePeriod: Get<BlockNumberFor<Self>>;
+
+		/// Maximum number of ads in a batch
+		#[pallet::constant]
+		type MaxAdsPerBatch: Get<u32>;
+
+		/// Maximum depth of the Merkle tree of a batch
+		#[pallet::constant]
+		type MaxProofLength: Get<u32>;
+
+		/// Handler for slashed aggregator bonds
+		type OnSlash: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+		/// Weight information for extrinsics in this pallet
+		type WeightInfo: WeightInfo;
+
+		/// Creates ads for benchmarks
+		#[cfg(feature = "runtime-benchmarks")]
+		type BenchmarkHelper: crate::BenchmarkHelper;
+	}
+
+	/// Type alias for Balance
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	/// Type alias for the attestations of a runtime
+	pub type SignedAttestationOf<T> =
+		SignedAttestation<BlockNumberFor<T>, <T as pallet_timestamp::Config>::Moment>;
+
+	/// Type alias for the imbalance of slashed funds
+	pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+		<T as frame_system::Config>::AccountId,
+	>>::NegativeImbalance;
+
+	/// Maximum number of leaves in a rate limit challenge: one more than the larger of
+	/// `MaxInteractionsPerBlock` and `MaxViewsPerAccountPerDay`
+	pub struct MaxLeavesPerChallenge<T>(PhantomData<T>);
+
+	impl<T: Config> Get<u32> for MaxLeavesPerChallenge<T> {
+		fn get() -> u32 {
+			T::MaxInteractionsPerBlock::get()
+				.max(T::MaxViewsPerAccountPerDay::get())
+				.saturating_add(1)
+		}
 	}
 
 	/// Ad metrics structure
+	///
+	/// Raw totals count every view and click of the ad; the billable ones are those within
+	/// the rate limits, the rest are counted as rejected.
 	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, Default)]
 	pub struct AdMetrics {
 		pub total_views: u64,
+		pub billable_views: u64,
+		pub rejected_views: u64,
 		pub total_clicks: u64,
+		pub billable_clicks: u64,
+		pub rejected_clicks: u64,
+		/// Accounts with at least one billable view
 		pub unique_viewers: u64,
 	}
 
+	/// Public key of an approved player, such as the snap or a publisher SDK
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum ClientKey {
+		Sr25519(sr25519::Public),
+		Ed25519(ed25519::Public),
+	}
+
+	/// Signature by a [`ClientKey`]
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub enum ClientSignature {
+		Sr25519(sr25519::Signature),
+		Ed25519(ed25519::Signature),
+	}
+
+	impl ClientKey {
+		/// Whether `signature` is a signature of `message` by this key.
+		pub fn verify(&self, signature: &ClientSignature, message: &[u8]) -> bool {
+			match (self, signature) {
+				(ClientKey::Sr25519(key), ClientSignature::Sr25519(signature)) =>
+					signature.verify(message, key),
+				(ClientKey::Ed25519(key), ClientSignature::Ed25519(signature)) =>
+					signature.verify(message, key),
+				_ => false,
+			}
+		}
+	}
+
+	/// What a client key attests about a view it played
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct Attestation<BlockNumber, Moment> {
+		pub ad_id: u32,
+		pub creative_version: u32,
+		/// When playback started and ended, in `pallet_timestamp` milliseconds
+		pub started_at: Moment,
+		pub ended_at: Moment,
+		/// Used once per client key and expiry block to prevent replays
+		pub nonce: u64,
+		/// Last block the attestation can be used in
+		pub expires_at: BlockNumber,
+	}
+
+	/// An [`Attestation`] signed by a client key
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct SignedAttestation<BlockNumber, Moment> {
+		pub attestation: Attestation<BlockNumber, Moment>,
+		pub key: ClientKey,
+		pub signature: ClientSignature,
+	}
+
+	/// Rate limit a view or click was rejected by
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum RejectionReason {
+		/// The account had a billable view or click of the ad within the cooldown
+		Cooldown,
+		/// The account reached `MaxInteractionsPerBlock` in this block
+		BlockCap,
+		/// The account reached `MaxViewsPerAccountPerDay` today
+		DailyCap,
+	}
+
+	/// Block and `pallet_timestamp` moment at which a view started or completed
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct ViewTime<BlockNumber, Moment> {
+		pub block: BlockNumber,
+		pub moment: Moment,
+	}
+
 	/// View record structure
 	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
 	#[scale_info(skip_type_params(T))]
 	pub struct ViewRecord<T: Config> {
 		pub ad_id: u32,
 		pub viewer: T::AccountId,
-		pub timestamp: u64,
-		pub completed: bool,
+		/// When the view was recorded
+		pub started: ViewTime<BlockNumberFor<T>, T::Moment>,
+		/// When the view was completed, if it was
+		pub completed: Option<ViewTime<BlockNumberFor<T>, T::Moment>>,
+	}
+
+	/// Registered aggregator
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct AggregatorInfo<T: Config> {
+		/// Reserved bond, zero once slashed
+		pub bond: BalanceOf<T>,
+		/// Latest period the aggregator submitted a batch for
+		pub last_period: Option<BlockNumberFor<T>>,
+		/// Submitted batches still in their challenge period
+		pub pending_batches: u32,
+		/// Whether a batch of the aggregator was proven invalid; slashed aggregators can't
+		/// submit batches and must deregister once their pending batches are finalized
+		pub slashed: bool,
+	}
+
+	/// Views of one period submitted by an aggregator
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct Batch<T: Config> {
+		pub aggregator: T::AccountId,
+		/// Index of the `BatchPeriod` the views were recorded in
+		pub period: BlockNumberFor<T>,
+		/// Merkle root of the batch's view leaves
+		pub root: T::Hash,
+		/// View count per ad (ad_id, views)
+		pub views: BoundedVec<(u32, u64), T::MaxAdsPerBatch>,
+		/// Number of leaves, the sum of the view counts
+		pub leaf_count: u64,
+		/// Block from which the batch can no longer be challenged
+		pub challenge_ends: BlockNumberFor<T>,
+	}
+
+	/// One view in the Merkle tree of a batch
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct ViewLeaf<T: Config> {
+		pub viewer: T::AccountId,
+		/// Block the view was recorded at
+		pub block: BlockNumberFor<T>,
+		/// Ad, creative and playback of the view, attested for `viewer` by a client key
+		pub attestation: SignedAttestationOf<T>,
+	}
+
+	/// A batch leaf with its index and the sibling hashes from the leaf up to the root
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct LeafProof<T: Config> {
+		pub leaf: ViewLeaf<T>,
+		pub index: u64,
+		pub proof: BoundedVec<T::Hash, T::MaxProofLength>,
+	}
+
+	/// Why a batch leaf is invalid
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum InvalidLeaf {
+		/// The batch counts views of another ad at the leaf's index
+		WrongAd,
+		/// The view was not recorded in the batch's period
+		OutsidePeriod,
+		/// The viewer account doesn't exist
+		UnknownViewer,
+		/// The attestation is not signed for the viewer by a client key registered when the
+		/// view was recorded, is for a creative the ad never had, or expired before the view
+		Unattested,
+		/// The view lasted less than the minimum watch duration of the ad
+		TooShort,
+		/// The batch counts another view of the same ad by the same viewer
+		Duplicate,
+		/// The batch counts more views of the viewer in a block or a day than `record_view`
+		/// bills
+		RateLimited(RejectionReason),
 	}
 
 	/// Storage: Ad metrics by ad ID
@@ -66,22 +379,159 @@ pub mod pallet {
 		ValueQuery,
 	>;
 
+	/// Storage: Block of the latest completed view not used for sponsorship yet
+	/// (user -> ad_id -> block_number)
+	#[pallet::storage]
+	#[pallet::getter(fn last_completed_views)]
+	pub type LastCompletedView<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		u32,
+		BlockNumberFor<T>,
+	>;
+
+	/// Storage: Block of the latest billable view (user -> ad_id -> block_number)
+	#[pallet::storage]
+	pub type LastBillableView<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		u32,
+		BlockNumberFor<T>,
+	>;
+
+	/// Storage: Block of the latest billable click (user -> ad_id -> block_number)
+	#[pallet::storage]
+	pub type LastBillableClick<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		u32,
+		BlockNumberFor<T>,
+	>;
+
+	/// Storage: Billable views and clicks of an account in its latest active block
+	/// (user -> (block_number, count))
+	#[pallet::storage]
+	pub type InteractionsInBlock<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, (BlockNumberFor<T>, u32), ValueQuery>;
+
+	/// Storage: Billable views of an account in its latest active day (user -> (day, count))
+	#[pallet::storage]
+	pub type DailyViews<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, (BlockNumberFor<T>, u32), ValueQuery>;
+
+	/// Storage: Views and clicks of an account rejected by the rate limits
+	#[pallet::storage]
+	pub type RejectedAttempts<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
 	/// Storage: Click records (ad_id -> click_count)
 	#[pallet::storage]
 	#[pallet::getter(fn click_records)]
 	pub type ClickRecords<T: Config> = StorageMap<_, Blake2_128Concat, u32, u64, ValueQuery>;
 
+	/// Storage: Registered client keys and the block they were registered at
+	#[pallet::storage]
+	pub type ClientKeys<T: Config> = StorageMap<_, Blake2_128Concat, ClientKey, BlockNumberFor<T>>;
+
+	/// Storage: Attestation nonces used by each client key, by the block the attestations
+	/// expire at (expires_at -> (key, nonce))
+	#[pallet::storage]
+	pub type UsedAttestationNonces<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		BlockNumberFor<T>,
+		Blake2_128Concat,
+		(ClientKey, u64),
+		(),
+	>;
+
+	/// Storage: Number of used attestations expiring at each block
+	#[pallet::storage]
+	pub type AttestationsExpiringAt<T: Config> =
+		StorageMap<_, Twox64Concat, BlockNumberFor<T>, u32, ValueQuery>;
+
+	/// Storage: Client key that attested a view (view_id -> key)
+	#[pallet::storage]
+	pub type ViewAttestations<T: Config> = StorageMap<_, Blake2_128Concat, u32, ClientKey>;
+
+	/// Storage: Completed views attested by a client key (ad_id -> view_count)
+	#[pallet::storage]
+	pub type AttestedViews<T: Config> = StorageMap<_, Blake2_128Concat, u32, u64, ValueQuery>;
+
+	/// Storage: Registered aggregators
+	#[pallet::storage]
+	pub type Aggregators<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, AggregatorInfo<T>>;
+
+	/// Storage: Batches in their challenge period or awaiting finalization by ID
+	#[pallet::storage]
+	pub type Batches<T: Config> = StorageMap<_, Blake2_128Concat, u32, Batch<T>>;
+
+	/// Storage: Next batch ID
+	#[pallet::storage]
+	pub type NextBatchId<T: Config> = StorageValue<_, u32, ValueQuery>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
 		/// Ad view recorded
 		AdViewRecorded { view_id: u32, ad_id: u32, viewer: T::AccountId },
-		/// Ad view completed
-		AdViewCompleted { view_id: u32, ad_id: u32, viewer: T::AccountId },
+		/// Ad view completed after being watched for `duration` milliseconds
+		AdViewCompleted { view_id: u32, ad_id: u32, viewer: T::AccountId, duration: T::Moment },
 		/// Ad click recorded
 		AdClickRecorded { ad_id: u32, viewer: T::AccountId },
-		/// Metrics updated
-		MetricsUpdated { ad_id: u32, views: u64, clicks: u64 },
+		/// Ad view not billed because it exceeded a rate limit
+		AdViewRejected { ad_id: u32, viewer: T::AccountId, reason: RejectionReason },
+		/// Ad click not billed because it exceeded a rate limit
+		AdClickRejected { ad_id: u32, viewer: T::AccountId, reason: RejectionReason },
+		/// Client key registered
+		ClientKeyRegistered { key: ClientKey },
+		/// Client key revoked; its attestations are no longer accepted
+		ClientKeyRevoked { key: ClientKey },
+		/// View attested by a client key
+		AdViewAttested { view_id: u32, key: ClientKey },
+		/// Aggregator registered with a reserved bond
+		AggregatorRegistered { aggregator: T::AccountId, bond: BalanceOf<T> },
+		/// Aggregator deregistered and its remaining bond returned
+		AggregatorDeregistered { aggregator: T::AccountId, refunded: BalanceOf<T> },
+		/// Batch of `views` views submitted; its view counts are added to the ad metrics
+		BatchSubmitted {
+			batch_id: u32,
+			aggregator: T::AccountId,
+			period: BlockNumberFor<T>,
+			root: T::Hash,
+			views: u64,
+		},
+		/// Batch proven invalid: its metrics were reverted and the aggregator slashed
+		BatchSlashed {
+			batch_id: u32,
+			aggregator: T::AccountId,
+			challenger: T::AccountId,
+			reason: InvalidLeaf,
+			slashed: BalanceOf<T>,
+		},
+		/// Batch finalized after its challenge period
+		BatchFinalized { batch_id: u32, aggregator: T::AccountId },
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+			// Attestations that expired at the previous block can no longer be used, so their
+			// nonces can't be replayed
+			let expired = now.saturating_sub(One::one());
+			let count = AttestationsExpiringAt::<T>::take(expired);
+			if count > 0 {
+				let _ = UsedAttestationNonces::<T>::clear_prefix(expired, count, None);
+			}
+			T::WeightInfo::prune_attestation_nonces(count)
+		}
 	}
 
 	#[pallet::error]
@@ -90,36 +540,128 @@ pub mod pallet {
 		ViewRecordNotFound,
 		/// View already completed
 		ViewAlreadyCompleted,
-		/// Ad not found
+		/// Ad not found, or not active, approved by moderation and funded
 		AdNotFound,
+		/// The view has not lasted the minimum watch duration of the ad yet
+		ViewTooShort,
+		/// Client key is not registered
+		UnknownClientKey,
+		/// Client key is already registered
+		ClientKeyAlreadyRegistered,
+		/// Attestation signature is invalid
+		InvalidAttestation,
+		/// Attestation is for another ad or creative, or its times are inconsistent
+		AttestationMismatch,
+		/// Attestation nonce was already used by the client key
+		AttestationNonceUsed,
+		/// Attestation expired, or expires more than `MaxAttestationLifetime` blocks from now
+		InvalidAttestationExpiry,
+		/// `MaxAttestationsPerBlock` attestations already expire at the same block
+		TooManyAttestations,
+		/// Account is already a registered aggregator
+		AggregatorAlreadyRegistered,
+		/// Account is not a registered aggregator, or was slashed
+		NotAggregator,
+		/// Insufficient balance for the aggregator bond
+		InsufficientBalance,
+		/// Aggregator still has batches in their challenge period
+		PendingBatches,
+		/// Period is not over yet, or the aggregator already submitted a later batch
+		InvalidPeriod,
+		/// Batch has no views
+		EmptyBatch,
+		/// Batch views must list each ad once, by increasing ad id, with a non-zero count
+		InvalidBatchViews,
+		/// Batch has more views than a tree of `MaxProofLength` levels holds
+		BatchTooLarge,
+		/// Batch not found
+		BatchNotFound,
+		/// Batch can no longer be challenged
+		ChallengePeriodOver,
+		/// Batch can still be challenged
+		ChallengePeriodNotOver,
+		/// Merkle proof doesn't lead from the leaf to the batch root
+		InvalidMerkleProof,
+		/// Leaf is a valid view of the batch
+		LeafValid,
+		/// Leaves of a duplicate or rate limit challenge are at the same index, or out of order
+		SameLeaf,
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		/// Record an ad view
+		///
+		/// Only ads that are active, approved and have budget left can be viewed. The view
+		/// starts at the current block and `pallet_timestamp` moment.
+		///
+		/// A view within `ViewCooldown` of the caller's previous billable view of the ad, or
+		/// beyond `MaxInteractionsPerBlock` or `MaxViewsPerAccountPerDay`, is not billed: it
+		/// is counted as rejected (`AdViewRejected`) and no view record is created.
+		///
+		/// An `attestation` from a registered client key marks the view as played by an
+		/// approved player. The attested playback must fit in the creative length before now.
 		#[pallet::call_index(0)]
-		#[pallet::weight(10_000)]
+		#[pallet::weight(T::WeightInfo::record_view())]
 		pub fn record_view(
 			origin: OriginFor<T>,
 			ad_id: u32,
-			timestamp: u64,
+			attestation: Option<SignedAttestationOf<T>>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			ensure!(T::Ads::is_servable(ad_id), Error::<T>::AdNotFound);
+			if let Some(attestation) = &attestation {
+				let creative: T::Moment = T::Ads::creative_duration(ad_id).into();
+				let since = pallet_timestamp::Pallet::<T>::get().saturating_sub(creative);
+				Self::check_attestation(&who, ad_id, attestation, since)?;
+			}
+			
+			let now = frame_system::Pallet::<T>::block_number();
+			let today = now / T::BlocksPerDay::get().max(One::one());
+			let (day, views_today) = DailyViews::<T>::get(&who);
+			let views_today = if day == today { views_today } else { 0 };
+			let rejection = Self::rate_limit(
+				&who,
+				LastBillableView::<T>::get(&who, ad_id),
+				T::ViewCooldown::get(),
+			)
+			.or((views_today >= T::MaxViewsPerAccountPerDay::get())
+				.then_some(RejectionReason::DailyCap));
+			if let Some(reason) = rejection {
+				AdMetricsStorage::<T>::mutate(ad_id, |metrics| {
+					metrics.total_views = metrics.total_views.saturating_add(1);
+					metrics.rejected_views = metrics.rejected_views.saturating_add(1);
+				});
+				RejectedAttempts::<T>::mutate(&who, |attempts| {
+					*attempts = attempts.saturating_add(1)
+				});
+				Self::deposit_event(Event::AdViewRejected { ad_id, viewer: who, reason });
+				return Ok(());
+			}
+			
+			Self::count_interaction(&who, now);
+			DailyViews::<T>::insert(&who, (today, views_today.saturating_add(1)));
+			LastBillableView::<T>::insert(&who, ad_id, now);
 			
 			let view_id = NextViewId::<T>::get();
 			let view_record = ViewRecord {
 				ad_id,
 				viewer: who.clone(),
-				timestamp,
-				completed: false,
+				started: Self::now(),
+				completed: None,
 			};
 			
 			ViewRecords::<T>::insert(view_id, view_record);
 			NextViewId::<T>::put(view_id.saturating_add(1));
+			if let Some(SignedAttestation { key, .. }) = attestation {
+				ViewAttestations::<T>::insert(view_id, key.clone());
+				Self::deposit_event(Event::AdViewAttested { view_id, key });
+			}
 			
 			// Update metrics
 			AdMetricsStorage::<T>::mutate(ad_id, |metrics| {
 				metrics.total_views = metrics.total_views.saturating_add(1);
+				metrics.billable_views = metrics.billable_views.saturating_add(1);
 				
 				// Check if this is a unique viewer
 				if !UserViews::<T>::get(&who, ad_id) {
@@ -134,23 +676,58 @@ pub mod pallet {
 		}
 
 		/// Mark ad view as completed
+		///
+		/// The view must have lasted the minimum watch duration of the ad: `MinWatchRatio`
+		/// of the length of its creative, and at least `MinWatchDuration`.
+		///
+		/// An `attestation` from a registered client key must attest playback for at least
+		/// that long, between the recorded start and now. Completed views attested here or in
+		/// `record_view` count as `AttestedViews` of the ad.
 		#[pallet::call_index(1)]
-		#[pallet::weight(10_000)]
-		pub fn complete_view(origin: OriginFor<T>, view_id: u32) -> DispatchResult {
+		#[pallet::weight(T::WeightInfo::complete_view())]
+		pub fn complete_view(
+			origin: OriginFor<T>,
+			view_id: u32,
+			attestation: Option<SignedAttestationOf<T>>,
+		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 			
 			ViewRecords::<T>::try_mutate(view_id, |maybe_record| -> DispatchResult {
 				let record = maybe_record.as_mut().ok_or(Error::<T>::ViewRecordNotFound)?;
 				
 				ensure!(record.viewer == who, DispatchError::BadOrigin);
-				ensure!(!record.completed, Error::<T>::ViewAlreadyCompleted);
+				ensure!(record.completed.is_none(), Error::<T>::ViewAlreadyCompleted);
+				
+				let now = Self::now();
+				let duration = now.moment.saturating_sub(record.started.moment);
+				let min_watch_duration = Self::min_watch_duration(record.ad_id);
+				ensure!(duration >= min_watch_duration, Error::<T>::ViewTooShort);
 				
-				record.completed = true;
+				if let Some(signed) = attestation {
+					Self::check_attestation(&who, record.ad_id, &signed, record.started.moment)?;
+					let attestation = &signed.attestation;
+					ensure!(
+						attestation.ended_at.saturating_sub(attestation.started_at) >=
+							min_watch_duration,
+						Error::<T>::ViewTooShort
+					);
+					ViewAttestations::<T>::insert(view_id, signed.key.clone());
+					Self::deposit_event(Event::AdViewAttested { view_id, key: signed.key });
+				}
+				if ViewAttestations::<T>::contains_key(view_id) {
+					AttestedViews::<T>::mutate(record.ad_id, |count| {
+						*count = count.saturating_add(1);
+					});
+				}
+				
+				LastCompletedView::<T>::insert(&who, record.ad_id, now.block);
+				record.completed = Some(now);
 				
 				Self::deposit_event(Event::AdViewCompleted {
 					view_id,
 					ad_id: record.ad_id,
 					viewer: who,
+					duration,
 				});
 				
 				Ok(())
@@ -158,10 +735,38 @@ pub mod pallet {
 		}
 
 		/// Record an ad click
+		///
+		/// Only ads that are active, approved and have budget left can be clicked.
+		///
+		/// A click within `ClickCooldown` of the caller's previous billable click of the ad,
+		/// or beyond `MaxInteractionsPerBlock`, is not billed but counted as rejected
+		/// (`AdClickRejected`).
 		#[pallet::call_index(2)]
-		#[pallet::weight(10_000)]
+		#[pallet::weight(T::WeightInfo::record_click())]
 		pub fn record_click(origin: OriginFor<T>, ad_id: u32) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			ensure!(T::Ads::is_servable(ad_id), Error::<T>::AdNotFound);
+			
+			let now = frame_system::Pallet::<T>::block_number();
+			let rejection = Self::rate_limit(
+				&who,
+				LastBillableClick::<T>::get(&who, ad_id),
+				T::ClickCooldown::get(),
+			);
+			if let Some(reason) = rejection {
+				AdMetricsStorage::<T>::mutate(ad_id, |metrics| {
+					metrics.total_clicks = metrics.total_clicks.saturating_add(1);
+					metrics.rejected_clicks = metrics.rejected_clicks.saturating_add(1);
+				});
+				RejectedAttempts::<T>::mutate(&who, |attempts| {
+					*attempts = attempts.saturating_add(1)
+				});
+				Self::deposit_event(Event::AdClickRejected { ad_id, viewer: who, reason });
+				return Ok(());
+			}
+			
+			Self::count_interaction(&who, now);
+			LastBillableClick::<T>::insert(&who, ad_id, now);
 			
 			// Update click count
 			ClickRecords::<T>::mutate(ad_id, |count| {
@@ -171,6 +776,7 @@ pub mod pallet {
 			// Update metrics
 			AdMetricsStorage::<T>::mutate(ad_id, |metrics| {
 				metrics.total_clicks = metrics.total_clicks.saturating_add(1);
+				metrics.billable_clicks = metrics.billable_clicks.saturating_add(1);
 			});
 			
 			Self::deposit_event(Event::AdClickRecorded { ad_id, viewer: who });
@@ -178,21 +784,544 @@ pub mod pallet {
 			Ok(())
 		}
 
-		/// Get ad performance metrics (callable by anyone)
-		#[pallet::call_index(3)]
-		#[pallet::weight(10_000)]
-		pub fn get_ad_metrics(origin: OriginFor<T>, ad_id: u32) -> DispatchResult {
-			let _ = ensure_signed(origin)?;
+		// Call index 3 was `get_ad_metrics`, retired in favour of the `PolkaAdsApi` runtime
+		// API. Don't reuse it.
+
+		/// Register a client key whose view attestations are accepted
+		#[pallet::call_index(4)]
+		#[pallet::weight(T::WeightInfo::register_client_key())]
+		pub fn register_client_key(origin: OriginFor<T>, key: ClientKey) -> DispatchResult {
+			T::ClientKeyOrigin::ensure_origin(origin)?;
+			ensure!(!ClientKeys::<T>::contains_key(&key), Error::<T>::ClientKeyAlreadyRegistered);
 			
-			let metrics = AdMetricsStorage::<T>::get(ad_id);
+			ClientKeys::<T>::insert(&key, frame_system::Pallet::<T>::block_number());
+			Self::deposit_event(Event::ClientKeyRegistered { key });
+			Ok(())
+		}
+
+		/// Revoke a client key
+		///
+		/// Views it already attested stay attested.
+		#[pallet::call_index(5)]
+		#[pallet::weight(T::WeightInfo::revoke_client_key())]
+		pub fn revoke_client_key(origin: OriginFor<T>, key: ClientKey) -> DispatchResult {
+			T::ClientKeyOrigin::ensure_origin(origin)?;
+			ensure!(ClientKeys::<T>::contains_key(&key), Error::<T>::UnknownClientKey);
 			
-			Self::deposit_event(Event::MetricsUpdated {
-				ad_id,
-				views: metrics.total_views,
-				clicks: metrics.total_clicks,
+			ClientKeys::<T>::remove(&key);
+			Self::deposit_event(Event::ClientKeyRevoked { key });
+			Ok(())
+		}
+
+		/// Register as an aggregator by reserving `AggregatorBond`
+		#[pallet::call_index(6)]
+		#[pallet::weight(T::WeightInfo::register_aggregator())]
+		pub fn register_aggregator(origin: OriginFor<T>) -> DispatchResult {
+			let who = T::AggregatorOrigin::ensure_origin(origin)?;
+			ensure!(
+				!Aggregators::<T>::contains_key(&who),
+				Error::<T>::AggregatorAlreadyRegistered
+			);
+			
+			let bond = T::AggregatorBond::get();
+			T::Currency::reserve(&who, bond).map_err(|_| Error::<T>::InsufficientBalance)?;
+			Aggregators::<T>::insert(
+				&who,
+				AggregatorInfo { bond, last_period: None, pending_batches: 0, slashed: false },
+			);
+			
+			Self::deposit_event(Event::AggregatorRegistered { aggregator: who, bond });
+			Ok(())
+		}
+
+		/// Deregister as an aggregator and get the remaining bond back
+		///
+		/// All batches of the aggregator must be finalized first.
+		#[pallet::call_index(7)]
+		#[pallet::weight(T::WeightInfo::deregister_aggregator())]
+		pub fn deregister_aggregator(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let info = Aggregators::<T>::get(&who).ok_or(Error::<T>::NotAggregator)?;
+			ensure!(info.pending_batches == 0, Error::<T>::PendingBatches);
+			
+			T::Currency::unreserve(&who, info.bond);
+			Aggregators::<T>::remove(&who);
+			
+			Self::deposit_event(Event::AggregatorDeregistered {
+				aggregator: who,
+				refunded: info.bond,
+			});
+			Ok(())
+		}
+
+		/// Submit the views of a past `period` as view counts per ad and the Merkle root of
+		/// their [`ViewLeaf`]s
+		///
+		/// `views` lists each ad once by increasing ad id, and the leaves are ordered the same
+		/// way: the first count of leaves are views of the first ad, and so on. Only ads that are
+		/// active, approved and have budget left can be counted.
+		///
+		/// Each leaf is attested by a client key, and a batch holds at most one view per viewer
+		/// and ad. Like `record_view`, it holds at most `MaxInteractionsPerBlock` views of a
+		/// viewer per block and `MaxViewsPerAccountPerDay` per day.
+		///
+		/// The counts are added to the raw and billable views of the ads right away and
+		/// reverted if the batch is proven invalid within `ChallengePeriod` blocks. Each
+		/// aggregator submits at most one batch per period, in increasing order of periods.
+		#[pallet::call_index(8)]
+		#[pallet::weight(T::WeightInfo::submit_batch(views.len() as u32))]
+		pub fn submit_batch(
+			origin: OriginFor<T>,
+			period: BlockNumberFor<T>,
+			root: T::Hash,
+			views: BoundedVec<(u32, u64), T::MaxAdsPerBatch>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut info = Aggregators::<T>::get(&who).ok_or(Error::<T>::NotAggregator)?;
+			ensure!(!info.slashed, Error::<T>::NotAggregator);
+			
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(
+				period < now / Self::batch_period() &&
+					info.last_period.map_or(true, |last| period > last),
+				Error::<T>::InvalidPeriod
+			);
+			
+			let mut leaf_count: u64 = 0;
+			let mut previous: Option<u32> = None;
+			for (ad_id, count) in views.iter() {
+				ensure!(
+					*count > 0 && previous.map_or(true, |previous| previous < *ad_id),
+					Error::<T>::InvalidBatchViews
+				);
+				ensure!(T::Ads::is_servable(*ad_id), Error::<T>::AdNotFound);
+				leaf_count = leaf_count.saturating_add(*count);
+				previous = Some(*ad_id);
+			}
+			ensure!(leaf_count > 0, Error::<T>::EmptyBatch);
+			ensure!(
+				Self::merkle_depth(leaf_count) <= T::MaxProofLength::get(),
+				Error::<T>::BatchTooLarge
+			);
+			
+			for (ad_id, count) in views.iter() {
+				AdMetricsStorage::<T>::mutate(ad_id, |metrics| {
+					metrics.total_views = metrics.total_views.saturating_add(*count);
+					metrics.billable_views = metrics.billable_views.saturating_add(*count);
+				});
+			}
+			
+			info.last_period = Some(period);
+			info.pending_batches = info.pending_batches.saturating_add(1);
+			Aggregators::<T>::insert(&who, info);
+			
+			let batch_id = NextBatchId::<T>::get();
+			Batches::<T>::insert(
+				batch_id,
+				Batch {
+					aggregator: who.clone(),
+					period,
+					root,
+					views,
+					leaf_count,
+					challenge_ends: now.saturating_add(T::ChallengePeriod::get()),
+				},
+			);
+			NextBatchId::<T>::put(batch_id.saturating_add(1));
+			
+			Self::deposit_event(Event::BatchSubmitted {
+				batch_id,
+				aggregator: who,
+				period,
+				root,
+				views: leaf_count,
+			});
+			Ok(())
+		}
+
+		/// Prove that `leaf`, at `index` in the Merkle tree of a batch, is invalid
+		///
+		/// `proof` holds the sibling hashes from the leaf up to the root. A valid challenge
+		/// reverts the batch's view counts, slashes the aggregator's bond to `OnSlash` and
+		/// removes the batch.
+		#[pallet::call_index(9)]
+		#[pallet::weight(T::WeightInfo::challenge_batch(T::MaxAdsPerBatch::get()))]
+		pub fn challenge_batch(
+			origin: OriginFor<T>,
+			batch_id: u32,
+			leaf: ViewLeaf<T>,
+			index: u64,
+			proof: BoundedVec<T::Hash, T::MaxProofLength>,
+		) -> DispatchResult {
+			let challenger = ensure_signed(origin)?;
+			let batch = Self::challengeable_batch(batch_id)?;
+			
+			Self::ensure_in_batch(&batch, &leaf, index, &proof)?;
+			let reason = Self::invalid_leaf(&batch, &leaf, index).ok_or(Error::<T>::LeafValid)?;
+			
+			Self::slash_batch(batch_id, batch, challenger, reason);
+			Ok(())
+		}
+
+		/// Finalize a batch whose challenge period is over
+		///
+		/// Can be called by anyone. The batch's view counts stay in the ad metrics.
+		#[pallet::call_index(10)]
+		#[pallet::weight(T::WeightInfo::finalize_batch())]
+		pub fn finalize_batch(origin: OriginFor<T>, batch_id: u32) -> DispatchResult {
+			ensure_signed(origin)?;
+			let batch = Batches::<T>::get(batch_id).ok_or(Error::<T>::BatchNotFound)?;
+			ensure!(
+				frame_system::Pallet::<T>::block_number() >= batch.challenge_ends,
+				Error::<T>::ChallengePeriodNotOver
+			);
+			
+			Batches::<T>::remove(batch_id);
+			Aggregators::<T>::mutate(&batch.aggregator, |maybe_info| {
+				if let Some(info) = maybe_info {
+					info.pending_batches = info.pending_batches.saturating_sub(1);
+				}
 			});
 			
+			Self::deposit_event(Event::BatchFinalized { batch_id, aggregator: batch.aggregator });
+			Ok(())
+		}
+
+		/// Prove that a batch counts two views of the same ad by the same viewer
+		///
+		/// Both leaves need a Merkle proof against the batch root, at different indices. A
+		/// valid challenge slashes the aggregator like `challenge_batch`.
+		#[pallet::call_index(11)]
+		#[pallet::weight(T::WeightInfo::challenge_duplicate(T::MaxAdsPerBatch::get()))]
+		pub fn challenge_duplicate(
+			origin: OriginFor<T>,
+			batch_id: u32,
+			first: LeafProof<T>,
+			second: LeafProof<T>,
+		) -> DispatchResult {
+			let challenger = ensure_signed(origin)?;
+			let batch = Self::challengeable_batch(batch_id)?;
+			
+			for LeafProof { leaf, index, proof } in [&first, &second] {
+				Self::ensure_in_batch(&batch, leaf, *index, proof)?;
+			}
+			ensure!(first.index != second.index, Error::<T>::SameLeaf);
+			ensure!(
+				first.leaf.viewer == second.leaf.viewer &&
+					first.leaf.attestation.attestation.ad_id ==
+						second.leaf.attestation.attestation.ad_id,
+				Error::<T>::LeafValid
+			);
+			
+			Self::slash_batch(batch_id, batch, challenger, InvalidLeaf::Duplicate);
+			Ok(())
+		}
+
+		/// Prove that a batch counts more views of one viewer in a block or a day than
+		/// `MaxInteractionsPerBlock` or `MaxViewsPerAccountPerDay` allow
+		///
+		/// `leaves` are views of the same viewer recorded in the same block or day, by
+		/// increasing index, each with a Merkle proof against the batch root. A valid challenge
+		/// slashes the aggregator like `challenge_batch`.
+		#[pallet::call_index(12)]
+		#[pallet::weight(T::WeightInfo::challenge_rate_limit(
+			T::MaxAdsPerBatch::get(),
+			leaves.len() as u32,
+		))]
+		pub fn challenge_rate_limit(
+			origin: OriginFor<T>,
+			batch_id: u32,
+			leaves: BoundedVec<LeafProof<T>, MaxLeavesPerChallenge<T>>,
+		) -> DispatchResult {
+			let challenger = ensure_signed(origin)?;
+			let batch = Self::challengeable_batch(batch_id)?;
+			
+			let mut previous: Option<u64> = None;
+			for LeafProof { leaf, index, proof } in leaves.iter() {
+				Self::ensure_in_batch(&batch, leaf, *index, proof)?;
+				ensure!(previous.map_or(true, |previous| previous < *index), Error::<T>::SameLeaf);
+				previous = Some(*index);
+			}
+			let reason = Self::rate_limited(&leaves).ok_or(Error::<T>::LeafValid)?;
+			
+			Self::slash_batch(batch_id, batch, challenger, InvalidLeaf::RateLimited(reason));
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Current block and `pallet_timestamp` moment.
+		fn now() -> ViewTime<BlockNumberFor<T>, T::Moment> {
+			ViewTime {
+				block: frame_system::Pallet::<T>::block_number(),
+				moment: pallet_timestamp::Pallet::<T>::get(),
+			}
+		}
+
+		/// Check that `signed` is a valid attestation by a registered client key of a view of
+		/// the current creative of `ad_id` by `viewer`, and use up its nonce.
+		///
+		/// The attested playback must lie between `since` and the current moment.
+		fn check_attestation(
+			viewer: &T::AccountId,
+			ad_id: u32,
+			signed: &SignedAttestationOf<T>,
+			since: T::Moment,
+		) -> DispatchResult {
+			let attestation = &signed.attestation;
+			ensure!(ClientKeys::<T>::contains_key(&signed.key), Error::<T>::UnknownClientKey);
+			
+			let message = (ATTESTATION_CONTEXT, viewer, attestation).encode();
+			ensure!(signed.key.verify(&signed.signature, &message), Error::<T>::InvalidAttestation);
+			
+			ensure!(
+				attestation.ad_id == ad_id &&
+					T::Ads::creative_version(ad_id) == Some(attestation.creative_version) &&
+					since <= attestation.started_at &&
+					attestation.started_at <= attestation.ended_at &&
+					attestation.ended_at <= pallet_timestamp::Pallet::<T>::get(),
+				Error::<T>::AttestationMismatch
+			);
+			
+			let now = frame_system::Pallet::<T>::block_number();
+			let expires_at = attestation.expires_at;
+			let lifetime = T::MaxAttestationLifetime::get();
+			ensure!(
+				now <= expires_at && expires_at <= now.saturating_add(lifetime),
+				Error::<T>::InvalidAttestationExpiry
+			);
+			let used = (signed.key.clone(), attestation.nonce);
+			ensure!(
+				!UsedAttestationNonces::<T>::contains_key(expires_at, &used),
+				Error::<T>::AttestationNonceUsed
+			);
+			AttestationsExpiringAt::<T>::try_mutate(expires_at, |count| -> DispatchResult {
+				ensure!(
+					*count < T::MaxAttestationsPerBlock::get(),
+					Error::<T>::TooManyAttestations
+				);
+				*count = count.saturating_add(1);
+				Ok(())
+			})?;
+			UsedAttestationNonces::<T>::insert(expires_at, used, ());
+			Ok(())
+		}
+
+		/// The rate limit a new view or click of `who` exceeds, if any, given the block of
+		/// their `last` billable one of the same kind and ad and its `cooldown`.
+		fn rate_limit(
+			who: &T::AccountId,
+			last: Option<BlockNumberFor<T>>,
+			cooldown: BlockNumberFor<T>,
+		) -> Option<RejectionReason> {
+			let now = frame_system::Pallet::<T>::block_number();
+			if last.map_or(false, |last| now < last.saturating_add(cooldown)) {
+				return Some(RejectionReason::Cooldown);
+			}
+			let (block, count) = InteractionsInBlock::<T>::get(who);
+			if block == now && count >= T::MaxInteractionsPerBlock::get() {
+				return Some(RejectionReason::BlockCap);
+			}
+			None
+		}
+
+		/// Count a billable view or click of `who` in block `now`.
+		fn count_interaction(who: &T::AccountId, now: BlockNumberFor<T>) {
+			InteractionsInBlock::<T>::mutate(who, |(block, count)| {
+				if *block != now {
+					*block = now;
+					*count = 0;
+				}
+				*count = count.saturating_add(1);
+			});
+		}
+
+		/// How long a view of `ad_id` must last before it can be completed.
+		pub fn min_watch_duration(ad_id: u32) -> T::Moment {
+			let creative: T::Moment = T::Ads::creative_duration(ad_id).into();
+			(T::MinWatchRatio::get() * creative).max(T::MinWatchDuration::get())
+		}
+
+		/// Length of the periods batches are submitted for, at least one block.
+		pub fn batch_period() -> BlockNumberFor<T> {
+			T::BatchPeriod::get().max(One::one())
+		}
+
+		/// Depth of the Merkle tree of a batch with `leaf_count` leaves.
+		fn merkle_depth(leaf_count: u64) -> u32 {
+			leaf_count.checked_next_power_of_two().map_or(u64::BITS, |size| size.trailing_zeros())
+		}
+
+		/// Hash of a batch leaf, prefixed with [`MERKLE_LEAF_PREFIX`].
+		pub fn leaf_hash(leaf: &ViewLeaf<T>) -> T::Hash {
+			T::Hashing::hash_of(&(MERKLE_LEAF_PREFIX, leaf))
+		}
+
+		/// Root of the Merkle tree of `leaf_count` leaves in which the leaf hashed to `leaf` is
+		/// at `index`, given the sibling hashes from the leaf up.
+		///
+		/// Each parent is `T::Hashing` of [`MERKLE_NODE_PREFIX`] and its left and right child.
+		/// The last node of a level without a sibling moves up unchanged. `None` if `index` is
+		/// beyond `leaf_count` or `proof` doesn't have one hash per sibling.
+		pub fn merkle_root(
+			leaf: T::Hash,
+			index: u64,
+			leaf_count: u64,
+			proof: &[T::Hash],
+		) -> Option<T::Hash> {
+			if index >= leaf_count {
+				return None;
+			}
+			let mut siblings = proof.iter();
+			let mut node = leaf;
+			let mut position = index;
+			let mut width = leaf_count;
+			while width > 1 {
+				if position % 2 == 1 {
+					node = T::Hashing::hash_of(&(MERKLE_NODE_PREFIX, siblings.next()?, node));
+				} else if position + 1 < width {
+					node = T::Hashing::hash_of(&(MERKLE_NODE_PREFIX, node, siblings.next()?));
+				}
+				position /= 2;
+				width = width.div_ceil(2);
+			}
+			siblings.next().is_none().then_some(node)
+		}
+
+		/// The ad whose views `batch` counts at leaf `index`.
+		fn ad_at(batch: &Batch<T>, index: u64) -> Option<u32> {
+			let mut end: u64 = 0;
+			batch.views.iter().find_map(|(ad_id, count)| {
+				end = end.saturating_add(*count);
+				(index < end).then_some(*ad_id)
+			})
+		}
+
+		/// A batch that can still be challenged.
+		fn challengeable_batch(batch_id: u32) -> Result<Batch<T>, DispatchError> {
+			let batch = Batches::<T>::get(batch_id).ok_or(Error::<T>::BatchNotFound)?;
+			ensure!(
+				frame_system::Pallet::<T>::block_number() < batch.challenge_ends,
+				Error::<T>::ChallengePeriodOver
+			);
+			Ok(batch)
+		}
+
+		/// Ensure `proof` leads from `leaf` at `index` to the root of `batch`.
+		fn ensure_in_batch(
+			batch: &Batch<T>,
+			leaf: &ViewLeaf<T>,
+			index: u64,
+			proof: &[T::Hash],
+		) -> DispatchResult {
+			ensure!(
+				Self::merkle_root(Self::leaf_hash(leaf), index, batch.leaf_count, proof) ==
+					Some(batch.root),
+				Error::<T>::InvalidMerkleProof
+			);
 			Ok(())
 		}
+
+		/// Revert the view counts of a batch proven invalid, remove it and slash its
+		/// aggregator.
+		fn slash_batch(
+			batch_id: u32,
+			batch: Batch<T>,
+			challenger: T::AccountId,
+			reason: InvalidLeaf,
+		) {
+			for (ad_id, count) in batch.views.iter() {
+				AdMetricsStorage::<T>::mutate(ad_id, |metrics| {
+					metrics.total_views = metrics.total_views.saturating_sub(*count);
+					metrics.billable_views = metrics.billable_views.saturating_sub(*count);
+				});
+			}
+			Batches::<T>::remove(batch_id);
+			
+			let slashed = Aggregators::<T>::mutate(&batch.aggregator, |maybe_info| {
+				let Some(info) = maybe_info else { return Zero::zero() };
+				
+				let (slashed, _) = T::Currency::slash_reserved(&batch.aggregator, info.bond);
+				let amount = slashed.peek();
+				T::OnSlash::on_unbalanced(slashed);
+				
+				info.bond = info.bond.saturating_sub(amount);
+				info.pending_batches = info.pending_batches.saturating_sub(1);
+				info.slashed = true;
+				amount
+			});
+			
+			Self::deposit_event(Event::BatchSlashed {
+				batch_id,
+				aggregator: batch.aggregator,
+				challenger,
+				reason,
+				slashed,
+			});
+		}
+
+		/// Why `leaf`, at `index` in the Merkle tree of `batch`, is invalid, if it is.
+		fn invalid_leaf(batch: &Batch<T>, leaf: &ViewLeaf<T>, index: u64) -> Option<InvalidLeaf> {
+			let attestation = &leaf.attestation.attestation;
+			let period_start = batch.period.saturating_mul(Self::batch_period());
+			let period_end = period_start.saturating_add(Self::batch_period());
+			if Self::ad_at(batch, index) != Some(attestation.ad_id) {
+				Some(InvalidLeaf::WrongAd)
+			} else if leaf.block < period_start || leaf.block >= period_end {
+				Some(InvalidLeaf::OutsidePeriod)
+			} else if !frame_system::Pallet::<T>::account_exists(&leaf.viewer) {
+				Some(InvalidLeaf::UnknownViewer)
+			} else if !Self::is_attested(leaf) {
+				Some(InvalidLeaf::Unattested)
+			} else if attestation.ended_at.saturating_sub(attestation.started_at) <
+				Self::min_watch_duration(attestation.ad_id)
+			{
+				Some(InvalidLeaf::TooShort)
+			} else {
+				None
+			}
+		}
+
+		/// The rate limit exceeded by `leaves` if they are views of one viewer, more than
+		/// `MaxInteractionsPerBlock` in a block or `MaxViewsPerAccountPerDay` in a day.
+		fn rate_limited(leaves: &[LeafProof<T>]) -> Option<RejectionReason> {
+			let first = &leaves.first()?.leaf;
+			let count = leaves.len() as u32;
+			let day = |block: BlockNumberFor<T>| block / T::BlocksPerDay::get().max(One::one());
+			if leaves.iter().any(|LeafProof { leaf, .. }| leaf.viewer != first.viewer) {
+				None
+			} else if count > T::MaxInteractionsPerBlock::get() &&
+				leaves.iter().all(|LeafProof { leaf, .. }| leaf.block == first.block)
+			{
+				Some(RejectionReason::BlockCap)
+			} else if count > T::MaxViewsPerAccountPerDay::get() &&
+				leaves.iter().all(|LeafProof { leaf, .. }| day(leaf.block) == day(first.block))
+			{
+				Some(RejectionReason::DailyCap)
+			} else {
+				None
+			}
+		}
+
+		/// Whether the attestation of `leaf` is signed for its viewer by a client key
+		/// registered by the leaf's block, for a creative version the ad had, and expires no
+		/// earlier than the leaf's block.
+		fn is_attested(leaf: &ViewLeaf<T>) -> bool {
+			let SignedAttestation { attestation, key, signature } = &leaf.attestation;
+			let registered = ClientKeys::<T>::get(key).map_or(false, |at| at <= leaf.block);
+			let creative = T::Ads::creative_version(attestation.ad_id).map_or(false, |version| {
+				(1..=version).contains(&attestation.creative_version)
+			});
+			let message = (ATTESTATION_CONTEXT, &leaf.viewer, attestation).encode();
+			registered && creative && leaf.block <= attestation.expires_at &&
+				attestation.started_at <= attestation.ended_at &&
+				key.verify(signature, &message)
+		}
+	}
+
+	impl<T: Config> crate::CompletedViewProvider<T::AccountId, BlockNumberFor<T>> for Pallet<T> {
+		fn take_completed_view(who: &T::AccountId, ad_id: u32) -> Option<BlockNumberFor<T>> {
+			LastCompletedView::<T>::take(who, ad_id)
+		}
 	}
 }
@@ -0,0 +1,282 @@
+//! Storage migrations for pallet-ad-tracking
+
+use super::*;
+use frame_support::{
+	migrations::VersionedMigration, pallet_prelude::*, traits::UncheckedOnRuntimeUpgrade,
+};
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+#[cfg(feature = "try-runtime")]
+use sp_std::vec::Vec;
+
+/// Storage layout at version 0.
+mod v0 {
+	use super::*;
+
+	/// View record with the timestamp passed by the viewer.
+	#[derive(Encode, Decode)]
+	pub(super) struct ViewRecord<T: Config> {
+		pub ad_id: u32,
+		pub viewer: T::AccountId,
+		pub _timestamp: u64,
+		pub completed: bool,
+	}
+
+	/// `ViewRecords` at storage version 0.
+	#[cfg(any(test, feature = "try-runtime"))]
+	#[frame_support::storage_alias]
+	pub(super) type ViewRecords<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, u32, ViewRecord<T>>;
+}
+
+/// Version 1 records the block and `pallet_timestamp` moment at which views start and
+/// complete instead of a timestamp passed by the viewer.
+pub mod v1 {
+	use super::*;
+	use sp_runtime::traits::{Saturating, Zero};
+
+	/// Translate view records to the new layout.
+	///
+	/// When old views started and completed is unknown, so they are recorded at block zero.
+	/// Completed views stay completed; views still in progress are dropped because their
+	/// watch duration can't be checked.
+	pub struct InnerMigrateV0ToV1<T>(PhantomData<T>);
+
+	impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV0ToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let mut translated = 0u64;
+
+			ViewRecords::<T>::translate::<v0::ViewRecord<T>, _>(|_, old| {
+				translated.saturating_inc();
+				let unknown = ViewTime { block: Zero::zero(), moment: Zero::zero() };
+				old.completed.then(|| ViewRecord {
+					ad_id: old.ad_id,
+					viewer: old.viewer,
+					started: unknown.clone(),
+					completed: Some(unknown),
+				})
+			});
+
+			T::DbWeight::get().reads_writes(translated, translated)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			let completed =
+				v0::ViewRecords::<T>::iter_values().filter(|old| old.completed).count() as u32;
+			Ok(completed.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			let completed = u32::decode(&mut &state[..]).map_err(|_| "pre-upgrade state must decode")?;
+
+			ensure!(
+				ViewRecords::<T>::iter_values().filter(|view| view.completed.is_some()).count() as u32 ==
+					completed,
+				"every completed view must be kept"
+			);
+			ensure!(
+				ViewRecords::<T>::iter_values().all(|view| view.completed.is_some()),
+				"views in progress must be dropped"
+			);
+
+			Ok(())
+		}
+	}
+
+	/// Migrate pallet-ad-tracking from storage version 0 to 1.
+	pub type MigrateV0ToV1<T> = VersionedMigration<
+		0,
+		1,
+		InnerMigrateV0ToV1<T>,
+		Pallet<T>,
+		<T as frame_system::Config>::DbWeight,
+	>;
+}
+
+/// Version 2 splits `AdMetrics` into raw, billable and rejected views and clicks.
+pub mod v2 {
+	use super::*;
+	use sp_runtime::traits::Saturating;
+
+	/// Ad metrics before rate limiting.
+	#[derive(Encode, Decode)]
+	pub(super) struct OldAdMetrics {
+		pub total_views: u64,
+		pub total_clicks: u64,
+		pub unique_viewers: u64,
+	}
+
+	/// Count all views and clicks recorded so far as billable.
+	pub struct InnerMigrateV1ToV2<T>(PhantomData<T>);
+
+	impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV1ToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let mut translated = 0u64;
+
+			AdMetricsStorage::<T>::translate::<OldAdMetrics, _>(|_, old| {
+				translated.saturating_inc();
+				Some(AdMetrics {
+					total_views: old.total_views,
+					billable_views: old.total_views,
+					rejected_views: 0,
+					total_clicks: old.total_clicks,
+					billable_clicks: old.total_clicks,
+					rejected_clicks: 0,
+					unique_viewers: old.unique_viewers,
+				})
+			});
+
+			T::DbWeight::get().reads_writes(translated, translated)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			Ok((AdMetricsStorage::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			let ads = u32::decode(&mut &state[..]).map_err(|_| "pre-upgrade state must decode")?;
+
+			ensure!(
+				AdMetricsStorage::<T>::iter_values()
+					.filter(|metrics| metrics.billable_views == metrics.total_views)
+					.count() as u32 ==
+					ads,
+				"every ad must decode with all its views billable"
+			);
+
+			Ok(())
+		}
+	}
+
+	/// Migrate pallet-ad-tracking from storage version 1 to 2.
+	pub type MigrateV1ToV2<T> = VersionedMigration<
+		1,
+		2,
+		InnerMigrateV1ToV2<T>,
+		Pallet<T>,
+		<T as frame_system::Config>::DbWeight,
+	>;
+
+	/// `UsedAttestationNonces` at storage version 2, by client key.
+	#[frame_support::storage_alias]
+	pub(super) type UsedAttestationNonces<T: Config> =
+		StorageDoubleMap<Pallet<T>, Blake2_128Concat, ClientKey, Twox64Concat, u64, ()>;
+}
+
+/// Version 3 keeps attestation nonces by the block the attestations expire at, so that they
+/// can be pruned.
+pub mod v3 {
+	use super::*;
+
+	/// Remove the nonces of attestations without expiry.
+	///
+	/// Attestations signed before the upgrade have no `expires_at` and can no longer be used,
+	/// so their nonces don't need to be kept.
+	pub struct InnerMigrateV2ToV3<T>(PhantomData<T>);
+
+	impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV2ToV3<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let removed = v2::UsedAttestationNonces::<T>::clear(u32::MAX, None).unique;
+
+			T::DbWeight::get().writes(removed.into())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			Ok(Vec::new())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			ensure!(
+				v2::UsedAttestationNonces::<T>::iter_keys().next().is_none(),
+				"every nonce without expiry must be removed"
+			);
+
+			Ok(())
+		}
+	}
+
+	/// Migrate pallet-ad-tracking from storage version 2 to 3.
+	pub type MigrateV2ToV3<T> = VersionedMigration<
+		2,
+		3,
+		InnerMigrateV2ToV3<T>,
+		Pallet<T>,
+		<T as frame_system::Config>::DbWeight,
+	>;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{new_test_ext, Test, ALICE, BOB};
+	use frame_support::{storage::unhashed, traits::OnRuntimeUpgrade};
+
+	#[test]
+	fn baseline_storage_migrates_to_v2() {
+		new_test_ext().execute_with(|| {
+			StorageVersion::new(0).put::<Pallet<Test>>();
+			let view = |viewer, completed| v0::ViewRecord::<Test> {
+				ad_id: 0,
+				viewer,
+				_timestamp: 1_000,
+				completed,
+			};
+			v0::ViewRecords::<Test>::insert(0, view(ALICE, true));
+			v0::ViewRecords::<Test>::insert(1, view(BOB, false));
+			unhashed::put(
+				&AdMetricsStorage::<Test>::hashed_key_for(0),
+				&v2::OldAdMetrics { total_views: 2, total_clicks: 1, unique_viewers: 2 },
+			);
+
+			v1::MigrateV0ToV1::<Test>::on_runtime_upgrade();
+			v2::MigrateV1ToV2::<Test>::on_runtime_upgrade();
+
+			assert_eq!(StorageVersion::get::<Pallet<Test>>(), 2);
+			let unknown = ViewTime { block: 0, moment: 0 };
+			assert_eq!(
+				ViewRecords::<Test>::get(0),
+				Some(ViewRecord {
+					ad_id: 0,
+					viewer: ALICE,
+					started: unknown.clone(),
+					completed: Some(unknown)
+				})
+			);
+			assert!(!ViewRecords::<Test>::contains_key(1));
+			assert_eq!(
+				AdMetricsStorage::<Test>::get(0),
+				AdMetrics {
+					total_views: 2,
+					billable_views: 2,
+					rejected_views: 0,
+					total_clicks: 1,
+					billable_clicks: 1,
+					rejected_clicks: 0,
+					unique_viewers: 2
+				}
+			);
+		});
+	}
+
+	#[test]
+	fn nonces_without_expiry_are_removed_by_v3() {
+		new_test_ext().execute_with(|| {
+			StorageVersion::new(2).put::<Pallet<Test>>();
+			let key = ClientKey::Sr25519(sp_core::sr25519::Public::from_raw([1; 32]));
+			v2::UsedAttestationNonces::<Test>::insert(&key, 0, ());
+			v2::UsedAttestationNonces::<Test>::insert(&key, 1, ());
+
+			v3::MigrateV2ToV3::<Test>::on_runtime_upgrade();
+
+			assert_eq!(StorageVersion::get::<Pallet<Test>>(), 3);
+			assert_eq!(v2::UsedAttestationNonces::<Test>::iter_keys().count(), 0);
+			assert_eq!(UsedAttestationNonces::<Test>::iter_keys().count(), 0);
+		});
+	}
+}
@@ -0,0 +1,174 @@
+//! Test environment for pallet-ad-tracking
+
+use crate as pallet_ad_tracking;
+use frame_support::{
+	assert_ok, derive_impl, parameter_types,
+	traits::{ConstU32, ConstU64, Currency},
+};
+use frame_system::{EnsureRoot, EnsureSigned};
+use sp_keystore::{testing::MemoryKeystore, KeystoreExt};
+use sp_runtime::{BuildStorage, Perbill};
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+pub const ALICE: u64 = 1;
+pub const BOB: u64 = 2;
+pub const AGGREGATOR: u64 = 3;
+
+/// Free balance of every test account at genesis.
+pub const INITIAL_BALANCE: u64 = 10_000;
+
+/// Bond reserved from aggregators.
+pub const AGGREGATOR_BOND: u64 = 1_000;
+
+/// Length of the creatives of test ads in milliseconds.
+pub const CREATIVE_DURATION: u32 = 10_000;
+
+/// `pallet_timestamp` moment at genesis.
+pub const GENESIS_MOMENT: u64 = 1_000_000;
+
+frame_support::construct_runtime!(
+	pub enum Test {
+		System: frame_system,
+		Timestamp: pallet_timestamp,
+		Balances: pallet_balances,
+		Ads: pallet_ads,
+		AdTracking: pallet_ad_tracking,
+	}
+);
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+impl frame_system::Config for Test {
+	type Block = Block;
+	type AccountData = pallet_balances::AccountData<u64>;
+}
+
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = ConstU64<1>;
+	type WeightInfo = ();
+}
+
+#[derive_impl(pallet_balances::config_preludes::TestDefaultConfig)]
+impl pallet_balances::Config for Test {
+	type AccountStore = System;
+}
+
+impl pallet_ads::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type MaxAdNameLength = ConstU32<32>;
+	type MaxAdDescriptionLength = ConstU32<64>;
+	type MaxCidLength = ConstU32<64>;
+	type MaxAdvertiserNameLength = ConstU32<32>;
+	type MinAdvertiserDeposit = ConstU64<100>;
+	type AuctionRevenue = ();
+	type MaxAuctionBids = ConstU32<4>;
+	type MaxAuctionsPerBlock = ConstU32<4>;
+	type MaxScheduledAdsPerBlock = ConstU32<4>;
+	type ModeratorOrigin = EnsureRoot<u64>;
+	type MaxRejectionReasonLength = ConstU32<32>;
+	type AppealBond = ConstU64<10>;
+	type GovernanceOrigin = EnsureRoot<u64>;
+	type OnSlash = ();
+	type MaxSuspensionsPerBlock = ConstU32<4>;
+	type MaxCreativeHistory = ConstU32<2>;
+	type WithdrawalCooldown = ConstU64<10>;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const MinWatchRatio: Perbill = Perbill::from_percent(80);
+	pub static BatchPeriod: u64 = 10;
+}
+
+impl pallet_ad_tracking::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Ads = Ads;
+	type MinWatchDuration = ConstU64<5_000>;
+	type MinWatchRatio = MinWatchRatio;
+	type ViewCooldown = ConstU64<10>;
+	type ClickCooldown = ConstU64<5>;
+	type MaxInteractionsPerBlock = ConstU32<2>;
+	type MaxViewsPerAccountPerDay = ConstU32<3>;
+	type BlocksPerDay = ConstU64<100>;
+	type ClientKeyOrigin = EnsureRoot<u64>;
+	type MaxAttestationLifetime = ConstU64<10>;
+	type MaxAttestationsPerBlock = ConstU32<2>;
+	type Currency = Balances;
+	type AggregatorOrigin = EnsureSigned<u64>;
+	type AggregatorBond = ConstU64<AGGREGATOR_BOND>;
+	type BatchPeriod = BatchPeriod;
+	type ChallengePeriod = ConstU64<20>;
+	type MaxAdsPerBatch = ConstU32<4>;
+	type MaxProofLength = ConstU32<8>;
+	type OnSlash = ();
+	type WeightInfo = ();
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = BenchmarkHelper;
+}
+
+/// Creates the ads the benchmarks record views and clicks of.
+#[cfg(feature = "runtime-benchmarks")]
+pub struct BenchmarkHelper;
+
+#[cfg(feature = "runtime-benchmarks")]
+impl crate::BenchmarkHelper for BenchmarkHelper {
+	fn create_ad() -> u32 {
+		create_ad()
+	}
+}
+
+/// Create an approved, funded ad with a creative of `CREATIVE_DURATION` and return its id.
+///
+/// Each ad gets its own advertiser and spot.
+pub fn create_ad() -> u32 {
+	let spot_id = pallet_ads::NextSpotId::<Test>::get();
+	let ad_id = pallet_ads::NextAdId::<Test>::get();
+	let advertiser = 1_000 + u64::from(ad_id);
+	let origin = RuntimeOrigin::signed(advertiser);
+	Balances::make_free_balance_be(&advertiser, 1_000_000);
+
+	assert_ok!(Ads::create_ad_spot(RuntimeOrigin::root()));
+	assert_ok!(Ads::register_advertiser(origin.clone(), b"Advertiser".to_vec(), 100));
+	assert_ok!(Ads::submit_ad(
+		origin,
+		spot_id,
+		b"Ad".to_vec(),
+		b"An ad".to_vec(),
+		b"QmCid".to_vec(),
+		CREATIVE_DURATION,
+		100_000,
+		None,
+		None,
+	));
+	assert_ok!(Ads::approve_ad(RuntimeOrigin::root(), ad_id));
+	ad_id
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![
+			(ALICE, INITIAL_BALANCE),
+			(BOB, INITIAL_BALANCE),
+			(AGGREGATOR, INITIAL_BALANCE),
+		],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.register_extension(KeystoreExt::new(MemoryKeystore::new()));
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		Timestamp::set_timestamp(GENESIS_MOMENT);
+	});
+	ext
+}
+
+/// Advance the `pallet_timestamp` moment by `ms` milliseconds.
+pub fn advance_time(ms: u64) {
+	Timestamp::set_timestamp(Timestamp::get() + ms);
+}
@@ -0,0 +1,878 @@
+use crate::{
+	mock::*, AdMetricsStorage, Aggregators, Attestation, AttestationsExpiringAt, AttestedViews,
+	Batches, ClientKey, ClientSignature, CompletedViewProvider, Error, Event, InvalidLeaf,
+	LastCompletedView, LeafProof, RejectedAttempts, RejectionReason, SignedAttestation,
+	UsedAttestationNonces, ViewAttestations, ViewLeaf, ATTESTATION_CONTEXT, MERKLE_NODE_PREFIX,
+};
+use codec::Encode;
+use frame_support::{assert_noop, assert_ok, traits::Hooks, BoundedVec};
+use sp_core::{sr25519, Pair, H256};
+use sp_runtime::{traits::Hash, DispatchError, DispatchResult};
+
+type Hashing = <Test as frame_system::Config>::Hashing;
+type Proof = BoundedVec<H256, <Test as crate::Config>::MaxProofLength>;
+
+/// Minimum watch duration of the test ads.
+const MIN_WATCH: u64 = CREATIVE_DURATION as u64 * 8 / 10;
+
+/// Seed of the client key attesting the test leaves.
+const LEAF_KEY: u8 = 9;
+
+/// Block the test attestations expire at.
+const EXPIRES_AT: u64 = 10;
+
+fn client_pair(seed: u8) -> sr25519::Pair {
+	sr25519::Pair::from_seed(&[seed; 32])
+}
+
+fn register_client_key(pair: &sr25519::Pair) -> ClientKey {
+	let key = ClientKey::Sr25519(pair.public());
+	assert_ok!(AdTracking::register_client_key(RuntimeOrigin::root(), key.clone()));
+	key
+}
+
+/// Attestation by `pair` of a view of `ad_id` by `viewer`, expiring at `EXPIRES_AT`.
+fn attest(
+	pair: &sr25519::Pair,
+	viewer: u64,
+	ad_id: u32,
+	started_at: u64,
+	ended_at: u64,
+	nonce: u64,
+) -> SignedAttestation<u64, u64> {
+	let attestation = Attestation {
+		ad_id,
+		creative_version: 1,
+		started_at,
+		ended_at,
+		nonce,
+		expires_at: EXPIRES_AT,
+	};
+	sign(pair, viewer, attestation)
+}
+
+/// `attestation` signed by `pair` for `viewer`.
+fn sign(
+	pair: &sr25519::Pair,
+	viewer: u64,
+	attestation: Attestation<u64, u64>,
+) -> SignedAttestation<u64, u64> {
+	let message = (ATTESTATION_CONTEXT, viewer, &attestation).encode();
+	SignedAttestation {
+		attestation,
+		key: ClientKey::Sr25519(pair.public()),
+		signature: ClientSignature::Sr25519(pair.sign(&message)),
+	}
+}
+
+fn record_view(who: u64, ad_id: u32) -> u32 {
+	let view_id = AdTracking::next_view_id();
+	assert_ok!(AdTracking::record_view(RuntimeOrigin::signed(who), ad_id, None));
+	view_id
+}
+
+/// Register `AGGREGATOR` and the client key attesting the test leaves.
+fn register_aggregator() {
+	assert_ok!(AdTracking::register_aggregator(RuntimeOrigin::signed(AGGREGATOR)));
+	register_client_key(&client_pair(LEAF_KEY));
+}
+
+/// A view of `ad_id` by `viewer` recorded at `block` that lasted `duration` milliseconds,
+/// attested by the `LEAF_KEY` client key.
+fn leaf_of(viewer: u64, ad_id: u32, block: u64, duration: u64) -> ViewLeaf<Test> {
+	let ended_at = GENESIS_MOMENT + duration;
+	let attestation = attest(&client_pair(LEAF_KEY), viewer, ad_id, GENESIS_MOMENT, ended_at, 0);
+	ViewLeaf { viewer, block, attestation }
+}
+
+/// A view of `ad_id` by `BOB`, see [`leaf_of`].
+fn leaf(ad_id: u32, block: u64, duration: u64) -> ViewLeaf<Test> {
+	leaf_of(BOB, ad_id, block, duration)
+}
+
+fn node(left: H256, right: H256) -> H256 {
+	Hashing::hash_of(&(MERKLE_NODE_PREFIX, left, right))
+}
+
+/// Root of the Merkle tree of `leaves` and the proof of the leaf at `index`.
+fn merkle_proof(leaves: &[ViewLeaf<Test>], index: usize) -> (H256, Proof) {
+	let mut level: Vec<H256> = leaves.iter().map(AdTracking::leaf_hash).collect();
+	let mut position = index;
+	let mut proof = Vec::new();
+	while level.len() > 1 {
+		if let Some(sibling) = level.get(position ^ 1) {
+			proof.push(*sibling);
+		}
+		level = level
+			.chunks(2)
+			.map(|pair| match pair {
+				[left, right] => node(*left, *right),
+				[last] => *last,
+				_ => unreachable!("chunks are never empty"),
+			})
+			.collect();
+		position /= 2;
+	}
+	(level[0], proof.try_into().expect("test trees are shallow"))
+}
+
+/// Submit a batch of `leaves` for period zero counting `views`, and return its id.
+fn submit_batch(leaves: &[ViewLeaf<Test>], views: Vec<(u32, u64)>) -> u32 {
+	let batch_id = crate::NextBatchId::<Test>::get();
+	let (root, _) = merkle_proof(leaves, 0);
+	System::set_block_number(10);
+	assert_ok!(AdTracking::submit_batch(
+		RuntimeOrigin::signed(AGGREGATOR),
+		0,
+		root,
+		views.try_into().unwrap()
+	));
+	batch_id
+}
+
+fn challenge(batch_id: u32, leaves: &[ViewLeaf<Test>], index: usize) -> DispatchResult {
+	let (_, proof) = merkle_proof(leaves, index);
+	AdTracking::challenge_batch(
+		RuntimeOrigin::signed(ALICE),
+		batch_id,
+		leaves[index].clone(),
+		index as u64,
+		proof,
+	)
+}
+
+#[test]
+fn record_view_requires_servable_ad() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			AdTracking::record_view(RuntimeOrigin::signed(ALICE), 0, None),
+			Error::<Test>::AdNotFound
+		);
+
+		let ad_id = create_ad();
+		let advertiser = pallet_ads::Ads::<Test>::get(ad_id).unwrap().advertiser;
+		assert_ok!(Ads::deactivate_ad(RuntimeOrigin::signed(advertiser), ad_id));
+		assert_noop!(
+			AdTracking::record_view(RuntimeOrigin::signed(ALICE), ad_id, None),
+			Error::<Test>::AdNotFound
+		);
+		assert_noop!(
+			AdTracking::record_click(RuntimeOrigin::signed(ALICE), ad_id),
+			Error::<Test>::AdNotFound
+		);
+	});
+}
+
+#[test]
+fn record_view_updates_metrics() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad();
+		let view_id = record_view(ALICE, ad_id);
+
+		System::assert_last_event(Event::AdViewRecorded { view_id, ad_id, viewer: ALICE }.into());
+		let record = AdTracking::view_records(view_id).unwrap();
+		assert_eq!(record.started.moment, GENESIS_MOMENT);
+		assert_eq!(record.completed, None);
+
+		System::set_block_number(20);
+		record_view(ALICE, ad_id);
+		record_view(BOB, ad_id);
+		let metrics = AdMetricsStorage::<Test>::get(ad_id);
+		assert_eq!(metrics.total_views, 3);
+		assert_eq!(metrics.billable_views, 3);
+		assert_eq!(metrics.unique_viewers, 2);
+	});
+}
+
+#[test]
+fn complete_view_requires_min_watch_duration() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad();
+		let view_id = record_view(ALICE, ad_id);
+
+		advance_time(MIN_WATCH - 1);
+		assert_noop!(
+			AdTracking::complete_view(RuntimeOrigin::signed(ALICE), view_id, None),
+			Error::<Test>::ViewTooShort
+		);
+
+		advance_time(1);
+		assert_noop!(
+			AdTracking::complete_view(RuntimeOrigin::signed(BOB), view_id, None),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(AdTracking::complete_view(RuntimeOrigin::signed(ALICE), view_id, None));
+		System::assert_last_event(
+			Event::AdViewCompleted { view_id, ad_id, viewer: ALICE, duration: MIN_WATCH }.into(),
+		);
+		assert_noop!(
+			AdTracking::complete_view(RuntimeOrigin::signed(ALICE), view_id, None),
+			Error::<Test>::ViewAlreadyCompleted
+		);
+		assert_noop!(
+			AdTracking::complete_view(RuntimeOrigin::signed(ALICE), view_id + 1, None),
+			Error::<Test>::ViewRecordNotFound
+		);
+	});
+}
+
+#[test]
+fn completed_view_is_taken_once() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad();
+		let view_id = record_view(ALICE, ad_id);
+		advance_time(MIN_WATCH);
+		assert_ok!(AdTracking::complete_view(RuntimeOrigin::signed(ALICE), view_id, None));
+
+		assert_eq!(LastCompletedView::<Test>::get(ALICE, ad_id), Some(1));
+		assert_eq!(AdTracking::take_completed_view(&ALICE, ad_id), Some(1));
+		assert_eq!(AdTracking::take_completed_view(&ALICE, ad_id), None);
+	});
+}
+
+#[test]
+fn view_within_cooldown_is_not_billed() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad();
+		record_view(ALICE, ad_id);
+
+		System::set_block_number(5);
+		let view_id = AdTracking::next_view_id();
+		assert_ok!(AdTracking::record_view(RuntimeOrigin::signed(ALICE), ad_id, None));
+		System::assert_last_event(
+			Event::AdViewRejected { ad_id, viewer: ALICE, reason: RejectionReason::Cooldown }
+				.into(),
+		);
+		assert_eq!(AdTracking::next_view_id(), view_id);
+		assert_eq!(RejectedAttempts::<Test>::get(ALICE), 1);
+		let metrics = AdMetricsStorage::<Test>::get(ad_id);
+		assert_eq!(
+			(metrics.total_views, metrics.billable_views, metrics.rejected_views),
+			(2, 1, 1)
+		);
+
+		System::set_block_number(11);
+		record_view(ALICE, ad_id);
+		assert_eq!(AdMetricsStorage::<Test>::get(ad_id).billable_views, 2);
+	});
+}
+
+#[test]
+fn views_beyond_block_and_daily_caps_are_not_billed() {
+	new_test_ext().execute_with(|| {
+		let ad_ids: Vec<u32> = (0..4).map(|_| create_ad()).collect();
+		record_view(ALICE, ad_ids[0]);
+		record_view(ALICE, ad_ids[1]);
+		assert_ok!(AdTracking::record_view(RuntimeOrigin::signed(ALICE), ad_ids[2], None));
+		System::assert_last_event(
+			Event::AdViewRejected {
+				ad_id: ad_ids[2],
+				viewer: ALICE,
+				reason: RejectionReason::BlockCap,
+			}
+			.into(),
+		);
+
+		System::set_block_number(2);
+		record_view(ALICE, ad_ids[2]);
+		assert_ok!(AdTracking::record_view(RuntimeOrigin::signed(ALICE), ad_ids[3], None));
+		System::assert_last_event(
+			Event::AdViewRejected {
+				ad_id: ad_ids[3],
+				viewer: ALICE,
+				reason: RejectionReason::DailyCap,
+			}
+			.into(),
+		);
+
+		// The daily cap resets the next day
+		System::set_block_number(100);
+		record_view(ALICE, ad_ids[3]);
+		assert_eq!(AdMetricsStorage::<Test>::get(ad_ids[3]).billable_views, 1);
+	});
+}
+
+#[test]
+fn click_within_cooldown_is_not_billed() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad();
+		assert_ok!(AdTracking::record_click(RuntimeOrigin::signed(ALICE), ad_id));
+		assert_ok!(AdTracking::record_click(RuntimeOrigin::signed(ALICE), ad_id));
+		System::assert_last_event(
+			Event::AdClickRejected { ad_id, viewer: ALICE, reason: RejectionReason::Cooldown }
+				.into(),
+		);
+
+		System::set_block_number(6);
+		assert_ok!(AdTracking::record_click(RuntimeOrigin::signed(ALICE), ad_id));
+		assert_eq!(AdTracking::click_records(ad_id), 2);
+		let metrics = AdMetricsStorage::<Test>::get(ad_id);
+		assert_eq!((metrics.total_clicks, metrics.rejected_clicks), (3, 1));
+	});
+}
+
+#[test]
+fn client_keys_are_managed_by_origin() {
+	new_test_ext().execute_with(|| {
+		let key = ClientKey::Sr25519(client_pair(1).public());
+		assert_noop!(
+			AdTracking::register_client_key(RuntimeOrigin::signed(ALICE), key.clone()),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(AdTracking::register_client_key(RuntimeOrigin::root(), key.clone()));
+		assert_noop!(
+			AdTracking::register_client_key(RuntimeOrigin::root(), key.clone()),
+			Error::<Test>::ClientKeyAlreadyRegistered
+		);
+
+		assert_ok!(AdTracking::revoke_client_key(RuntimeOrigin::root(), key.clone()));
+		assert_noop!(
+			AdTracking::revoke_client_key(RuntimeOrigin::root(), key),
+			Error::<Test>::UnknownClientKey
+		);
+	});
+}
+
+#[test]
+fn attested_view_is_recorded() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad();
+		let pair = client_pair(1);
+		let signed = attest(&pair, ALICE, ad_id, GENESIS_MOMENT - 2_000, GENESIS_MOMENT, 0);
+		assert_noop!(
+			AdTracking::record_view(RuntimeOrigin::signed(ALICE), ad_id, Some(signed.clone())),
+			Error::<Test>::UnknownClientKey
+		);
+
+		let key = register_client_key(&pair);
+		let view_id = AdTracking::next_view_id();
+		assert_ok!(AdTracking::record_view(RuntimeOrigin::signed(ALICE), ad_id, Some(signed)));
+		assert_eq!(ViewAttestations::<Test>::get(view_id), Some(key));
+
+		// Nonces can't be replayed, not even for another viewer
+		System::set_block_number(5);
+		let replay = attest(&pair, BOB, ad_id, GENESIS_MOMENT - 2_000, GENESIS_MOMENT, 0);
+		assert_noop!(
+			AdTracking::record_view(RuntimeOrigin::signed(BOB), ad_id, Some(replay)),
+			Error::<Test>::AttestationNonceUsed
+		);
+	});
+}
+
+#[test]
+fn attestations_expire_and_their_nonces_are_pruned() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad();
+		let pair = client_pair(1);
+		let key = register_client_key(&pair);
+		let attestation = |nonce, expires_at| {
+			let signed = attest(&pair, ALICE, ad_id, GENESIS_MOMENT - 2_000, GENESIS_MOMENT, nonce);
+			sign(&pair, ALICE, Attestation { expires_at, ..signed.attestation })
+		};
+		let record =
+			|signed| AdTracking::record_view(RuntimeOrigin::signed(ALICE), ad_id, Some(signed));
+
+		// Attestations expire at most `MaxAttestationLifetime` blocks from now
+		assert_noop!(record(attestation(0, 12)), Error::<Test>::InvalidAttestationExpiry);
+
+		// At most `MaxAttestationsPerBlock` attestations expire at the same block
+		assert_ok!(record(attestation(0, 3)));
+		assert_ok!(record(attestation(1, 3)));
+		assert_noop!(record(attestation(2, 3)), Error::<Test>::TooManyAttestations);
+		assert_eq!(AttestationsExpiringAt::<Test>::get(3), 2);
+		assert!(UsedAttestationNonces::<Test>::contains_key(3, (key.clone(), 0)));
+
+		// The nonces are pruned once the attestations expired, which can't be replayed
+		System::set_block_number(4);
+		AdTracking::on_initialize(4);
+		assert_eq!(AttestationsExpiringAt::<Test>::get(3), 0);
+		assert!(!UsedAttestationNonces::<Test>::contains_key(3, (key, 0)));
+		assert_noop!(record(attestation(0, 3)), Error::<Test>::InvalidAttestationExpiry);
+	});
+}
+
+#[test]
+fn attestation_must_be_signed_for_the_viewer() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad();
+		let pair = client_pair(1);
+		register_client_key(&pair);
+
+		let for_bob = attest(&pair, BOB, ad_id, GENESIS_MOMENT - 2_000, GENESIS_MOMENT, 0);
+		assert_noop!(
+			AdTracking::record_view(RuntimeOrigin::signed(ALICE), ad_id, Some(for_bob)),
+			Error::<Test>::InvalidAttestation
+		);
+
+		let mut forged =
+			attest(&client_pair(2), ALICE, ad_id, GENESIS_MOMENT - 2_000, GENESIS_MOMENT, 0);
+		forged.key = ClientKey::Sr25519(pair.public());
+		assert_noop!(
+			AdTracking::record_view(RuntimeOrigin::signed(ALICE), ad_id, Some(forged)),
+			Error::<Test>::InvalidAttestation
+		);
+	});
+}
+
+#[test]
+fn attestation_must_match_the_view() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad();
+		let other_ad = create_ad();
+		let pair = client_pair(1);
+		register_client_key(&pair);
+		let since = GENESIS_MOMENT - u64::from(CREATIVE_DURATION);
+
+		for (attested_ad, started_at, ended_at) in [
+			// Another ad
+			(other_ad, since, GENESIS_MOMENT),
+			// Playback started before the creative could have started playing
+			(ad_id, since - 1, GENESIS_MOMENT),
+			// Playback ended in the future
+			(ad_id, since, GENESIS_MOMENT + 1),
+			// Playback ended before it started
+			(ad_id, GENESIS_MOMENT, since),
+		] {
+			let signed = attest(&pair, ALICE, attested_ad, started_at, ended_at, 0);
+			assert_noop!(
+				AdTracking::record_view(RuntimeOrigin::signed(ALICE), ad_id, Some(signed)),
+				Error::<Test>::AttestationMismatch
+			);
+		}
+
+		let signed = attest(&pair, ALICE, ad_id, since, GENESIS_MOMENT, 0);
+		assert_ok!(AdTracking::record_view(RuntimeOrigin::signed(ALICE), ad_id, Some(signed)));
+	});
+}
+
+#[test]
+fn attested_completion_counts_attested_views() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad();
+		let pair = client_pair(1);
+		let key = register_client_key(&pair);
+		let view_id = record_view(ALICE, ad_id);
+		advance_time(MIN_WATCH + 1_000);
+		let now = GENESIS_MOMENT + MIN_WATCH + 1_000;
+
+		let early = attest(&pair, ALICE, ad_id, GENESIS_MOMENT - 1, now, 0);
+		assert_noop!(
+			AdTracking::complete_view(RuntimeOrigin::signed(ALICE), view_id, Some(early)),
+			Error::<Test>::AttestationMismatch
+		);
+		let short = attest(&pair, ALICE, ad_id, GENESIS_MOMENT, GENESIS_MOMENT + MIN_WATCH - 1, 0);
+		assert_noop!(
+			AdTracking::complete_view(RuntimeOrigin::signed(ALICE), view_id, Some(short)),
+			Error::<Test>::ViewTooShort
+		);
+
+		let signed = attest(&pair, ALICE, ad_id, GENESIS_MOMENT, GENESIS_MOMENT + MIN_WATCH, 0);
+		assert_ok!(AdTracking::complete_view(RuntimeOrigin::signed(ALICE), view_id, Some(signed)));
+		assert_eq!(ViewAttestations::<Test>::get(view_id), Some(key));
+		assert_eq!(AttestedViews::<Test>::get(ad_id), 1);
+	});
+}
+
+#[test]
+fn aggregator_bond_is_reserved_until_deregistration() {
+	new_test_ext().execute_with(|| {
+		register_aggregator();
+		assert_eq!(Balances::reserved_balance(AGGREGATOR), AGGREGATOR_BOND);
+		assert_noop!(
+			AdTracking::register_aggregator(RuntimeOrigin::signed(AGGREGATOR)),
+			Error::<Test>::AggregatorAlreadyRegistered
+		);
+
+		assert_ok!(AdTracking::deregister_aggregator(RuntimeOrigin::signed(AGGREGATOR)));
+		assert_eq!(Balances::reserved_balance(AGGREGATOR), 0);
+		assert_noop!(
+			AdTracking::deregister_aggregator(RuntimeOrigin::signed(AGGREGATOR)),
+			Error::<Test>::NotAggregator
+		);
+	});
+}
+
+#[test]
+fn submit_batch_adds_views_to_metrics() {
+	new_test_ext().execute_with(|| {
+		let (first, second) = (create_ad(), create_ad());
+		register_aggregator();
+		let leaves =
+			[leaf(first, 5, MIN_WATCH), leaf(first, 6, MIN_WATCH), leaf(second, 7, MIN_WATCH)];
+		let batch_id = submit_batch(&leaves, vec![(first, 2), (second, 1)]);
+
+		let batch = Batches::<Test>::get(batch_id).unwrap();
+		assert_eq!(batch.leaf_count, 3);
+		assert_eq!(batch.challenge_ends, 30);
+		assert_eq!(AdMetricsStorage::<Test>::get(first).billable_views, 2);
+		assert_eq!(AdMetricsStorage::<Test>::get(second).total_views, 1);
+		assert_eq!(Aggregators::<Test>::get(AGGREGATOR).unwrap().pending_batches, 1);
+
+		// One batch per period
+		assert_noop!(
+			AdTracking::submit_batch(
+				RuntimeOrigin::signed(AGGREGATOR),
+				0,
+				batch.root,
+				vec![(first, 1)].try_into().unwrap()
+			),
+			Error::<Test>::InvalidPeriod
+		);
+	});
+}
+
+#[test]
+fn submit_batch_checks_views() {
+	new_test_ext().execute_with(|| {
+		let (first, second) = (create_ad(), create_ad());
+		System::set_block_number(10);
+		let submit = |period: u64, views: Vec<(u32, u64)>| {
+			AdTracking::submit_batch(
+				RuntimeOrigin::signed(AGGREGATOR),
+				period,
+				H256::zero(),
+				views.try_into().unwrap(),
+			)
+		};
+		assert_noop!(submit(0, vec![(first, 1)]), Error::<Test>::NotAggregator);
+
+		register_aggregator();
+		assert_noop!(submit(1, vec![(first, 1)]), Error::<Test>::InvalidPeriod);
+		assert_noop!(submit(0, vec![]), Error::<Test>::EmptyBatch);
+		assert_noop!(submit(0, vec![(first, 0)]), Error::<Test>::InvalidBatchViews);
+		assert_noop!(submit(0, vec![(second, 1), (first, 1)]), Error::<Test>::InvalidBatchViews);
+		assert_noop!(submit(0, vec![(first, 1), (first, 1)]), Error::<Test>::InvalidBatchViews);
+		assert_noop!(submit(0, vec![(second + 1, 1)]), Error::<Test>::AdNotFound);
+		let advertiser = pallet_ads::Ads::<Test>::get(second).unwrap().advertiser;
+		assert_ok!(Ads::deactivate_ad(RuntimeOrigin::signed(advertiser), second));
+		assert_noop!(submit(0, vec![(first, 1), (second, 1)]), Error::<Test>::AdNotFound);
+		// A tree of `MaxProofLength` levels holds 256 leaves
+		assert_noop!(submit(0, vec![(first, 257)]), Error::<Test>::BatchTooLarge);
+		assert_ok!(submit(0, vec![(first, 256)]));
+	});
+}
+
+#[test]
+fn merkle_root_promotes_the_last_node_of_odd_levels() {
+	new_test_ext().execute_with(|| {
+		let leaves = [leaf(0, 5, MIN_WATCH), leaf(0, 6, MIN_WATCH), leaf(0, 7, MIN_WATCH)];
+		let hashes: Vec<H256> = leaves.iter().map(AdTracking::leaf_hash).collect();
+		let root = node(node(hashes[0], hashes[1]), hashes[2]);
+		let (expected, proof) = merkle_proof(&leaves, 2);
+		assert_eq!(root, expected);
+		assert_eq!(proof.to_vec(), vec![node(hashes[0], hashes[1])]);
+
+		assert_eq!(AdTracking::merkle_root(hashes[2], 2, 3, &proof), Some(root));
+		assert_eq!(AdTracking::merkle_root(hashes[0], 0, 3, &[hashes[1], hashes[2]]), Some(root));
+		// Leaves beyond the count and proofs with extra hashes lead nowhere
+		assert_eq!(AdTracking::merkle_root(hashes[2], 3, 3, &proof), None);
+		assert_eq!(AdTracking::merkle_root(hashes[2], 2, 3, &[proof[0], hashes[0]]), None);
+	});
+}
+
+#[test]
+fn challenge_with_view_of_wrong_ad_slashes_aggregator() {
+	new_test_ext().execute_with(|| {
+		let (first, second) = (create_ad(), create_ad());
+		register_aggregator();
+		// The last leaf is counted as a view of `second` but is a view of `first`
+		let leaves =
+			[leaf(first, 5, MIN_WATCH), leaf(first, 6, MIN_WATCH), leaf(first, 7, MIN_WATCH)];
+		let batch_id = submit_batch(&leaves, vec![(first, 2), (second, 1)]);
+
+		assert_noop!(challenge(batch_id, &leaves, 1), Error::<Test>::LeafValid);
+		assert_ok!(challenge(batch_id, &leaves, 2));
+		System::assert_last_event(
+			Event::BatchSlashed {
+				batch_id,
+				aggregator: AGGREGATOR,
+				challenger: ALICE,
+				reason: InvalidLeaf::WrongAd,
+				slashed: AGGREGATOR_BOND,
+			}
+			.into(),
+		);
+
+		assert!(!Batches::<Test>::contains_key(batch_id));
+		assert_eq!(AdMetricsStorage::<Test>::get(first).total_views, 0);
+		assert_eq!(AdMetricsStorage::<Test>::get(second).billable_views, 0);
+		assert_eq!(Balances::total_balance(&AGGREGATOR), INITIAL_BALANCE - AGGREGATOR_BOND);
+		let info = Aggregators::<Test>::get(AGGREGATOR).unwrap();
+		assert!(info.slashed);
+		assert_eq!(info.pending_batches, 0);
+
+		// Slashed aggregators can't submit batches
+		System::set_block_number(20);
+		assert_noop!(
+			AdTracking::submit_batch(
+				RuntimeOrigin::signed(AGGREGATOR),
+				1,
+				H256::zero(),
+				vec![(first, 1)].try_into().unwrap()
+			),
+			Error::<Test>::NotAggregator
+		);
+	});
+}
+
+#[test]
+fn challenge_with_too_short_or_outside_view_slashes_aggregator() {
+	for (invalid, reason) in [
+		(leaf(0, 7, MIN_WATCH - 1), InvalidLeaf::TooShort),
+		(leaf(0, 10, MIN_WATCH), InvalidLeaf::OutsidePeriod),
+	] {
+		new_test_ext().execute_with(|| {
+			let ad_id = create_ad();
+			register_aggregator();
+			let leaves = [leaf(ad_id, 5, MIN_WATCH), invalid.clone()];
+			let batch_id = submit_batch(&leaves, vec![(ad_id, 2)]);
+
+			assert_ok!(challenge(batch_id, &leaves, 1));
+			System::assert_last_event(
+				Event::BatchSlashed {
+					batch_id,
+					aggregator: AGGREGATOR,
+					challenger: ALICE,
+					reason,
+					slashed: AGGREGATOR_BOND,
+				}
+				.into(),
+			);
+		});
+	}
+}
+
+#[test]
+fn challenge_with_unattested_view_slashes_aggregator() {
+	let unregistered_key = || ViewLeaf {
+		attestation: attest(&client_pair(1), BOB, 0, GENESIS_MOMENT, GENESIS_MOMENT + MIN_WATCH, 0),
+		..leaf(0, 5, MIN_WATCH)
+	};
+	let other_viewer = || ViewLeaf { viewer: ALICE, ..leaf(0, 5, MIN_WATCH) };
+	// The key was registered at block 1
+	let before_key = || leaf(0, 0, MIN_WATCH);
+	let unknown_viewer = || leaf_of(42, 0, 5, MIN_WATCH);
+	for (invalid, reason) in [
+		(unregistered_key(), InvalidLeaf::Unattested),
+		(other_viewer(), InvalidLeaf::Unattested),
+		(before_key(), InvalidLeaf::Unattested),
+		(unknown_viewer(), InvalidLeaf::UnknownViewer),
+	] {
+		new_test_ext().execute_with(|| {
+			let ad_id = create_ad();
+			register_aggregator();
+			let leaves = [leaf(ad_id, 5, MIN_WATCH), invalid.clone()];
+			let batch_id = submit_batch(&leaves, vec![(ad_id, 2)]);
+
+			assert_noop!(challenge(batch_id, &leaves, 0), Error::<Test>::LeafValid);
+			assert_ok!(challenge(batch_id, &leaves, 1));
+			System::assert_last_event(
+				Event::BatchSlashed {
+					batch_id,
+					aggregator: AGGREGATOR,
+					challenger: ALICE,
+					reason,
+					slashed: AGGREGATOR_BOND,
+				}
+				.into(),
+			);
+		});
+	}
+}
+
+#[test]
+fn challenge_with_duplicate_view_slashes_aggregator() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad();
+		register_aggregator();
+		let leaves = [
+			leaf_of(ALICE, ad_id, 5, MIN_WATCH),
+			leaf(ad_id, 6, MIN_WATCH),
+			leaf(ad_id, 7, MIN_WATCH),
+		];
+		let batch_id = submit_batch(&leaves, vec![(ad_id, 3)]);
+		let leaf_proof = |index: usize| LeafProof {
+			leaf: leaves[index].clone(),
+			index: index as u64,
+			proof: merkle_proof(&leaves, index).1,
+		};
+		let challenge_duplicate = |first: usize, second: usize| {
+			AdTracking::challenge_duplicate(
+				RuntimeOrigin::signed(ALICE),
+				batch_id,
+				leaf_proof(first),
+				leaf_proof(second),
+			)
+		};
+
+		assert_noop!(challenge_duplicate(0, 1), Error::<Test>::LeafValid);
+		assert_noop!(challenge_duplicate(1, 1), Error::<Test>::SameLeaf);
+		assert_noop!(
+			AdTracking::challenge_duplicate(
+				RuntimeOrigin::signed(ALICE),
+				batch_id,
+				leaf_proof(1),
+				LeafProof { index: 0, ..leaf_proof(2) }
+			),
+			Error::<Test>::InvalidMerkleProof
+		);
+		assert_ok!(challenge_duplicate(1, 2));
+		System::assert_last_event(
+			Event::BatchSlashed {
+				batch_id,
+				aggregator: AGGREGATOR,
+				challenger: ALICE,
+				reason: InvalidLeaf::Duplicate,
+				slashed: AGGREGATOR_BOND,
+			}
+			.into(),
+		);
+		assert_eq!(AdMetricsStorage::<Test>::get(ad_id).billable_views, 0);
+	});
+}
+
+#[test]
+fn challenge_with_rate_limited_views_slashes_aggregator() {
+	// `MaxInteractionsPerBlock` is 2 and `MaxViewsPerAccountPerDay` 3
+	for (blocks, reason) in
+		[(vec![5, 5, 5], RejectionReason::BlockCap), (vec![2, 5, 5, 7], RejectionReason::DailyCap)]
+	{
+		new_test_ext().execute_with(|| {
+			let ad_ids: Vec<u32> = blocks.iter().map(|_| create_ad()).collect();
+			register_aggregator();
+			let leaves: Vec<_> = ad_ids
+				.iter()
+				.zip(&blocks)
+				.map(|(ad_id, block)| leaf(*ad_id, *block, MIN_WATCH))
+				.collect();
+			let batch_id = submit_batch(&leaves, ad_ids.iter().map(|ad_id| (*ad_id, 1)).collect());
+			let challenge_rate_limit = |indices: &[usize]| {
+				let proofs: Vec<_> = indices
+					.iter()
+					.map(|&index| LeafProof {
+						leaf: leaves[index].clone(),
+						index: index as u64,
+						proof: merkle_proof(&leaves, index).1,
+					})
+					.collect();
+				AdTracking::challenge_rate_limit(
+					RuntimeOrigin::signed(ALICE),
+					batch_id,
+					proofs.try_into().unwrap(),
+				)
+			};
+			let all: Vec<usize> = (0..leaves.len()).collect();
+
+			assert_noop!(challenge_rate_limit(&all[1..]), Error::<Test>::LeafValid);
+			assert_noop!(challenge_rate_limit(&[0, 0, 1]), Error::<Test>::SameLeaf);
+			assert_ok!(challenge_rate_limit(&all));
+			System::assert_last_event(
+				Event::BatchSlashed {
+					batch_id,
+					aggregator: AGGREGATOR,
+					challenger: ALICE,
+					reason: InvalidLeaf::RateLimited(reason),
+					slashed: AGGREGATOR_BOND,
+				}
+				.into(),
+			);
+		});
+	}
+}
+
+#[test]
+fn challenge_requires_a_valid_proof() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad();
+		register_aggregator();
+		let leaves = [leaf(ad_id, 5, MIN_WATCH), leaf(ad_id, 7, 0)];
+		let batch_id = submit_batch(&leaves, vec![(ad_id, 2)]);
+		let (_, proof) = merkle_proof(&leaves, 1);
+
+		assert_noop!(
+			AdTracking::challenge_batch(
+				RuntimeOrigin::signed(ALICE),
+				batch_id,
+				leaves[1].clone(),
+				0,
+				proof.clone()
+			),
+			Error::<Test>::InvalidMerkleProof
+		);
+		assert_noop!(
+			AdTracking::challenge_batch(
+				RuntimeOrigin::signed(ALICE),
+				batch_id,
+				leaf(ad_id, 7, 1),
+				1,
+				proof.clone()
+			),
+			Error::<Test>::InvalidMerkleProof
+		);
+		assert_noop!(
+			AdTracking::challenge_batch(
+				RuntimeOrigin::signed(ALICE),
+				batch_id + 1,
+				leaves[1].clone(),
+				1,
+				proof
+			),
+			Error::<Test>::BatchNotFound
+		);
+	});
+}
+
+#[test]
+fn batch_is_finalized_after_challenge_period() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad();
+		register_aggregator();
+		let leaves = [leaf(ad_id, 5, MIN_WATCH), leaf(ad_id, 7, 0)];
+		let batch_id = submit_batch(&leaves, vec![(ad_id, 2)]);
+
+		assert_noop!(
+			AdTracking::finalize_batch(RuntimeOrigin::signed(ALICE), batch_id),
+			Error::<Test>::ChallengePeriodNotOver
+		);
+		assert_noop!(
+			AdTracking::deregister_aggregator(RuntimeOrigin::signed(AGGREGATOR)),
+			Error::<Test>::PendingBatches
+		);
+
+		System::set_block_number(30);
+		assert_noop!(challenge(batch_id, &leaves, 1), Error::<Test>::ChallengePeriodOver);
+		assert_ok!(AdTracking::finalize_batch(RuntimeOrigin::signed(ALICE), batch_id));
+		assert_eq!(AdMetricsStorage::<Test>::get(ad_id).billable_views, 2);
+		assert_ok!(AdTracking::deregister_aggregator(RuntimeOrigin::signed(AGGREGATOR)));
+		assert_eq!(Balances::free_balance(AGGREGATOR), INITIAL_BALANCE);
+	});
+}
+
+#[test]
+fn zero_batch_period_lasts_one_block() {
+	new_test_ext().execute_with(|| {
+		BatchPeriod::set(0);
+		let ad_id = create_ad();
+		register_aggregator();
+		let leaves = [leaf(ad_id, 1, MIN_WATCH)];
+		let (root, proof) = merkle_proof(&leaves, 0);
+		System::set_block_number(2);
+		assert_ok!(AdTracking::submit_batch(
+			RuntimeOrigin::signed(AGGREGATOR),
+			1,
+			root,
+			vec![(ad_id, 1)].try_into().unwrap()
+		));
+
+		assert_noop!(
+			AdTracking::challenge_batch(
+				RuntimeOrigin::signed(ALICE),
+				0,
+				leaves[0].clone(),
+				0,
+				proof
+			),
+			Error::<Test>::LeafValid
+		);
+	});
+}
@@ -0,0 +1,329 @@
+//! Weights for `pallet_ad_tracking`
+//!
+//! Hand estimates from the storage accesses of each call, listed above each function, and a
+//! rough execution time. Replace them with the output of `polkaads-node benchmark pallet`
+//! once the benchmarks have been run on reference hardware.
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+#![allow(missing_docs)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use core::marker::PhantomData;
+
+/// Weight functions needed for `pallet_ad_tracking`.
+pub trait WeightInfo {
+	fn record_view() -> Weight;
+	fn complete_view() -> Weight;
+	fn record_click() -> Weight;
+	fn register_client_key() -> Weight;
+	fn revoke_client_key() -> Weight;
+	fn register_aggregator() -> Weight;
+	fn deregister_aggregator() -> Weight;
+	fn submit_batch(n: u32, ) -> Weight;
+	fn challenge_batch(n: u32, ) -> Weight;
+	fn challenge_duplicate(n: u32, ) -> Weight;
+	fn challenge_rate_limit(n: u32, l: u32, ) -> Weight;
+	fn finalize_batch() -> Weight;
+	fn prune_attestation_nonces(n: u32, ) -> Weight;
+}
+
+/// Estimated weights for `pallet_ad_tracking`.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: `Ads::Ads` (r:1 w:0)
+	/// Storage: `Ads::Moderation` (r:1 w:0)
+	/// Storage: `AdTracking::NextViewId` (r:1 w:1)
+	/// Storage: `AdTracking::AdMetricsStorage` (r:1 w:1)
+	/// Storage: `AdTracking::UserViews` (r:1 w:1)
+	/// Storage: `AdTracking::ViewRecords` (r:0 w:1)
+	/// Storage: `Timestamp::Now` (r:1 w:0)
+	/// Storage: `AdTracking::LastBillableView` (r:1 w:1)
+	/// Storage: `AdTracking::InteractionsInBlock` (r:1 w:1)
+	/// Storage: `AdTracking::DailyViews` (r:1 w:1)
+	/// Storage: `AdTracking::ClientKeys` (r:1 w:0)
+	/// Storage: `AdTracking::UsedAttestationNonces` (r:1 w:1)
+	/// Storage: `AdTracking::AttestationsExpiringAt` (r:1 w:1)
+	/// Storage: `AdTracking::ViewAttestations` (r:0 w:1)
+	fn record_view() -> Weight {
+		Weight::from_parts(85_000_000, 4312)
+			.saturating_add(T::DbWeight::get().reads(12_u64))
+			.saturating_add(T::DbWeight::get().writes(10_u64))
+	}
+	/// Storage: `AdTracking::ViewRecords` (r:1 w:1)
+	/// Storage: `AdTracking::LastCompletedView` (r:0 w:1)
+	/// Storage: `Timestamp::Now` (r:1 w:0)
+	/// Storage: `Ads::CreativeDurations` (r:1 w:0)
+	/// Storage: `Ads::Ads` (r:1 w:0)
+	/// Storage: `AdTracking::ClientKeys` (r:1 w:0)
+	/// Storage: `AdTracking::UsedAttestationNonces` (r:1 w:1)
+	/// Storage: `AdTracking::AttestationsExpiringAt` (r:1 w:1)
+	/// Storage: `AdTracking::ViewAttestations` (r:1 w:1)
+	/// Storage: `AdTracking::AttestedViews` (r:1 w:1)
+	fn complete_view() -> Weight {
+		Weight::from_parts(73_000_000, 4312)
+			.saturating_add(T::DbWeight::get().reads(9_u64))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:0)
+	/// Storage: `Ads::Moderation` (r:1 w:0)
+	/// Storage: `AdTracking::ClickRecords` (r:1 w:1)
+	/// Storage: `AdTracking::AdMetricsStorage` (r:1 w:1)
+	/// Storage: `AdTracking::LastBillableClick` (r:1 w:1)
+	/// Storage: `AdTracking::InteractionsInBlock` (r:1 w:1)
+	fn record_click() -> Weight {
+		Weight::from_parts(27_000_000, 4312)
+			.saturating_add(T::DbWeight::get().reads(6_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	/// Storage: `AdTracking::ClientKeys` (r:1 w:1)
+	fn register_client_key() -> Weight {
+		Weight::from_parts(13_000_000, 3518)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `AdTracking::ClientKeys` (r:1 w:1)
+	fn revoke_client_key() -> Weight {
+		Weight::from_parts(13_000_000, 3518)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `AdTracking::Aggregators` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn register_aggregator() -> Weight {
+		Weight::from_parts(26_000_000, 3593)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: `AdTracking::Aggregators` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn deregister_aggregator() -> Weight {
+		Weight::from_parts(25_000_000, 3593)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: `AdTracking::Aggregators` (r:1 w:1)
+	/// Storage: `AdTracking::NextBatchId` (r:1 w:1)
+	/// Storage: `AdTracking::Batches` (r:0 w:1)
+	/// Storage: `Ads::Ads` (r:100 w:0)
+	/// Storage: `Ads::Moderation` (r:100 w:0)
+	/// Storage: `AdTracking::AdMetricsStorage` (r:100 w:100)
+	fn submit_batch(n: u32, ) -> Weight {
+		Weight::from_parts(24_000_000, 3539)
+			.saturating_add(Weight::from_parts(11_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 3322).saturating_mul(n.into()))
+	}
+	/// Storage: `AdTracking::Batches` (r:1 w:1)
+	/// Storage: `System::Account` (r:2 w:1)
+	/// Storage: `AdTracking::ClientKeys` (r:1 w:0)
+	/// Storage: `Ads::Ads` (r:1 w:0)
+	/// Storage: `Ads::CreativeDurations` (r:1 w:0)
+	/// Storage: `AdTracking::Aggregators` (r:1 w:1)
+	/// Storage: `AdTracking::AdMetricsStorage` (r:100 w:100)
+	fn challenge_batch(n: u32, ) -> Weight {
+		Weight::from_parts(100_000_000, 4767)
+			.saturating_add(Weight::from_parts(4_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2551).saturating_mul(n.into()))
+	}
+	/// Storage: `AdTracking::Batches` (r:1 w:1)
+	/// Storage: `AdTracking::Aggregators` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Storage: `AdTracking::AdMetricsStorage` (r:100 w:100)
+	fn challenge_duplicate(n: u32, ) -> Weight {
+		Weight::from_parts(60_000_000, 4767)
+			.saturating_add(Weight::from_parts(4_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2551).saturating_mul(n.into()))
+	}
+	/// Storage: `AdTracking::Batches` (r:1 w:1)
+	/// Storage: `AdTracking::Aggregators` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Storage: `AdTracking::AdMetricsStorage` (r:100 w:100)
+	fn challenge_rate_limit(n: u32, l: u32, ) -> Weight {
+		Weight::from_parts(30_000_000, 4767)
+			.saturating_add(Weight::from_parts(4_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(18_000_000, 0).saturating_mul(l.into()))
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2551).saturating_mul(n.into()))
+	}
+	/// Storage: `AdTracking::Batches` (r:1 w:1)
+	/// Storage: `AdTracking::Aggregators` (r:1 w:1)
+	fn finalize_batch() -> Weight {
+		Weight::from_parts(21_000_000, 4767)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: `AdTracking::AttestationsExpiringAt` (r:1 w:1)
+	/// Storage: `AdTracking::UsedAttestationNonces` (r:0 w:256)
+	fn prune_attestation_nonces(n: u32, ) -> Weight {
+		Weight::from_parts(5_000_000, 3507)
+			.saturating_add(Weight::from_parts(1_500_000, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(n.into())))
+	}
+}
+
+// For backwards compatibility and tests.
+impl WeightInfo for () {
+	/// Storage: `Ads::Ads` (r:1 w:0)
+	/// Storage: `Ads::Moderation` (r:1 w:0)
+	/// Storage: `AdTracking::NextViewId` (r:1 w:1)
+	/// Storage: `AdTracking::AdMetricsStorage` (r:1 w:1)
+	/// Storage: `AdTracking::UserViews` (r:1 w:1)
+	/// Storage: `AdTracking::ViewRecords` (r:0 w:1)
+	/// Storage: `Timestamp::Now` (r:1 w:0)
+	/// Storage: `AdTracking::LastBillableView` (r:1 w:1)
+	/// Storage: `AdTracking::InteractionsInBlock` (r:1 w:1)
+	/// Storage: `AdTracking::DailyViews` (r:1 w:1)
+	/// Storage: `AdTracking::ClientKeys` (r:1 w:0)
+	/// Storage: `AdTracking::UsedAttestationNonces` (r:1 w:1)
+	/// Storage: `AdTracking::AttestationsExpiringAt` (r:1 w:1)
+	/// Storage: `AdTracking::ViewAttestations` (r:0 w:1)
+	fn record_view() -> Weight {
+		Weight::from_parts(85_000_000, 4312)
+			.saturating_add(RocksDbWeight::get().reads(12_u64))
+			.saturating_add(RocksDbWeight::get().writes(10_u64))
+	}
+	/// Storage: `AdTracking::ViewRecords` (r:1 w:1)
+	/// Storage: `AdTracking::LastCompletedView` (r:0 w:1)
+	/// Storage: `Timestamp::Now` (r:1 w:0)
+	/// Storage: `Ads::CreativeDurations` (r:1 w:0)
+	/// Storage: `Ads::Ads` (r:1 w:0)
+	/// Storage: `AdTracking::ClientKeys` (r:1 w:0)
+	/// Storage: `AdTracking::UsedAttestationNonces` (r:1 w:1)
+	/// Storage: `AdTracking::AttestationsExpiringAt` (r:1 w:1)
+	/// Storage: `AdTracking::ViewAttestations` (r:1 w:1)
+	/// Storage: `AdTracking::AttestedViews` (r:1 w:1)
+	fn complete_view() -> Weight {
+		Weight::from_parts(73_000_000, 4312)
+			.saturating_add(RocksDbWeight::get().reads(9_u64))
+			.saturating_add(RocksDbWeight::get().writes(6_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:0)
+	/// Storage: `Ads::Moderation` (r:1 w:0)
+	/// Storage: `AdTracking::ClickRecords` (r:1 w:1)
+	/// Storage: `AdTracking::AdMetricsStorage` (r:1 w:1)
+	/// Storage: `AdTracking::LastBillableClick` (r:1 w:1)
+	/// Storage: `AdTracking::InteractionsInBlock` (r:1 w:1)
+	fn record_click() -> Weight {
+		Weight::from_parts(27_000_000, 4312)
+			.saturating_add(RocksDbWeight::get().reads(6_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	/// Storage: `AdTracking::ClientKeys` (r:1 w:1)
+	fn register_client_key() -> Weight {
+		Weight::from_parts(13_000_000, 3518)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `AdTracking::ClientKeys` (r:1 w:1)
+	fn revoke_client_key() -> Weight {
+		Weight::from_parts(13_000_000, 3518)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `AdTracking::Aggregators` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn register_aggregator() -> Weight {
+		Weight::from_parts(26_000_000, 3593)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: `AdTracking::Aggregators` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn deregister_aggregator() -> Weight {
+		Weight::from_parts(25_000_000, 3593)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: `AdTracking::Aggregators` (r:1 w:1)
+	/// Storage: `AdTracking::NextBatchId` (r:1 w:1)
+	/// Storage: `AdTracking::Batches` (r:0 w:1)
+	/// Storage: `Ads::Ads` (r:100 w:0)
+	/// Storage: `Ads::Moderation` (r:100 w:0)
+	/// Storage: `AdTracking::AdMetricsStorage` (r:100 w:100)
+	fn submit_batch(n: u32, ) -> Weight {
+		Weight::from_parts(24_000_000, 3539)
+			.saturating_add(Weight::from_parts(11_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 3322).saturating_mul(n.into()))
+	}
+	/// Storage: `AdTracking::Batches` (r:1 w:1)
+	/// Storage: `System::Account` (r:2 w:1)
+	/// Storage: `AdTracking::ClientKeys` (r:1 w:0)
+	/// Storage: `Ads::Ads` (r:1 w:0)
+	/// Storage: `Ads::CreativeDurations` (r:1 w:0)
+	/// Storage: `AdTracking::Aggregators` (r:1 w:1)
+	/// Storage: `AdTracking::AdMetricsStorage` (r:100 w:100)
+	fn challenge_batch(n: u32, ) -> Weight {
+		Weight::from_parts(100_000_000, 4767)
+			.saturating_add(Weight::from_parts(4_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2551).saturating_mul(n.into()))
+	}
+	/// Storage: `AdTracking::Batches` (r:1 w:1)
+	/// Storage: `AdTracking::Aggregators` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Storage: `AdTracking::AdMetricsStorage` (r:100 w:100)
+	fn challenge_duplicate(n: u32, ) -> Weight {
+		Weight::from_parts(60_000_000, 4767)
+			.saturating_add(Weight::from_parts(4_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2551).saturating_mul(n.into()))
+	}
+	/// Storage: `AdTracking::Batches` (r:1 w:1)
+	/// Storage: `AdTracking::Aggregators` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Storage: `AdTracking::AdMetricsStorage` (r:100 w:100)
+	fn challenge_rate_limit(n: u32, l: u32, ) -> Weight {
+		Weight::from_parts(30_000_000, 4767)
+			.saturating_add(Weight::from_parts(4_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(18_000_000, 0).saturating_mul(l.into()))
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2551).saturating_mul(n.into()))
+	}
+	/// Storage: `AdTracking::Batches` (r:1 w:1)
+	/// Storage: `AdTracking::Aggregators` (r:1 w:1)
+	fn finalize_batch() -> Weight {
+		Weight::from_parts(21_000_000, 4767)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: `AdTracking::AttestationsExpiringAt` (r:1 w:1)
+	/// Storage: `AdTracking::UsedAttestationNonces` (r:0 w:256)
+	fn prune_attestation_nonces(n: u32, ) -> Weight {
+		Weight::from_parts(5_000_000, 3507)
+			.saturating_add(Weight::from_parts(1_500_000, 0).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(n.into())))
+	}
+}
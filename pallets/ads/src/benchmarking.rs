@@ -0,0 +1,480 @@
+//! Benchmarking setup for pallet-ads
+
+use super::*;
+
+use frame_benchmarking::v2::*;
+use frame_support::traits::{Currency, EnsureOrigin, Get};
+use frame_system::{pallet_prelude::BlockNumberFor, RawOrigin};
+use sp_runtime::{traits::Bounded, Perbill};
+use sp_std::{vec, vec::Vec};
+
+type RuntimeOriginOf<T> = <T as frame_system::Config>::RuntimeOrigin;
+
+/// Budget escrowed by benchmark ads.
+const AD_FUNDING: u32 = 1_000_000;
+
+/// Length of benchmark creatives in milliseconds.
+const CREATIVE_DURATION: u32 = 30_000;
+
+fn funded_account<T: Config>(name: &'static str, index: u32) -> T::AccountId {
+	let account: T::AccountId = account(name, index, 0);
+	T::Currency::make_free_balance_be(&account, BalanceOf::<T>::max_value() / 2u32.into());
+	account
+}
+
+fn max_bytes(len: u32) -> Vec<u8> {
+	vec![b'x'; len as usize]
+}
+
+fn registered_advertiser<T: Config>(index: u32) -> T::AccountId {
+	let advertiser = funded_account::<T>("advertiser", index);
+	Pallet::<T>::do_register_advertiser(
+		&advertiser,
+		max_bytes(T::MaxAdvertiserNameLength::get()),
+		T::MinAdvertiserDeposit::get(),
+	)
+	.expect("advertiser is funded; qed");
+	advertiser
+}
+
+/// Register an advertiser whose deposit covers a bid of `bid`.
+fn bidder<T: Config>(index: u32, bid: BalanceOf<T>) -> T::AccountId {
+	let bidder = funded_account::<T>("advertiser", index);
+	Pallet::<T>::do_register_advertiser(
+		&bidder,
+		max_bytes(T::MaxAdvertiserNameLength::get()),
+		T::MinAdvertiserDeposit::get().saturating_add(bid),
+	)
+	.expect("bidder is funded; qed");
+	bidder
+}
+
+/// Create a free ad spot and return its id.
+fn free_spot<T: Config>() -> u32 {
+	Pallet::<T>::do_create_ad_spot();
+	NextSpotId::<T>::get() - 1
+}
+
+fn moderator<T: Config>() -> Result<RuntimeOriginOf<T>, BenchmarkError> {
+	T::ModeratorOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)
+}
+
+/// Submit an ad with maximum length metadata for `advertiser` and return its id.
+///
+/// The ad waits for moderation.
+fn pending_ad<T: Config>(advertiser: &T::AccountId) -> u32 {
+	let spot_id = free_spot::<T>();
+	let ad_id = NextAdId::<T>::get();
+	Pallet::<T>::do_submit_ad(
+		advertiser,
+		spot_id,
+		max_bytes(T::MaxAdNameLength::get()),
+		max_bytes(T::MaxAdDescriptionLength::get()),
+		max_bytes(T::MaxCidLength::get()),
+		CREATIVE_DURATION,
+		AD_FUNDING.into(),
+		None,
+		None,
+	)
+	.expect("advertiser is registered and funded; qed");
+	ad_id
+}
+
+/// Submit and approve an ad for `advertiser` and return its id.
+fn submitted_ad<T: Config>(advertiser: &T::AccountId) -> u32 {
+	let ad_id = pending_ad::<T>(advertiser);
+	Pallet::<T>::do_approve_ad(ad_id).expect("ad is pending; qed");
+	ad_id
+}
+
+/// Submit an ad for `advertiser`, reject it with a maximum length reason and appeal.
+fn appealed_ad<T: Config>(advertiser: &T::AccountId) -> Result<u32, BenchmarkError> {
+	let ad_id = pending_ad::<T>(advertiser);
+	let reason = max_bytes(T::MaxRejectionReasonLength::get());
+	Pallet::<T>::reject_ad(moderator::<T>()?, ad_id, reason)?;
+	Pallet::<T>::appeal_rejection(RawOrigin::Signed(advertiser.clone()).into(), ad_id)?;
+	Ok(ad_id)
+}
+
+/// Open an auction for a new spot with `bids` bids from different advertisers.
+///
+/// Returns the spot id and the block at which the auction ends.
+fn auction_with_bids<T: Config>(bids: u32) -> (u32, BlockNumberFor<T>) {
+	let spot_id = free_spot::<T>();
+	Pallet::<T>::open_spot_auction(RawOrigin::Root.into(), spot_id, 10u32.into(), 100u32.into())
+		.expect("spot is free; qed");
+	for i in 0..bids {
+		let amount: BalanceOf<T> = AD_FUNDING.saturating_mul(i + 1).into();
+		let bidder = bidder::<T>(i + 1, amount);
+		Pallet::<T>::bid_for_spot(RawOrigin::Signed(bidder).into(), spot_id, amount)
+			.expect("bids are increasing and covered by the deposit; qed");
+	}
+	let ends_at = SpotAuctions::<T>::get(spot_id).expect("opened above; qed").ends_at;
+	(spot_id, ends_at)
+}
+
+#[benchmarks]
+mod benchmarks {
+	use super::*;
+
+	#[benchmark]
+	fn create_ad_spot() {
+		let spot_id = NextSpotId::<T>::get();
+
+		#[extrinsic_call]
+		_(RawOrigin::Root);
+
+		assert!(AdSpots::<T>::contains_key(spot_id));
+	}
+
+	#[benchmark]
+	fn register_advertiser() {
+		let caller = funded_account::<T>("caller", 0);
+		let name = max_bytes(T::MaxAdvertiserNameLength::get());
+		let deposit = T::MinAdvertiserDeposit::get();
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), name, deposit);
+
+		assert!(AdvertiserProfiles::<T>::contains_key(&caller));
+	}
+
+	#[benchmark]
+	fn increase_advertiser_deposit() {
+		let caller = registered_advertiser::<T>(0);
+		let amount: BalanceOf<T> = AD_FUNDING.into();
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), amount);
+
+		let profile = AdvertiserProfiles::<T>::get(&caller).expect("registered above; qed");
+		assert_eq!(profile.deposit, T::MinAdvertiserDeposit::get() + amount);
+	}
+
+	#[benchmark]
+	fn deregister_advertiser() {
+		let caller = registered_advertiser::<T>(0);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()));
+
+		assert!(!AdvertiserProfiles::<T>::contains_key(&caller));
+	}
+
+	/// Scheduled start and end, the worst case.
+	#[benchmark]
+	fn submit_ad() {
+		let caller = registered_advertiser::<T>(0);
+		let spot_id = free_spot::<T>();
+		let ad_id = NextAdId::<T>::get();
+		let now = frame_system::Pallet::<T>::block_number();
+
+		#[extrinsic_call]
+		_(
+			RawOrigin::Signed(caller),
+			spot_id,
+			max_bytes(T::MaxAdNameLength::get()),
+			max_bytes(T::MaxAdDescriptionLength::get()),
+			max_bytes(T::MaxCidLength::get()),
+			CREATIVE_DURATION,
+			AD_FUNDING.into(),
+			Some(now + 10u32.into()),
+			Some(now + 100u32.into()),
+		);
+
+		assert!(Ads::<T>::contains_key(ad_id));
+	}
+
+	#[benchmark]
+	fn deactivate_ad() {
+		let caller = registered_advertiser::<T>(0);
+		let ad_id = submitted_ad::<T>(&caller);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), ad_id);
+
+		assert!(!Ads::<T>::get(ad_id).expect("submitted above; qed").active);
+	}
+
+	#[benchmark]
+	fn reclaim_ad_spot() {
+		let advertiser = registered_advertiser::<T>(0);
+		let ad_id = submitted_ad::<T>(&advertiser);
+		let spot_id = Ads::<T>::get(ad_id).expect("submitted above; qed").spot_id;
+
+		#[extrinsic_call]
+		_(RawOrigin::Root, spot_id);
+
+		assert!(AdSpots::<T>::get(spot_id).expect("created above; qed").available);
+	}
+
+	#[benchmark]
+	fn open_spot_auction() {
+		let spot_id = free_spot::<T>();
+
+		#[extrinsic_call]
+		_(RawOrigin::Root, spot_id, 10u32.into(), 100u32.into());
+
+		assert!(SpotAuctions::<T>::contains_key(spot_id));
+	}
+
+	#[benchmark]
+	fn bid_for_spot() {
+		let (spot_id, _) = auction_with_bids::<T>(T::MaxAuctionBids::get().saturating_sub(1));
+		let amount: BalanceOf<T> = AD_FUNDING.saturating_mul(T::MaxAuctionBids::get()).into();
+		let caller = bidder::<T>(0, amount);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), spot_id, amount);
+
+		let auction = SpotAuctions::<T>::get(spot_id).expect("opened above; qed");
+		assert!(auction.bids.contains(&(caller, amount)));
+	}
+
+	/// `b` is the number of bids to settle.
+	#[benchmark]
+	fn close_spot_auction(b: Linear<0, { T::MaxAuctionBids::get() }>) {
+		let (spot_id, ends_at) = auction_with_bids::<T>(b);
+
+		#[block]
+		{
+			Pallet::<T>::do_close_spot_auction(spot_id, ends_at);
+		}
+
+		assert!(!SpotAuctions::<T>::contains_key(spot_id));
+	}
+
+	#[benchmark]
+	fn end_spot_lease() {
+		let (spot_id, ends_at) = auction_with_bids::<T>(1);
+		Pallet::<T>::do_close_spot_auction(spot_id, ends_at);
+		let lease = SpotLeases::<T>::get(spot_id).expect("auction had a bid; qed");
+		let ad_id = NextAdId::<T>::get();
+		Pallet::<T>::do_submit_ad(
+			&lease.holder,
+			spot_id,
+			max_bytes(T::MaxAdNameLength::get()),
+			max_bytes(T::MaxAdDescriptionLength::get()),
+			max_bytes(T::MaxCidLength::get()),
+			CREATIVE_DURATION,
+			AD_FUNDING.into(),
+			None,
+			None,
+		)
+		.expect("holder can place an ad in the leased spot; qed");
+		Pallet::<T>::do_approve_ad(ad_id).expect("ad is pending; qed");
+
+		#[block]
+		{
+			Pallet::<T>::do_end_spot_lease(spot_id, lease.ends_at);
+		}
+
+		assert!(!Ads::<T>::get(ad_id).expect("submitted above; qed").active);
+	}
+
+	#[benchmark]
+	fn start_scheduled_ad() {
+		let advertiser = registered_advertiser::<T>(0);
+		let starts_at = frame_system::Pallet::<T>::block_number() + 10u32.into();
+		let spot_id = free_spot::<T>();
+		let ad_id = NextAdId::<T>::get();
+		Pallet::<T>::do_submit_ad(
+			&advertiser,
+			spot_id,
+			max_bytes(T::MaxAdNameLength::get()),
+			max_bytes(T::MaxAdDescriptionLength::get()),
+			max_bytes(T::MaxCidLength::get()),
+			CREATIVE_DURATION,
+			AD_FUNDING.into(),
+			Some(starts_at),
+			None,
+		)
+		.expect("advertiser is registered and funded; qed");
+		Pallet::<T>::do_approve_ad(ad_id).expect("ad is pending; qed");
+
+		#[block]
+		{
+			Pallet::<T>::do_start_scheduled_ad(ad_id);
+		}
+
+		assert!(Ads::<T>::get(ad_id).expect("submitted above; qed").active);
+	}
+
+	#[benchmark]
+	fn expire_ad() {
+		let advertiser = registered_advertiser::<T>(0);
+		let ad_id = submitted_ad::<T>(&advertiser);
+
+		#[block]
+		{
+			Pallet::<T>::do_expire_ad(ad_id);
+		}
+
+		assert!(!Ads::<T>::get(ad_id).expect("submitted above; qed").active);
+	}
+
+	/// Approving an appealed ad, which also returns the bond, is the worst case.
+	#[benchmark]
+	fn approve_ad() -> Result<(), BenchmarkError> {
+		let advertiser = registered_advertiser::<T>(0);
+		let ad_id = appealed_ad::<T>(&advertiser)?;
+		let origin = moderator::<T>()?;
+
+		#[extrinsic_call]
+		_(origin as RuntimeOriginOf<T>, ad_id);
+
+		assert!(Ads::<T>::get(ad_id).expect("submitted above; qed").active);
+		Ok(())
+	}
+
+	/// Rejecting an appeal, which burns the bond and deactivates the ad, is the worst case.
+	#[benchmark]
+	fn reject_ad() -> Result<(), BenchmarkError> {
+		let advertiser = registered_advertiser::<T>(0);
+		let ad_id = appealed_ad::<T>(&advertiser)?;
+		let origin = moderator::<T>()?;
+		let reason = max_bytes(T::MaxRejectionReasonLength::get());
+
+		#[extrinsic_call]
+		_(origin as RuntimeOriginOf<T>, ad_id, reason);
+
+		assert!(!Pallet::<T>::is_placed(ad_id, &Ads::<T>::get(ad_id).expect("submitted above; qed")));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn appeal_rejection() -> Result<(), BenchmarkError> {
+		let caller = registered_advertiser::<T>(0);
+		let ad_id = pending_ad::<T>(&caller);
+		let reason = max_bytes(T::MaxRejectionReasonLength::get());
+		Pallet::<T>::reject_ad(moderator::<T>()?, ad_id, reason)?;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), ad_id);
+
+		assert!(matches!(Moderation::<T>::get(ad_id), ModerationStatus::Appealed { .. }));
+		Ok(())
+	}
+
+	/// `a` is the number of ads of the advertiser, all of them running.
+	#[benchmark]
+	fn slash_advertiser(a: Linear<0, 100>) -> Result<(), BenchmarkError> {
+		let advertiser = registered_advertiser::<T>(0);
+		for _ in 0..a {
+			submitted_ad::<T>(&advertiser);
+		}
+		let origin =
+			T::GovernanceOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+
+		#[extrinsic_call]
+		_(
+			origin as RuntimeOriginOf<T>,
+			advertiser.clone(),
+			Perbill::from_percent(50),
+			Some(10u32.into()),
+			a,
+		);
+
+		let profile = AdvertiserProfiles::<T>::get(&advertiser).expect("registered above; qed");
+		assert_eq!(profile.active_ads, 0);
+		assert!(!profile.active);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn end_suspension() -> Result<(), BenchmarkError> {
+		let advertiser = registered_advertiser::<T>(0);
+		let origin =
+			T::GovernanceOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		Pallet::<T>::slash_advertiser(
+			origin,
+			advertiser.clone(),
+			Perbill::from_percent(50),
+			Some(10u32.into()),
+			0,
+		)?;
+		let until = Suspensions::<T>::get(&advertiser).expect("suspended above; qed");
+
+		#[block]
+		{
+			Pallet::<T>::do_end_suspension(&advertiser, until);
+		}
+
+		assert!(AdvertiserProfiles::<T>::get(&advertiser).expect("registered above; qed").active);
+		Ok(())
+	}
+
+	/// Updating an appealed ad with a full history, which returns the bond, is the worst case.
+	#[benchmark]
+	fn update_ad() -> Result<(), BenchmarkError> {
+		let caller = registered_advertiser::<T>(0);
+		let ad_id = appealed_ad::<T>(&caller)?;
+		for _ in 0..T::MaxCreativeHistory::get() {
+			Pallet::<T>::update_ad(
+				RawOrigin::Signed(caller.clone()).into(),
+				ad_id,
+				max_bytes(T::MaxAdNameLength::get()),
+				max_bytes(T::MaxAdDescriptionLength::get()),
+				max_bytes(T::MaxCidLength::get()),
+				CREATIVE_DURATION,
+			)?;
+		}
+		Pallet::<T>::reject_ad(moderator::<T>()?, ad_id, Vec::new())?;
+		Pallet::<T>::appeal_rejection(RawOrigin::Signed(caller.clone()).into(), ad_id)?;
+
+		#[extrinsic_call]
+		_(
+			RawOrigin::Signed(caller),
+			ad_id,
+			max_bytes(T::MaxAdNameLength::get()),
+			max_bytes(T::MaxAdDescriptionLength::get()),
+			max_bytes(T::MaxCidLength::get()),
+			CREATIVE_DURATION,
+		);
+
+		let ad = Ads::<T>::get(ad_id).expect("submitted above; qed");
+		assert_eq!(ad.creative_version, T::MaxCreativeHistory::get() + 2);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn set_auto_approve() -> Result<(), BenchmarkError> {
+		let origin = moderator::<T>()?;
+
+		#[extrinsic_call]
+		_(origin as RuntimeOriginOf<T>, Some(1));
+
+		assert_eq!(AutoApproveAfter::<T>::get(), Some(1));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn top_up_ad() {
+		let caller = registered_advertiser::<T>(0);
+		let ad_id = submitted_ad::<T>(&caller);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), ad_id, AD_FUNDING.into());
+
+		let funding: BalanceOf<T> = AD_FUNDING.saturating_mul(2).into();
+		assert_eq!(Ads::<T>::get(ad_id).expect("submitted above; qed").funding, funding);
+	}
+
+	#[benchmark]
+	fn withdraw_remaining_budget() -> Result<(), BenchmarkError> {
+		let caller = registered_advertiser::<T>(0);
+		let ad_id = submitted_ad::<T>(&caller);
+		Pallet::<T>::deactivate_ad(RawOrigin::Signed(caller.clone()).into(), ad_id)?;
+		let unlocks_at = BudgetUnlocksAt::<T>::get(ad_id).expect("budget was left; qed");
+		frame_system::Pallet::<T>::set_block_number(unlocks_at);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), ad_id);
+
+		let remaining = Ads::<T>::get(ad_id).expect("submitted above; qed").remaining_budget;
+		assert_eq!(remaining, 0u32.into());
+		Ok(())
+	}
+
+	impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
+}
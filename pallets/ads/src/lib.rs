@@ -7,22 +7,86 @@
 
 pub use pallet::*;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod migrations;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+pub mod weights;
+pub use weights::*;
+
+use sp_runtime::DispatchResult;
+
+/// Access to ad budgets for pallets that pay out of them.
+///
+/// Payments are two-phase: an amount is first [`debit`](Self::debit)ed from the remaining
+/// budget while the funds stay in escrow, then either paid out with
+/// [`settle`](Self::settle) or given back with [`credit`](Self::credit).
+pub trait AdBudgetProvider<AccountId, Balance> {
+	/// Whether `ad_id` exists and is currently active.
+	fn is_active(ad_id: u32) -> bool;
+
+	/// Whether payments can still be debited from `ad_id`: it is active, or deactivated
+	/// with its budget still locked for payments that were already underway.
+	fn is_payable(ad_id: u32) -> bool;
+
+	/// Budget of `ad_id` still available for payments, if the ad exists.
+	fn remaining_budget(ad_id: u32) -> Option<Balance>;
+
+	/// Set `amount` aside from the remaining budget of `ad_id`. Fails for ads that are not
+	/// [`is_payable`](Self::is_payable).
+	fn debit(ad_id: u32, amount: Balance) -> DispatchResult;
+
+	/// Return a previously debited `amount` to the budget of `ad_id`.
+	fn credit(ad_id: u32, amount: Balance) -> DispatchResult;
+
+	/// Pay a previously debited `amount` out of escrow to `beneficiary`, or slash it
+	/// when no beneficiary is given.
+	fn settle(ad_id: u32, amount: Balance, beneficiary: Option<&AccountId>) -> DispatchResult;
+}
+
+/// Read access to ads for pallets that record how they are watched.
+pub trait AdLookup {
+	/// Whether `ad_id` can be shown: it exists, is active, approved by moderation and has
+	/// budget left.
+	fn is_servable(ad_id: u32) -> bool;
+
+	/// Length in milliseconds of the current creative of `ad_id`, zero if unknown.
+	fn creative_duration(ad_id: u32) -> u32;
+
+	/// Version of the current creative of `ad_id`, if the ad exists.
+	fn creative_version(ad_id: u32) -> Option<u32>;
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use frame_support::pallet_prelude::*;
 	use frame_system::pallet_prelude::*;
 	use sp_std::vec::Vec;
-	use frame_support::traits::{Currency, ReservableCurrency};
-	use sp_runtime::traits::Saturating;
+	use frame_support::{
+		traits::{BalanceStatus, Currency, Imbalance, OnUnbalanced, ReservableCurrency},
+		DefaultNoBound,
+	};
+	use sp_runtime::{
+		traits::{Saturating, Zero},
+		Perbill,
+	};
+	use crate::WeightInfo;
+
+	/// The in-code storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(3);
 
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 		
-		/// The currency implementation for handling advertiser deposits
+		/// The currency implementation for handling advertiser deposits and ad budgets
 		type Currency: ReservableCurrency<Self::AccountId>;
 		
 		/// Maximum length for ad name
@@ -44,12 +108,65 @@ pub mod pallet {
 		/// Minimum deposit required to register as an advertiser
 		#[pallet::constant]
 		type MinAdvertiserDeposit: Get<<<Self as Config>::Currency as Currency<Self::AccountId>>::Balance>;
+		
+		/// Receives the winning bids of ad spot auctions
+		type AuctionRevenue: OnUnbalanced<NegativeImbalanceOf<Self>>;
+		
+		/// Maximum number of bidders in a single ad spot auction
+		#[pallet::constant]
+		type MaxAuctionBids: Get<u32>;
+		
+		/// Maximum number of spot auctions, and separately spot leases, ending in the same block
+		#[pallet::constant]
+		type MaxAuctionsPerBlock: Get<u32>;
+		
+		/// Maximum number of scheduled ads starting, and separately ending, in the same block
+		#[pallet::constant]
+		type MaxScheduledAdsPerBlock: Get<u32>;
+		
+		/// Origin that approves and rejects ads
+		type ModeratorOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		
+		/// Maximum length for the reason given when rejecting an ad
+		#[pallet::constant]
+		type MaxRejectionReasonLength: Get<u32>;
+		
+		/// Bond reserved to appeal a rejection, burned if the appeal is rejected
+		#[pallet::constant]
+		type AppealBond: Get<BalanceOf<Self>>;
+		
+		/// Origin that slashes and suspends advertisers for policy violations
+		type GovernanceOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		
+		/// Receives slashed advertiser deposits
+		type OnSlash: OnUnbalanced<NegativeImbalanceOf<Self>>;
+		
+		/// Maximum number of advertiser suspensions ending in the same block
+		#[pallet::constant]
+		type MaxSuspensionsPerBlock: Get<u32>;
+		
+		/// Maximum number of previous creatives kept per ad
+		#[pallet::constant]
+		type MaxCreativeHistory: Get<u32>;
+		
+		/// Blocks after deactivation during which the unspent budget of an ad stays locked
+		/// for sponsorships already underway, before it can be withdrawn
+		#[pallet::constant]
+		type WithdrawalCooldown: Get<BlockNumberFor<Self>>;
+		
+		/// Weight information for extrinsics in this pallet
+		type WeightInfo: WeightInfo;
 	}
 
 	/// Type alias for Balance
 	pub type BalanceOf<T> =
 		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
+	/// Type alias for the imbalance of slashed funds
+	pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+		<T as frame_system::Config>::AccountId,
+	>>::NegativeImbalance;
+
 	/// Ad metadata structure
 	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
 	#[scale_info(skip_type_params(T))]
@@ -58,10 +175,20 @@ pub mod pallet {
 		pub name: BoundedVec<u8, T::MaxAdNameLength>,
 		pub description: BoundedVec<u8, T::MaxAdDescriptionLength>,
 		pub ipfs_cid: BoundedVec<u8, T::MaxCidLength>,
-		pub funding: u128,
-		pub remaining_budget: u128,
+		/// Version of the creative, starting at 1 and incremented by `update_ad`
+		pub creative_version: u32,
+		/// Ad spot the ad is placed in while active
+		pub spot_id: u32,
+		/// Total budget escrowed for this ad, at submission and by top-ups
+		pub funding: BalanceOf<T>,
+		/// Portion of the escrowed budget that has not been spent or released yet
+		pub remaining_budget: BalanceOf<T>,
 		pub views: u64,
 		pub active: bool,
+		/// Block at which the ad goes live; until then it holds its spot inactive
+		pub starts_at: BlockNumberFor<T>,
+		/// Block at which the ad expires, if the campaign is time-bounded
+		pub ends_at: Option<BlockNumberFor<T>>,
 	}
 
 	/// Advertiser profile information
@@ -78,10 +205,14 @@ pub mod pallet {
 		pub deposit: BalanceOf<T>,
 		/// Whether the advertiser account is active
 		pub active: bool,
-		/// Total amount advertiser has funded across all ads
-		pub total_funded: u128,
+		/// Total amount advertiser has funded across all ads, the sum of their `funding`
+		pub total_funded: BalanceOf<T>,
 		/// Total number of ads submitted
 		pub total_ads: u32,
+		/// Number of ads that are active or scheduled to start
+		pub active_ads: u32,
+		/// Deposit must be topped up to the minimum before submitting ads or bidding
+		pub needs_top_up: bool,
 	}
 
 	/// Ad spot structure
@@ -89,6 +220,66 @@ pub mod pallet {
 	pub struct AdSpot {
 		pub spot_id: u32,
 		pub available: bool,
+		/// Ad currently placed in the spot
+		pub ad_id: Option<u32>,
+	}
+
+	/// Open auction for an ad spot
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct SpotAuction<T: Config> {
+		/// Block at which the auction closes
+		pub ends_at: BlockNumberFor<T>,
+		/// Number of blocks the winner leases the spot for
+		pub lease_period: BlockNumberFor<T>,
+		/// Reserved bids, one per bidder
+		pub bids: BoundedVec<(T::AccountId, BalanceOf<T>), T::MaxAuctionBids>,
+	}
+
+	/// Exclusive right of an advertiser to place ads in a spot
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct SpotLease<T: Config> {
+		/// Winner of the auction for the spot
+		pub holder: T::AccountId,
+		/// Block at which the lease ends and the ad in the spot is taken down
+		pub ends_at: BlockNumberFor<T>,
+	}
+
+	/// A previous creative of an ad
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct CreativeVersion<T: Config> {
+		pub version: u32,
+		pub ipfs_cid: BoundedVec<u8, T::MaxCidLength>,
+		/// Block at which the creative was replaced
+		pub replaced_at: BlockNumberFor<T>,
+	}
+
+	/// Moderation state of an ad
+	#[derive(
+		Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DefaultNoBound,
+	)]
+	#[scale_info(skip_type_params(T))]
+	pub enum ModerationStatus<T: Config> {
+		/// Waiting for the moderator; the ad holds its spot but doesn't run
+		Pending,
+		/// Cleared to run
+		#[default]
+		Approved,
+		/// Rejected by the moderator; the ad holds its spot until it is appealed or withdrawn
+		Rejected { reason: BoundedVec<u8, T::MaxRejectionReasonLength> },
+		/// Rejection appealed by the advertiser, who reserved `bond`
+		Appealed { reason: BoundedVec<u8, T::MaxRejectionReasonLength>, bond: BalanceOf<T> },
+	}
+
+	/// Moderation decisions on the ads of an advertiser
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, Default)]
+	pub struct ModerationRecord {
+		/// Ads approved by the moderator or automatically
+		pub approved: u32,
+		/// Ads rejected and not approved on appeal
+		pub rejected: u32,
 	}
 
 	/// Storage: Ad spots by ID
@@ -106,6 +297,61 @@ pub mod pallet {
 	#[pallet::getter(fn advertiser_profiles)]
 	pub type AdvertiserProfiles<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, AdvertiserProfile<T>>;
 
+	/// Storage: Ads of each advertiser, active or not
+	#[pallet::storage]
+	pub type AdsByAdvertiser<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Twox64Concat, u32, ()>;
+
+	/// Storage: Block from which the unspent budget of a deactivated ad can be withdrawn
+	#[pallet::storage]
+	pub type BudgetUnlocksAt<T: Config> = StorageMap<_, Blake2_128Concat, u32, BlockNumberFor<T>>;
+
+	/// Storage: Length in milliseconds of the current creative of each ad, zero if unknown
+	#[pallet::storage]
+	pub type CreativeDurations<T: Config> = StorageMap<_, Blake2_128Concat, u32, u32, ValueQuery>;
+
+	/// Storage: Previous creatives of ads, oldest first
+	#[pallet::storage]
+	pub type CreativeHistory<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u32,
+		BoundedVec<CreativeVersion<T>, T::MaxCreativeHistory>,
+		ValueQuery,
+	>;
+
+	/// Storage: Moderation state of ads
+	///
+	/// Ads without an entry are approved; entries are removed on approval.
+	#[pallet::storage]
+	pub type Moderation<T: Config> =
+		StorageMap<_, Blake2_128Concat, u32, ModerationStatus<T>, ValueQuery>;
+
+	/// Storage: Moderation decisions per advertiser
+	#[pallet::storage]
+	pub type ModerationRecords<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, ModerationRecord, ValueQuery>;
+
+	/// Storage: Approved ads an advertiser needs, with none rejected, for new ads to be
+	/// approved automatically; `None` disables auto-approval
+	#[pallet::storage]
+	pub type AutoApproveAfter<T: Config> = StorageValue<_, u32, OptionQuery>;
+
+	/// Storage: Block until which a slashed advertiser is suspended
+	#[pallet::storage]
+	pub type Suspensions<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, BlockNumberFor<T>>;
+
+	/// Storage: Advertisers whose suspension ends at a block
+	#[pallet::storage]
+	pub type SuspensionsEndingAt<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		BlockNumberFor<T>,
+		BoundedVec<T::AccountId, T::MaxSuspensionsPerBlock>,
+		ValueQuery,
+	>;
+
 	/// Storage: Next ad ID
 	#[pallet::storage]
 	#[pallet::getter(fn next_ad_id)]
@@ -116,10 +362,133 @@ pub mod pallet {
 	#[pallet::getter(fn next_spot_id)]
 	pub type NextSpotId<T: Config> = StorageValue<_, u32, ValueQuery>;
 
-	/// Storage: Advertiser accounts (legacy, kept for backward compatibility)
+	/// Storage: Open ad spot auctions by spot ID
+	#[pallet::storage]
+	pub type SpotAuctions<T: Config> = StorageMap<_, Blake2_128Concat, u32, SpotAuction<T>>;
+
+	/// Storage: Ad spot leases by spot ID
+	#[pallet::storage]
+	pub type SpotLeases<T: Config> = StorageMap<_, Blake2_128Concat, u32, SpotLease<T>>;
+
+	/// Storage: Spots whose auction closes at a block
+	#[pallet::storage]
+	pub type AuctionsEndingAt<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		BlockNumberFor<T>,
+		BoundedVec<u32, T::MaxAuctionsPerBlock>,
+		ValueQuery,
+	>;
+
+	/// Storage: Spots whose lease ends at a block
+	///
+	/// Slots are taken when the auction opens, so closing an auction never fails.
+	#[pallet::storage]
+	pub type LeasesEndingAt<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		BlockNumberFor<T>,
+		BoundedVec<u32, T::MaxAuctionsPerBlock>,
+		ValueQuery,
+	>;
+
+	/// Storage: Scheduled ads going live at a block
+	#[pallet::storage]
+	pub type AdsStartingAt<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		BlockNumberFor<T>,
+		BoundedVec<u32, T::MaxScheduledAdsPerBlock>,
+		ValueQuery,
+	>;
+
+	/// Storage: Time-bounded ads expiring at a block
 	#[pallet::storage]
-	#[pallet::getter(fn advertisers)]
-	pub type Advertisers<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, bool, ValueQuery>;
+	pub type AdsEndingAt<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		BlockNumberFor<T>,
+		BoundedVec<u32, T::MaxScheduledAdsPerBlock>,
+		ValueQuery,
+	>;
+
+	#[pallet::genesis_config]
+	#[derive(frame_support::DefaultNoBound)]
+	pub struct GenesisConfig<T: Config> {
+		/// Number of available ad spots created at genesis
+		pub ad_spots: u32,
+		/// Advertisers registered at genesis: `(account, name, deposit)`
+		pub advertisers: Vec<(T::AccountId, Vec<u8>, BalanceOf<T>)>,
+		/// Ads submitted at genesis: `(advertiser, spot_id, name, description, ipfs_cid, funding)`
+		pub ads: Vec<(T::AccountId, u32, Vec<u8>, Vec<u8>, Vec<u8>, BalanceOf<T>)>,
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+		fn build(&self) {
+			for _ in 0..self.ad_spots {
+				Pallet::<T>::do_create_ad_spot();
+			}
+
+			for (advertiser, name, deposit) in &self.advertisers {
+				Pallet::<T>::do_register_advertiser(advertiser, name.clone(), *deposit)
+					.expect("Genesis advertisers must be able to reserve their deposit");
+			}
+
+			for (advertiser, spot_id, name, description, ipfs_cid, funding) in &self.ads {
+				let ad_id = NextAdId::<T>::get();
+				Pallet::<T>::do_submit_ad(
+					advertiser,
+					*spot_id,
+					name.clone(),
+					description.clone(),
+					ipfs_cid.clone(),
+					0,
+					*funding,
+					None,
+					None,
+				)
+				.expect("Genesis ads must reference a free spot and a funded advertiser");
+				if Moderation::<T>::get(ad_id) == ModerationStatus::Pending {
+					Pallet::<T>::do_approve_ad(ad_id).expect("Genesis ads were just submitted");
+				}
+			}
+		}
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+			let mut weight = T::DbWeight::get().reads_writes(5, 5);
+
+			for spot_id in AuctionsEndingAt::<T>::take(now) {
+				let bids = Self::do_close_spot_auction(spot_id, now);
+				weight.saturating_accrue(T::WeightInfo::close_spot_auction(bids));
+			}
+
+			for spot_id in LeasesEndingAt::<T>::take(now) {
+				weight.saturating_accrue(T::WeightInfo::end_spot_lease());
+				Self::do_end_spot_lease(spot_id, now);
+			}
+
+			for ad_id in AdsStartingAt::<T>::take(now) {
+				weight.saturating_accrue(T::WeightInfo::start_scheduled_ad());
+				Self::do_start_scheduled_ad(ad_id);
+			}
+
+			for ad_id in AdsEndingAt::<T>::take(now) {
+				weight.saturating_accrue(T::WeightInfo::expire_ad());
+				Self::do_expire_ad(ad_id);
+			}
+
+			for who in SuspensionsEndingAt::<T>::take(now) {
+				weight.saturating_accrue(T::WeightInfo::end_suspension());
+				Self::do_end_suspension(&who, now);
+			}
+
+			weight
+		}
+	}
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -132,7 +501,7 @@ pub mod pallet {
 		AdvertiserDeregistered { advertiser: T::AccountId, refunded: BalanceOf<T> },
 		/// Ad submitted
 		AdSubmitted { ad_id: u32, advertiser: T::AccountId, spot_id: u32 },
-		/// Ad activated
+		/// Ad went live, at submission or at its scheduled start
 		AdActivated { ad_id: u32 },
 		/// Ad deactivated
 		AdDeactivated { ad_id: u32 },
@@ -140,6 +509,58 @@ pub mod pallet {
 		DepositIncreased { advertiser: T::AccountId, amount: BalanceOf<T> },
 		/// Advertiser profile updated
 		AdvertiserProfileUpdated { advertiser: T::AccountId },
+		/// Part of an ad budget was paid out of escrow
+		AdBudgetSpent { ad_id: u32, amount: BalanceOf<T>, remaining: BalanceOf<T> },
+		/// Unspent ad budget was withdrawn by the advertiser
+		AdBudgetReleased { ad_id: u32, advertiser: T::AccountId, amount: BalanceOf<T> },
+		/// Ad budget was used up and the ad was deactivated
+		AdBudgetExhausted { ad_id: u32 },
+		/// Ad spot was freed by the ad placed in it and can take a new ad
+		AdSpotReleased { spot_id: u32, ad_id: u32 },
+		/// Auction opened for an ad spot
+		SpotAuctionOpened {
+			spot_id: u32,
+			ends_at: BlockNumberFor<T>,
+			lease_period: BlockNumberFor<T>,
+		},
+		/// Bid placed or raised in an ad spot auction
+		SpotBidPlaced { spot_id: u32, bidder: T::AccountId, amount: BalanceOf<T> },
+		/// Ad spot auction closed; the winner, if any, leases the spot until `lease_ends_at`
+		SpotAuctionClosed {
+			spot_id: u32,
+			winner: Option<T::AccountId>,
+			amount: BalanceOf<T>,
+			lease_ends_at: BlockNumberFor<T>,
+		},
+		/// Ad spot lease ended and the spot can take a new ad
+		SpotLeaseEnded { spot_id: u32, holder: T::AccountId },
+		/// Time-bounded ad reached its end block and was deactivated
+		AdExpired { ad_id: u32 },
+		/// Ad approved by the moderator, on appeal or automatically
+		AdApproved { ad_id: u32 },
+		/// Ad rejected by the moderator
+		AdRejected { ad_id: u32, reason: BoundedVec<u8, T::MaxRejectionReasonLength> },
+		/// Rejection of an ad appealed by its advertiser
+		AdAppealed { ad_id: u32, bond: BalanceOf<T> },
+		/// Appeal rejected and its bond burned
+		AppealBondSlashed { ad_id: u32, advertiser: T::AccountId, amount: BalanceOf<T> },
+		/// Auto-approval threshold changed
+		AutoApproveSet { min_approved: Option<u32> },
+		/// Advertiser slashed for a policy violation and their ads deactivated
+		AdvertiserSlashed {
+			advertiser: T::AccountId,
+			amount: BalanceOf<T>,
+			deactivated_ads: u32,
+			suspended_until: Option<BlockNumberFor<T>>,
+		},
+		/// Suspension of a slashed advertiser ended
+		AdvertiserSuspensionEnded { advertiser: T::AccountId },
+		/// Ad got a new creative; it waits for moderation again unless auto-approved
+		AdCreativeUpdated { ad_id: u32, version: u32 },
+		/// Ad budget topped up by its advertiser
+		AdToppedUp { ad_id: u32, amount: BalanceOf<T>, remaining: BalanceOf<T> },
+		/// Unspent budget of a deactivated ad is locked until `unlocks_at`
+		AdBudgetLocked { ad_id: u32, amount: BalanceOf<T>, unlocks_at: BlockNumberFor<T> },
 	}
 
 	#[pallet::error]
@@ -174,26 +595,59 @@ pub mod pallet {
 		CannotWithdrawWithActiveAds,
 		/// Unauthorized - only advertiser can perform this action
 		Unauthorized,
+		/// Ad budget cannot cover the requested amount
+		InsufficientAdBudget,
+		/// Ad is not active
+		AdNotActive,
+		/// No ad is placed in the ad spot
+		AdSpotNotOccupied,
+		/// Auction duration and lease period must be non-zero
+		InvalidAuctionPeriod,
+		/// Too many auctions or leases end in the same block
+		TooManyAuctionsEnding,
+		/// No auction is open for the ad spot
+		AuctionNotFound,
+		/// The auction has already ended
+		AuctionEnded,
+		/// Bid must be higher than the current highest bid
+		BidTooLow,
+		/// The auction has reached its maximum number of bidders
+		TooManyBids,
+		/// Ad must end after it starts and after the current block
+		InvalidSchedule,
+		/// Too many scheduled ads start or end in the same block
+		TooManyScheduledAds,
+		/// Rejection reason too long
+		RejectionReasonTooLong,
+		/// Ad is not waiting for a moderation decision
+		AdNotUnderReview,
+		/// Only rejected ads can be appealed
+		AdNotRejected,
+		/// The advertiser has more ads than the given witness
+		InvalidAdCountWitness,
+		/// Suspension period must be non-zero
+		InvalidSuspensionPeriod,
+		/// Too many advertiser suspensions end in the same block
+		TooManySuspensionsEnding,
+		/// Advertiser is suspended
+		AdvertiserSuspended,
+		/// Budget can only be withdrawn from deactivated ads
+		AdStillActive,
+		/// The ad has no unspent budget to withdraw
+		NothingToWithdraw,
+		/// The budget of the ad is still locked for sponsorships underway
+		BudgetStillLocked,
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		/// Create a new ad spot
 		#[pallet::call_index(0)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::create_ad_spot())]
 		pub fn create_ad_spot(origin: OriginFor<T>) -> DispatchResult {
 			ensure_root(origin)?;
 			
-			let spot_id = NextSpotId::<T>::get();
-			let spot = AdSpot {
-				spot_id,
-				available: true,
-			};
-			
-			AdSpots::<T>::insert(spot_id, spot);
-			NextSpotId::<T>::put(spot_id.saturating_add(1));
-			
-			Self::deposit_event(Event::AdSpotCreated { spot_id });
+			Self::do_create_ad_spot();
 			Ok(())
 		}
 
@@ -207,74 +661,24 @@ pub mod pallet {
 		/// The deposit is held in reserve and can be withdrawn later.
 		/// An advertiser must be registered before they can submit ads.
 		#[pallet::call_index(1)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::register_advertiser())]
 		pub fn register_advertiser(
 			origin: OriginFor<T>,
 			name: Vec<u8>,
 			deposit_amount: BalanceOf<T>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
-			let now = frame_system::Pallet::<T>::block_number();
-			
-			// Validate that advertiser is not already registered
-			ensure!(
-				!AdvertiserProfiles::<T>::contains_key(&who),
-				Error::<T>::AdvertiserAlreadyRegistered
-			);
-
-			// Validate deposit amount meets minimum requirement
-			let min_deposit = T::MinAdvertiserDeposit::get();
-			ensure!(
-				deposit_amount >= min_deposit,
-				Error::<T>::DepositTooLow
-			);
-
-			// Validate advertiser name length
-			let bounded_name = BoundedVec::try_from(name)
-				.map_err(|_| Error::<T>::AdvertiserNameTooLong)?;
-
-			// Check if advertiser has sufficient balance
-			let free_balance = T::Currency::free_balance(&who);
-			ensure!(
-				free_balance >= deposit_amount,
-				Error::<T>::InsufficientBalance
-			);
-
-			// Reserve the deposit amount
-			T::Currency::reserve(&who, deposit_amount)
-				.map_err(|_| Error::<T>::InsufficientBalance)?;
-
-			// Create advertiser profile
-			let profile = AdvertiserProfile {
-				account_id: who.clone(),
-				name: bounded_name,
-				registration_block: now,
-				deposit: deposit_amount,
-				active: true,
-				total_funded: 0,
-				total_ads: 0,
-			};
-
-			// Store the profile
-			AdvertiserProfiles::<T>::insert(&who, profile);
-			
-			// Also set the legacy advertiser flag for backward compatibility
-			Advertisers::<T>::insert(&who, true);
-
-			Self::deposit_event(Event::AdvertiserRegistered {
-				advertiser: who,
-				deposit: deposit_amount,
-			});
 
-			Ok(())
+			Self::do_register_advertiser(&who, name, deposit_amount)
 		}
 
 		/// Increase advertiser deposit
 		///
 		/// This allows an advertiser to add more funds to their deposit.
-		/// Only called by the advertiser themselves.
+		/// Only called by the advertiser themselves. Advertisers that need a top-up can
+		/// submit ads again once their deposit reaches `MinAdvertiserDeposit`.
 		#[pallet::call_index(2)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::increase_advertiser_deposit())]
 		pub fn increase_advertiser_deposit(
 			origin: OriginFor<T>,
 			additional_amount: BalanceOf<T>,
@@ -301,6 +705,9 @@ pub mod pallet {
 
 			// Update deposit
 			profile.deposit = profile.deposit.saturating_add(additional_amount);
+			if profile.deposit >= T::MinAdvertiserDeposit::get() {
+				profile.needs_top_up = false;
+			}
 			AdvertiserProfiles::<T>::insert(&who, profile);
 
 			Self::deposit_event(Event::DepositIncreased {
@@ -316,7 +723,7 @@ pub mod pallet {
 		/// This extrinsic allows an advertiser to unregister and withdraw their deposit.
 		/// All ads must be deactivated before deregistering.
 		#[pallet::call_index(3)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::deregister_advertiser())]
 		pub fn deregister_advertiser(origin: OriginFor<T>) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 
@@ -324,12 +731,11 @@ pub mod pallet {
 			let profile = AdvertiserProfiles::<T>::get(&who)
 				.ok_or(Error::<T>::AdvertiserNotFound)?;
 
-			// Check if advertiser has any active ads
-			let has_active_ads = Ads::<T>::iter_values()
-				.any(|ad| ad.advertiser == who && ad.active);
+			ensure!(!Suspensions::<T>::contains_key(&who), Error::<T>::AdvertiserSuspended);
 
+			// Scheduled ads count as active
 			ensure!(
-				!has_active_ads,
+				profile.active_ads == 0,
 				Error::<T>::CannotWithdrawWithActiveAds
 			);
 
@@ -340,7 +746,6 @@ pub mod pallet {
 
 			// Remove advertiser profile
 			AdvertiserProfiles::<T>::remove(&who);
-			Advertisers::<T>::remove(&who);
 
 			Self::deposit_event(Event::AdvertiserDeregistered {
 				advertiser: who,
@@ -351,74 +756,51 @@ pub mod pallet {
 		}
 
 		/// Submit an ad
+		///
+		/// The `funding` amount is reserved from the advertiser's free balance and held
+		/// in escrow as the ad budget. Sponsorships are paid out of this escrow and any
+		/// unspent budget can be withdrawn once the ad is deactivated.
+		///
+		/// `creative_duration` is the length of the creative in milliseconds, from which
+		/// the minimum watch time of a view is derived; zero if unknown.
+		///
+		/// An ad with a future `starts_at` holds its spot and goes live at that block; an
+		/// ad with `ends_at` expires at that block.
 		#[pallet::call_index(4)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::submit_ad())]
+		#[allow(clippy::too_many_arguments)]
 		pub fn submit_ad(
 			origin: OriginFor<T>,
 			spot_id: u32,
 			name: Vec<u8>,
 			description: Vec<u8>,
 			ipfs_cid: Vec<u8>,
-			funding: u128,
+			creative_duration: u32,
+			funding: BalanceOf<T>,
+			starts_at: Option<BlockNumberFor<T>>,
+			ends_at: Option<BlockNumberFor<T>>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
-			
-			// Verify advertiser is registered
-			let mut profile = AdvertiserProfiles::<T>::get(&who)
-				.ok_or(Error::<T>::AdvertiserNotRegistered)?;
-
-			// Verify advertiser is active
-			ensure!(profile.active, Error::<T>::AdvertiserNotRegistered);
 
-			// Verify ad spot exists and is available
-			ensure!(AdSpots::<T>::contains_key(spot_id), Error::<T>::AdSpotNotFound);
-			let spot = AdSpots::<T>::get(spot_id).ok_or(Error::<T>::AdSpotNotFound)?;
-			ensure!(spot.available, Error::<T>::AdSpotNotAvailable);
-			
-			// Validate inputs
-			let bounded_name = BoundedVec::try_from(name)
-				.map_err(|_| Error::<T>::AdNameTooLong)?;
-			let bounded_description = BoundedVec::try_from(description)
-				.map_err(|_| Error::<T>::AdDescriptionTooLong)?;
-			let bounded_cid = BoundedVec::try_from(ipfs_cid)
-				.map_err(|_| Error::<T>::CidTooLong)?;
-			
-			// Create ad
-			let ad_id = NextAdId::<T>::get();
-			let ad = AdMetadata {
-				advertiser: who.clone(),
-				name: bounded_name,
-				description: bounded_description,
-				ipfs_cid: bounded_cid,
+			Self::do_submit_ad(
+				&who,
+				spot_id,
+				name,
+				description,
+				ipfs_cid,
+				creative_duration,
 				funding,
-				remaining_budget: funding,
-				views: 0,
-				active: true,
-			};
-			
-			// Store ad
-			Ads::<T>::insert(ad_id, ad);
-			NextAdId::<T>::put(ad_id.saturating_add(1));
-
-			// Update advertiser profile
-			profile.total_funded = profile.total_funded.saturating_add(funding);
-			profile.total_ads = profile.total_ads.saturating_add(1);
-			AdvertiserProfiles::<T>::insert(&who, profile);
-			
-			// Mark spot as unavailable
-			AdSpots::<T>::mutate(spot_id, |maybe_spot| {
-				if let Some(spot) = maybe_spot {
-					spot.available = false;
-				}
-			});
-			
-			Self::deposit_event(Event::AdSubmitted { ad_id, advertiser: who, spot_id });
-			Ok(())
+				starts_at,
+				ends_at,
+			)
 		}
 
 		/// Deactivate an ad
+		///
+		/// Any budget remaining in escrow can be withdrawn with `withdraw_remaining_budget`
+		/// after `WithdrawalCooldown` blocks.
 		#[pallet::call_index(5)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::deactivate_ad())]
 		pub fn deactivate_ad(origin: OriginFor<T>, ad_id: u32) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 			
@@ -430,12 +812,1005 @@ pub mod pallet {
 					ad.advertiser == who,
 					Error::<T>::Unauthorized
 				);
+				ensure!(Self::is_placed(ad_id, ad), Error::<T>::AdNotActive);
 
-				ad.active = false;
-				
-				Self::deposit_event(Event::AdDeactivated { ad_id });
+				Self::do_deactivate_ad(ad_id, ad);
+				Ok(())
+			})
+		}
+
+		/// Reclaim an ad spot
+		///
+		/// Deactivates the ad placed in the spot, locking its unspent budget for withdrawal
+		/// by the advertiser, and makes the spot available again.
+		#[pallet::call_index(6)]
+		#[pallet::weight(T::WeightInfo::reclaim_ad_spot())]
+		pub fn reclaim_ad_spot(origin: OriginFor<T>, spot_id: u32) -> DispatchResult {
+			ensure_root(origin)?;
+
+			let spot = AdSpots::<T>::get(spot_id).ok_or(Error::<T>::AdSpotNotFound)?;
+			let ad_id = spot.ad_id.ok_or(Error::<T>::AdSpotNotOccupied)?;
+
+			Ads::<T>::try_mutate(ad_id, |maybe_ad| -> DispatchResult {
+				let ad = maybe_ad.as_mut().ok_or(Error::<T>::AdNotFound)?;
+
+				Self::do_deactivate_ad(ad_id, ad);
+				Ok(())
+			})
+		}
+
+		/// Open an auction for an ad spot
+		///
+		/// The spot must be available. It is withdrawn from `submit_ad` until the auction
+		/// closes after `duration` blocks; the highest bidder then leases it for
+		/// `lease_period` blocks, during which only they can place an ad in it.
+		#[pallet::call_index(7)]
+		#[pallet::weight(T::WeightInfo::open_spot_auction())]
+		pub fn open_spot_auction(
+			origin: OriginFor<T>,
+			spot_id: u32,
+			duration: BlockNumberFor<T>,
+			lease_period: BlockNumberFor<T>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(
+				!duration.is_zero() && !lease_period.is_zero(),
+				Error::<T>::InvalidAuctionPeriod
+			);
+
+			AdSpots::<T>::try_mutate(spot_id, |maybe_spot| -> DispatchResult {
+				let spot = maybe_spot.as_mut().ok_or(Error::<T>::AdSpotNotFound)?;
+				ensure!(spot.available, Error::<T>::AdSpotNotAvailable);
+
+				let ends_at = frame_system::Pallet::<T>::block_number().saturating_add(duration);
+				AuctionsEndingAt::<T>::try_append(ends_at, spot_id)
+					.map_err(|_| Error::<T>::TooManyAuctionsEnding)?;
+				LeasesEndingAt::<T>::try_append(ends_at.saturating_add(lease_period), spot_id)
+					.map_err(|_| Error::<T>::TooManyAuctionsEnding)?;
+
+				spot.available = false;
+				SpotAuctions::<T>::insert(
+					spot_id,
+					SpotAuction { ends_at, lease_period, bids: BoundedVec::new() },
+				);
+
+				Self::deposit_event(Event::SpotAuctionOpened { spot_id, ends_at, lease_period });
 				Ok(())
 			})
 		}
+
+		/// Bid for an ad spot
+		///
+		/// `amount` is the bidder's total bid and must exceed the highest bid so far. It is taken
+		/// out of the bidder's reserved deposit, which must stay at `MinAdvertiserDeposit`, and
+		/// stays reserved until the auction closes; raising a bid only takes the difference.
+		#[pallet::call_index(8)]
+		#[pallet::weight(T::WeightInfo::bid_for_spot())]
+		pub fn bid_for_spot(
+			origin: OriginFor<T>,
+			spot_id: u32,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let mut profile = AdvertiserProfiles::<T>::get(&who)
+				.ok_or(Error::<T>::AdvertiserNotRegistered)?;
+			ensure!(profile.active, Error::<T>::AdvertiserNotRegistered);
+			ensure!(!profile.needs_top_up, Error::<T>::DepositTooLow);
+			let available = profile.deposit.saturating_sub(T::MinAdvertiserDeposit::get());
+
+			SpotAuctions::<T>::try_mutate(spot_id, |maybe_auction| -> DispatchResult {
+				let auction = maybe_auction.as_mut().ok_or(Error::<T>::AuctionNotFound)?;
+				ensure!(
+					frame_system::Pallet::<T>::block_number() < auction.ends_at,
+					Error::<T>::AuctionEnded
+				);
+
+				let highest = auction.bids.iter().map(|(_, bid)| *bid).max().unwrap_or_default();
+				ensure!(!amount.is_zero() && amount > highest, Error::<T>::BidTooLow);
+
+				let additional = match auction.bids.iter_mut().find(|(bidder, _)| *bidder == who) {
+					Some((_, bid)) => {
+						let additional = amount.saturating_sub(*bid);
+						*bid = amount;
+						additional
+					},
+					None => {
+						auction
+							.bids
+							.try_push((who.clone(), amount))
+							.map_err(|_| Error::<T>::TooManyBids)?;
+						amount
+					},
+				};
+				ensure!(additional <= available, Error::<T>::DepositTooLow);
+				profile.deposit = profile.deposit.saturating_sub(additional);
+				AdvertiserProfiles::<T>::insert(&who, profile);
+
+				Self::deposit_event(Event::SpotBidPlaced { spot_id, bidder: who, amount });
+				Ok(())
+			})
+		}
+
+		/// Approve a pending or appealed ad
+		///
+		/// The ad goes live right away, or at its scheduled start. An appeal bond is
+		/// returned to the advertiser.
+		#[pallet::call_index(9)]
+		#[pallet::weight(T::WeightInfo::approve_ad())]
+		pub fn approve_ad(origin: OriginFor<T>, ad_id: u32) -> DispatchResult {
+			T::ModeratorOrigin::ensure_origin(origin)?;
+
+			Self::do_approve_ad(ad_id)
+		}
+
+		/// Reject a pending or appealed ad
+		///
+		/// A rejected ad keeps its spot and budget so the advertiser can appeal once. When
+		/// an appeal is rejected the bond is burned and the ad is deactivated.
+		#[pallet::call_index(10)]
+		#[pallet::weight(T::WeightInfo::reject_ad())]
+		pub fn reject_ad(origin: OriginFor<T>, ad_id: u32, reason: Vec<u8>) -> DispatchResult {
+			T::ModeratorOrigin::ensure_origin(origin)?;
+			let reason: BoundedVec<u8, T::MaxRejectionReasonLength> = BoundedVec::try_from(reason)
+				.map_err(|_| Error::<T>::RejectionReasonTooLong)?;
+
+			Ads::<T>::try_mutate(ad_id, |maybe_ad| -> DispatchResult {
+				let ad = maybe_ad.as_mut().ok_or(Error::<T>::AdNotFound)?;
+				ensure!(Self::is_placed(ad_id, ad), Error::<T>::AdNotActive);
+
+				let appealed = match Moderation::<T>::get(ad_id) {
+					ModerationStatus::Pending => {
+						ModerationRecords::<T>::mutate(&ad.advertiser, |record| {
+							record.rejected.saturating_inc()
+						});
+						false
+					},
+					ModerationStatus::Appealed { bond, .. } => {
+						let (slashed, _) = T::Currency::slash_reserved(&ad.advertiser, bond);
+						drop(slashed);
+						Self::deposit_event(Event::AppealBondSlashed {
+							ad_id,
+							advertiser: ad.advertiser.clone(),
+							amount: bond,
+						});
+						true
+					},
+					_ => return Err(Error::<T>::AdNotUnderReview.into()),
+				};
+
+				let rejected = ModerationStatus::Rejected { reason: reason.clone() };
+				Moderation::<T>::insert(ad_id, rejected);
+				if appealed {
+					Self::do_deactivate_ad(ad_id, ad);
+				}
+				Self::deposit_event(Event::AdRejected { ad_id, reason });
+				Ok(())
+			})
+		}
+
+		/// Appeal the rejection of an ad
+		///
+		/// Reserves `AppealBond` from the advertiser until the moderator decides again.
+		#[pallet::call_index(11)]
+		#[pallet::weight(T::WeightInfo::appeal_rejection())]
+		pub fn appeal_rejection(origin: OriginFor<T>, ad_id: u32) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let ad = Ads::<T>::get(ad_id).ok_or(Error::<T>::AdNotFound)?;
+			ensure!(ad.advertiser == who, Error::<T>::Unauthorized);
+			ensure!(Self::is_placed(ad_id, &ad), Error::<T>::AdNotActive);
+			let ModerationStatus::Rejected { reason } = Moderation::<T>::get(ad_id) else {
+				return Err(Error::<T>::AdNotRejected.into())
+			};
+
+			let bond = T::AppealBond::get();
+			T::Currency::reserve(&who, bond).map_err(|_| Error::<T>::InsufficientBalance)?;
+			Moderation::<T>::insert(ad_id, ModerationStatus::Appealed { reason, bond });
+
+			Self::deposit_event(Event::AdAppealed { ad_id, bond });
+			Ok(())
+		}
+
+		/// Approve new ads automatically for advertisers with at least `min_approved`
+		/// approved ads and no rejected ones, or disable auto-approval with `None`
+		#[pallet::call_index(12)]
+		#[pallet::weight(T::WeightInfo::set_auto_approve())]
+		pub fn set_auto_approve(origin: OriginFor<T>, min_approved: Option<u32>) -> DispatchResult {
+			T::ModeratorOrigin::ensure_origin(origin)?;
+
+			AutoApproveAfter::<T>::set(min_approved);
+			Self::deposit_event(Event::AutoApproveSet { min_approved });
+			Ok(())
+		}
+
+		/// Slash an advertiser for a policy violation
+		///
+		/// Slashes `fraction` of the advertiser's deposit to `OnSlash` and deactivates all
+		/// their ads. With `suspend_for` the advertiser is also suspended for that many blocks.
+		///
+		/// `ad_count` must be at least the number of ads the advertiser ever submitted.
+		#[pallet::call_index(13)]
+		#[pallet::weight(T::WeightInfo::slash_advertiser(*ad_count))]
+		pub fn slash_advertiser(
+			origin: OriginFor<T>,
+			advertiser: T::AccountId,
+			fraction: Perbill,
+			suspend_for: Option<BlockNumberFor<T>>,
+			ad_count: u32,
+		) -> DispatchResultWithPostInfo {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+
+			ensure!(
+				AdvertiserProfiles::<T>::contains_key(&advertiser),
+				Error::<T>::AdvertiserNotFound
+			);
+			let ad_ids: Vec<u32> = AdsByAdvertiser::<T>::iter_key_prefix(&advertiser)
+				.take(ad_count.saturating_add(1) as usize)
+				.collect();
+			ensure!(ad_ids.len() as u32 <= ad_count, Error::<T>::InvalidAdCountWitness);
+
+			let suspended_until = match suspend_for {
+				Some(period) => {
+					ensure!(!period.is_zero(), Error::<T>::InvalidSuspensionPeriod);
+					let until = frame_system::Pallet::<T>::block_number().saturating_add(period);
+					// An ongoing suspension is only ever extended, and only ends at its last end
+					match Suspensions::<T>::get(&advertiser) {
+						Some(current) if current >= until => Some(current),
+						_ => {
+							SuspensionsEndingAt::<T>::try_append(until, advertiser.clone())
+								.map_err(|_| Error::<T>::TooManySuspensionsEnding)?;
+							Suspensions::<T>::insert(&advertiser, until);
+							Some(until)
+						},
+					}
+				},
+				None => None,
+			};
+
+			// Take down every ad still holding a spot
+			let mut deactivated_ads = 0u32;
+			for ad_id in &ad_ids {
+				Ads::<T>::mutate(ad_id, |maybe_ad| {
+					if let Some(ad) = maybe_ad.as_mut().filter(|ad| Self::is_placed(*ad_id, ad)) {
+						Self::do_deactivate_ad(*ad_id, ad);
+						deactivated_ads.saturating_inc();
+					}
+				});
+			}
+
+			let amount = AdvertiserProfiles::<T>::mutate(&advertiser, |maybe_profile| {
+				let Some(profile) = maybe_profile else { return Zero::zero() };
+
+				let (slashed, _) =
+					T::Currency::slash_reserved(&advertiser, fraction * profile.deposit);
+				let amount = slashed.peek();
+				T::OnSlash::on_unbalanced(slashed);
+
+				profile.deposit = profile.deposit.saturating_sub(amount);
+				if profile.deposit < T::MinAdvertiserDeposit::get() {
+					profile.needs_top_up = true;
+				}
+				if suspended_until.is_some() {
+					profile.active = false;
+				}
+				amount
+			});
+
+			Self::deposit_event(Event::AdvertiserSlashed {
+				advertiser,
+				amount,
+				deactivated_ads,
+				suspended_until,
+			});
+			Ok(Some(T::WeightInfo::slash_advertiser(ad_ids.len() as u32)).into())
+		}
+
+		/// Replace the creative of an ad
+		///
+		/// The new creative gets the next `creative_version` and the previous CID is kept in
+		/// `CreativeHistory`. Unless the advertiser is auto-approved, the ad goes back to
+		/// moderation.
+		#[pallet::call_index(14)]
+		#[pallet::weight(T::WeightInfo::update_ad())]
+		pub fn update_ad(
+			origin: OriginFor<T>,
+			ad_id: u32,
+			name: Vec<u8>,
+			description: Vec<u8>,
+			ipfs_cid: Vec<u8>,
+			creative_duration: u32,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let name = BoundedVec::try_from(name).map_err(|_| Error::<T>::AdNameTooLong)?;
+			let description =
+				BoundedVec::try_from(description).map_err(|_| Error::<T>::AdDescriptionTooLong)?;
+			let ipfs_cid = BoundedVec::try_from(ipfs_cid).map_err(|_| Error::<T>::CidTooLong)?;
+
+			Ads::<T>::try_mutate(ad_id, |maybe_ad| -> DispatchResult {
+				let ad = maybe_ad.as_mut().ok_or(Error::<T>::AdNotFound)?;
+				ensure!(ad.advertiser == who, Error::<T>::Unauthorized);
+				ensure!(Self::is_placed(ad_id, ad), Error::<T>::AdNotActive);
+
+				CreativeHistory::<T>::mutate(ad_id, |history| {
+					if history.is_full() && !history.is_empty() {
+						history.remove(0);
+					}
+					let _ = history.try_push(CreativeVersion {
+						version: ad.creative_version,
+						ipfs_cid: sp_std::mem::replace(&mut ad.ipfs_cid, ipfs_cid),
+						replaced_at: frame_system::Pallet::<T>::block_number(),
+					});
+				});
+				ad.name = name;
+				ad.description = description;
+				ad.creative_version = ad.creative_version.saturating_add(1);
+				CreativeDurations::<T>::insert(ad_id, creative_duration);
+
+				if !Self::is_auto_approved(&who) {
+					if let ModerationStatus::Appealed { bond, .. } = Moderation::<T>::get(ad_id) {
+						T::Currency::unreserve(&who, bond);
+					}
+					Moderation::<T>::insert(ad_id, ModerationStatus::Pending);
+					ad.active = false;
+				}
+
+				let version = ad.creative_version;
+				Self::deposit_event(Event::AdCreativeUpdated { ad_id, version });
+				Ok(())
+			})
+		}
+
+		/// Add `amount` to the budget of an ad that is running or waiting to run
+		///
+		/// The amount is reserved from the advertiser's free balance like the initial funding.
+		#[pallet::call_index(15)]
+		#[pallet::weight(T::WeightInfo::top_up_ad())]
+		pub fn top_up_ad(origin: OriginFor<T>, ad_id: u32, amount: BalanceOf<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!amount.is_zero(), Error::<T>::InsufficientFunding);
+
+			Ads::<T>::try_mutate(ad_id, |maybe_ad| -> DispatchResult {
+				let ad = maybe_ad.as_mut().ok_or(Error::<T>::AdNotFound)?;
+				ensure!(ad.advertiser == who, Error::<T>::Unauthorized);
+				ensure!(Self::is_placed(ad_id, ad), Error::<T>::AdNotActive);
+
+				T::Currency::reserve(&who, amount).map_err(|_| Error::<T>::InsufficientBalance)?;
+				ad.funding = ad.funding.saturating_add(amount);
+				ad.remaining_budget = ad.remaining_budget.saturating_add(amount);
+				AdvertiserProfiles::<T>::mutate(&who, |maybe_profile| {
+					if let Some(profile) = maybe_profile {
+						profile.total_funded = profile.total_funded.saturating_add(amount);
+					}
+				});
+
+				Self::deposit_event(Event::AdToppedUp {
+					ad_id,
+					amount,
+					remaining: ad.remaining_budget,
+				});
+				Ok(())
+			})
+		}
+
+		/// Withdraw the unspent budget of a deactivated ad
+		///
+		/// The budget stays locked for `WithdrawalCooldown` blocks after deactivation so
+		/// that sponsorships verified while the ad ran can still be paid.
+		#[pallet::call_index(16)]
+		#[pallet::weight(T::WeightInfo::withdraw_remaining_budget())]
+		pub fn withdraw_remaining_budget(origin: OriginFor<T>, ad_id: u32) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			Ads::<T>::try_mutate(ad_id, |maybe_ad| -> DispatchResult {
+				let ad = maybe_ad.as_mut().ok_or(Error::<T>::AdNotFound)?;
+				ensure!(ad.advertiser == who, Error::<T>::Unauthorized);
+				ensure!(!Self::is_placed(ad_id, ad), Error::<T>::AdStillActive);
+
+				let unlocks_at = BudgetUnlocksAt::<T>::get(ad_id).ok_or(Error::<T>::NothingToWithdraw)?;
+				ensure!(
+					frame_system::Pallet::<T>::block_number() >= unlocks_at,
+					Error::<T>::BudgetStillLocked
+				);
+
+				let amount = ad.remaining_budget;
+				T::Currency::unreserve(&who, amount);
+				ad.remaining_budget = Zero::zero();
+				BudgetUnlocksAt::<T>::remove(ad_id);
+
+				Self::deposit_event(Event::AdBudgetReleased { ad_id, advertiser: who, amount });
+				Ok(())
+			})
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Create a new available ad spot.
+		pub(crate) fn do_create_ad_spot() {
+			let spot_id = NextSpotId::<T>::get();
+			let spot = AdSpot {
+				spot_id,
+				available: true,
+				ad_id: None,
+			};
+			
+			AdSpots::<T>::insert(spot_id, spot);
+			NextSpotId::<T>::put(spot_id.saturating_add(1));
+			
+			Self::deposit_event(Event::AdSpotCreated { spot_id });
+		}
+
+		/// Register `who` as an advertiser, reserving `deposit_amount`.
+		pub(crate) fn do_register_advertiser(
+			who: &T::AccountId,
+			name: Vec<u8>,
+			deposit_amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let now = frame_system::Pallet::<T>::block_number();
+			
+			// Validate that advertiser is not already registered
+			ensure!(
+				!AdvertiserProfiles::<T>::contains_key(who),
+				Error::<T>::AdvertiserAlreadyRegistered
+			);
+
+			// Validate deposit amount meets minimum requirement
+			let min_deposit = T::MinAdvertiserDeposit::get();
+			ensure!(
+				deposit_amount >= min_deposit,
+				Error::<T>::DepositTooLow
+			);
+
+			// Validate advertiser name length
+			let bounded_name = BoundedVec::try_from(name)
+				.map_err(|_| Error::<T>::AdvertiserNameTooLong)?;
+
+			// Check if advertiser has sufficient balance
+			let free_balance = T::Currency::free_balance(who);
+			ensure!(
+				free_balance >= deposit_amount,
+				Error::<T>::InsufficientBalance
+			);
+
+			// Reserve the deposit amount
+			T::Currency::reserve(who, deposit_amount)
+				.map_err(|_| Error::<T>::InsufficientBalance)?;
+
+			// Create advertiser profile
+			let profile = AdvertiserProfile {
+				account_id: who.clone(),
+				name: bounded_name,
+				registration_block: now,
+				deposit: deposit_amount,
+				active: true,
+				total_funded: Zero::zero(),
+				total_ads: 0,
+				active_ads: 0,
+				needs_top_up: false,
+			};
+
+			// Store the profile
+			AdvertiserProfiles::<T>::insert(who, profile);
+
+			Self::deposit_event(Event::AdvertiserRegistered {
+				advertiser: who.clone(),
+				deposit: deposit_amount,
+			});
+
+			Ok(())
+		}
+
+		/// Submit an ad for `who`, escrowing `funding` as its budget.
+		///
+		/// A `starts_at` that is not in the future starts the ad right away.
+		#[allow(clippy::too_many_arguments)]
+		pub(crate) fn do_submit_ad(
+			who: &T::AccountId,
+			spot_id: u32,
+			name: Vec<u8>,
+			description: Vec<u8>,
+			ipfs_cid: Vec<u8>,
+			creative_duration: u32,
+			funding: BalanceOf<T>,
+			starts_at: Option<BlockNumberFor<T>>,
+			ends_at: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			// Verify advertiser is registered
+			let mut profile = AdvertiserProfiles::<T>::get(who)
+				.ok_or(Error::<T>::AdvertiserNotRegistered)?;
+
+			// Verify advertiser is active
+			ensure!(profile.active, Error::<T>::AdvertiserNotRegistered);
+			ensure!(!profile.needs_top_up, Error::<T>::DepositTooLow);
+
+			// Verify ad spot exists and is available
+			ensure!(AdSpots::<T>::contains_key(spot_id), Error::<T>::AdSpotNotFound);
+			let spot = AdSpots::<T>::get(spot_id).ok_or(Error::<T>::AdSpotNotFound)?;
+			let leased_to_caller = spot.ad_id.is_none() &&
+				SpotLeases::<T>::get(spot_id).map_or(false, |lease| lease.holder == *who);
+			ensure!(spot.available || leased_to_caller, Error::<T>::AdSpotNotAvailable);
+			
+			// Validate inputs
+			let bounded_name = BoundedVec::try_from(name)
+				.map_err(|_| Error::<T>::AdNameTooLong)?;
+			let bounded_description = BoundedVec::try_from(description)
+				.map_err(|_| Error::<T>::AdDescriptionTooLong)?;
+			let bounded_cid = BoundedVec::try_from(ipfs_cid)
+				.map_err(|_| Error::<T>::CidTooLong)?;
+
+			// Validate the campaign schedule
+			let now = frame_system::Pallet::<T>::block_number();
+			let starts_at = starts_at.filter(|starts_at| *starts_at > now).unwrap_or(now);
+			if let Some(ends_at) = ends_at {
+				ensure!(ends_at > starts_at, Error::<T>::InvalidSchedule);
+			}
+
+			// Validate and escrow the ad budget
+			ensure!(!funding.is_zero(), Error::<T>::InsufficientFunding);
+			ensure!(
+				T::Currency::free_balance(who) >= funding,
+				Error::<T>::InsufficientBalance
+			);
+			T::Currency::reserve(who, funding)
+				.map_err(|_| Error::<T>::InsufficientBalance)?;
+			
+			// Create ad, pending moderation unless the advertiser has a good track record
+			let ad_id = NextAdId::<T>::get();
+			let approved = Self::is_auto_approved(who);
+			let live = approved && starts_at == now;
+			// Ads starting now go live on approval, as `on_initialize` already ran this block
+			if starts_at > now {
+				AdsStartingAt::<T>::try_append(starts_at, ad_id)
+					.map_err(|_| Error::<T>::TooManyScheduledAds)?;
+			}
+			if let Some(ends_at) = ends_at {
+				AdsEndingAt::<T>::try_append(ends_at, ad_id)
+					.map_err(|_| Error::<T>::TooManyScheduledAds)?;
+			}
+			let ad = AdMetadata {
+				advertiser: who.clone(),
+				name: bounded_name,
+				description: bounded_description,
+				ipfs_cid: bounded_cid,
+				creative_version: 1,
+				spot_id,
+				funding,
+				remaining_budget: funding,
+				views: 0,
+				active: live,
+				starts_at,
+				ends_at,
+			};
+			
+			// Store ad
+			Ads::<T>::insert(ad_id, ad);
+			CreativeDurations::<T>::insert(ad_id, creative_duration);
+			if approved {
+				ModerationRecords::<T>::mutate(who, |record| record.approved.saturating_inc());
+			} else {
+				Moderation::<T>::insert(ad_id, ModerationStatus::Pending);
+			}
+			AdsByAdvertiser::<T>::insert(who, ad_id, ());
+			NextAdId::<T>::put(ad_id.saturating_add(1));
+
+			// Update advertiser profile
+			profile.total_funded = profile.total_funded.saturating_add(funding);
+			profile.total_ads = profile.total_ads.saturating_add(1);
+			profile.active_ads = profile.active_ads.saturating_add(1);
+			AdvertiserProfiles::<T>::insert(who, profile);
+			
+			// Place the ad in the spot
+			AdSpots::<T>::mutate(spot_id, |maybe_spot| {
+				if let Some(spot) = maybe_spot {
+					spot.available = false;
+					spot.ad_id = Some(ad_id);
+				}
+			});
+			
+			Self::deposit_event(Event::AdSubmitted { ad_id, advertiser: who.clone(), spot_id });
+			if approved {
+				Self::deposit_event(Event::AdApproved { ad_id });
+			}
+			if live {
+				Self::deposit_event(Event::AdActivated { ad_id });
+			}
+			Ok(())
+		}
+
+		/// Whether payments can be debited from `ad`: it is active, or deactivated and its
+		/// budget is still locked.
+		fn is_payable(ad_id: u32, ad: &AdMetadata<T>) -> bool {
+			let now = frame_system::Pallet::<T>::block_number();
+			ad.active || BudgetUnlocksAt::<T>::get(ad_id).map_or(false, |unlocks_at| now < unlocks_at)
+		}
+
+		/// Whether `ad` still holds its spot, i.e. it is active or scheduled to start.
+		pub(crate) fn is_placed(ad_id: u32, ad: &AdMetadata<T>) -> bool {
+			AdSpots::<T>::get(ad.spot_id).map_or(false, |spot| spot.ad_id == Some(ad_id))
+		}
+
+		/// Whether new ads of `who` skip moderation.
+		fn is_auto_approved(who: &T::AccountId) -> bool {
+			AutoApproveAfter::<T>::get().map_or(false, |min_approved| {
+				let record = ModerationRecords::<T>::get(who);
+				record.rejected == 0 && record.approved >= min_approved
+			})
+		}
+
+		/// Approve a pending or appealed ad, putting it live if its start has passed.
+		pub(crate) fn do_approve_ad(ad_id: u32) -> DispatchResult {
+			Ads::<T>::try_mutate(ad_id, |maybe_ad| -> DispatchResult {
+				let ad = maybe_ad.as_mut().ok_or(Error::<T>::AdNotFound)?;
+				ensure!(Self::is_placed(ad_id, ad), Error::<T>::AdNotActive);
+
+				match Moderation::<T>::get(ad_id) {
+					ModerationStatus::Pending => {},
+					ModerationStatus::Appealed { bond, .. } => {
+						T::Currency::unreserve(&ad.advertiser, bond);
+						ModerationRecords::<T>::mutate(&ad.advertiser, |record| {
+							record.rejected.saturating_dec()
+						});
+					},
+					_ => return Err(Error::<T>::AdNotUnderReview.into()),
+				}
+				Moderation::<T>::remove(ad_id);
+				ModerationRecords::<T>::mutate(&ad.advertiser, |record| {
+					record.approved.saturating_inc()
+				});
+				Self::deposit_event(Event::AdApproved { ad_id });
+
+				if ad.starts_at <= frame_system::Pallet::<T>::block_number() {
+					ad.active = true;
+					Self::deposit_event(Event::AdActivated { ad_id });
+				}
+				Ok(())
+			})
+		}
+
+		/// Put a scheduled ad live, unless it was withdrawn before its start or is not
+		/// approved yet.
+		pub(crate) fn do_start_scheduled_ad(ad_id: u32) {
+			Ads::<T>::mutate(ad_id, |maybe_ad| {
+				if let Some(ad) = maybe_ad.as_mut() {
+					let approved = Moderation::<T>::get(ad_id) == ModerationStatus::Approved;
+					if !ad.active && approved && Self::is_placed(ad_id, ad) {
+						ad.active = true;
+						Self::deposit_event(Event::AdActivated { ad_id });
+					}
+				}
+			});
+		}
+
+		/// Deactivate a time-bounded ad at its end block, if it still holds its spot.
+		///
+		/// This also takes down ads that were still waiting for moderation.
+		pub(crate) fn do_expire_ad(ad_id: u32) {
+			Ads::<T>::mutate(ad_id, |maybe_ad| {
+				if let Some(ad) = maybe_ad.as_mut().filter(|ad| Self::is_placed(ad_id, ad)) {
+					Self::do_deactivate_ad(ad_id, ad);
+					Self::deposit_event(Event::AdExpired { ad_id });
+				}
+			});
+		}
+
+		/// Deactivate `ad`, lock its unspent budget for withdrawal and release its spot.
+		///
+		/// Used whenever an ad stops running or is withdrawn before its start: deactivated by
+		/// its advertiser, out of budget, expired, at the end of its spot lease, reclaimed by
+		/// root or taken down by moderation or a slash. `ad` must still hold its spot.
+		pub(crate) fn do_deactivate_ad(ad_id: u32, ad: &mut AdMetadata<T>) {
+			ad.active = false;
+			AdvertiserProfiles::<T>::mutate(&ad.advertiser, |maybe_profile| {
+				if let Some(profile) = maybe_profile {
+					profile.active_ads = profile.active_ads.saturating_sub(1);
+				}
+			});
+
+			// An open appeal is withdrawn with the ad
+			if let ModerationStatus::Appealed { reason, bond } = Moderation::<T>::get(ad_id) {
+				T::Currency::unreserve(&ad.advertiser, bond);
+				Moderation::<T>::insert(ad_id, ModerationStatus::Rejected { reason });
+			}
+
+			Self::deposit_event(Event::AdDeactivated { ad_id });
+
+			// Keep the unspent budget in escrow for sponsorships already underway
+			if !ad.remaining_budget.is_zero() {
+				let unlocks_at = frame_system::Pallet::<T>::block_number()
+					.saturating_add(T::WithdrawalCooldown::get());
+				BudgetUnlocksAt::<T>::insert(ad_id, unlocks_at);
+				Self::deposit_event(Event::AdBudgetLocked {
+					ad_id,
+					amount: ad.remaining_budget,
+					unlocks_at,
+				});
+			}
+
+			AdSpots::<T>::mutate(ad.spot_id, |maybe_spot| {
+				if let Some(spot) = maybe_spot.as_mut().filter(|spot| spot.ad_id == Some(ad_id)) {
+					// A leased spot stays reserved for the lease holder
+					spot.available = !SpotLeases::<T>::contains_key(spot.spot_id);
+					spot.ad_id = None;
+					Self::deposit_event(Event::AdSpotReleased { spot_id: spot.spot_id, ad_id });
+				}
+			});
+		}
+
+		/// Close the auction for `spot_id`, leasing the spot to the highest bidder.
+		///
+		/// The winning bid goes to `AuctionRevenue` and all other bids are unreserved.
+		/// Without bids the spot becomes available again. Returns the number of bids.
+		pub(crate) fn do_close_spot_auction(spot_id: u32, now: BlockNumberFor<T>) -> u32 {
+			let Some(auction) = SpotAuctions::<T>::take(spot_id) else { return 0 };
+
+			let winner = auction
+				.bids
+				.iter()
+				.max_by_key(|(_, bid)| *bid)
+				.map(|(bidder, bid)| (bidder.clone(), *bid));
+			for (bidder, bid) in &auction.bids {
+				if winner.as_ref().map_or(true, |(winner, _)| winner != bidder) {
+					T::Currency::unreserve(bidder, *bid);
+				}
+			}
+
+			let lease_ends_at = now.saturating_add(auction.lease_period);
+			match &winner {
+				Some((holder, bid)) => {
+					let (revenue, _) = T::Currency::slash_reserved(holder, *bid);
+					T::AuctionRevenue::on_unbalanced(revenue);
+					SpotLeases::<T>::insert(
+						spot_id,
+						SpotLease { holder: holder.clone(), ends_at: lease_ends_at },
+					);
+				},
+				None => Self::set_spot_available(spot_id),
+			}
+
+			let (winner, amount) =
+				winner.map_or((None, Zero::zero()), |(winner, bid)| (Some(winner), bid));
+			Self::deposit_event(Event::SpotAuctionClosed { spot_id, winner, amount, lease_ends_at });
+			auction.bids.len() as u32
+		}
+
+		/// End the lease of `spot_id` that runs until `now`, taking down the ad placed in it.
+		///
+		/// Leases are queued when their auction opens, so the queue can name spots whose
+		/// auction had no winner or that have been leased again since.
+		pub(crate) fn do_end_spot_lease(spot_id: u32, now: BlockNumberFor<T>) {
+			let Some(lease) = SpotLeases::<T>::get(spot_id).filter(|lease| lease.ends_at == now)
+			else {
+				return
+			};
+			SpotLeases::<T>::remove(spot_id);
+
+			if let Some(ad_id) = AdSpots::<T>::get(spot_id).and_then(|spot| spot.ad_id) {
+				Ads::<T>::mutate(ad_id, |maybe_ad| {
+					if let Some(ad) = maybe_ad {
+						Self::do_deactivate_ad(ad_id, ad);
+					}
+				});
+			}
+
+			Self::set_spot_available(spot_id);
+			Self::deposit_event(Event::SpotLeaseEnded { spot_id, holder: lease.holder });
+		}
+
+		/// Reactivate `who` if their suspension ends at `now`.
+		///
+		/// Suspensions are queued when imposed, so the queue can name advertisers whose
+		/// suspension was replaced since.
+		pub(crate) fn do_end_suspension(who: &T::AccountId, now: BlockNumberFor<T>) {
+			if Suspensions::<T>::get(who) != Some(now) {
+				return
+			}
+			Suspensions::<T>::remove(who);
+
+			AdvertiserProfiles::<T>::mutate(who, |maybe_profile| {
+				if let Some(profile) = maybe_profile {
+					profile.active = true;
+				}
+			});
+			Self::deposit_event(Event::AdvertiserSuspensionEnded { advertiser: who.clone() });
+		}
+
+		fn set_spot_available(spot_id: u32) {
+			AdSpots::<T>::mutate(spot_id, |maybe_spot| {
+				if let Some(spot) = maybe_spot.as_mut().filter(|spot| spot.ad_id.is_none()) {
+					spot.available = true;
+				}
+			});
+		}
+
+		/// Active ads with an id of at least `start`, in id order, at most `limit` of them.
+		pub fn active_ads(start: u32, limit: u32) -> Vec<(u32, AdMetadata<T>)> {
+			(start..NextAdId::<T>::get())
+				.filter_map(|ad_id| Ads::<T>::get(ad_id).filter(|ad| ad.active).map(|ad| (ad_id, ad)))
+				.take(limit as usize)
+				.collect()
+		}
+
+		/// Ads of `who` with an id of at least `start`, in id order, at most `limit` of them.
+		pub fn advertiser_ads(
+			who: &T::AccountId,
+			start: u32,
+			limit: u32,
+		) -> Vec<(u32, AdMetadata<T>)> {
+			let mut ad_ids: Vec<u32> =
+				AdsByAdvertiser::<T>::iter_key_prefix(who).filter(|ad_id| *ad_id >= start).collect();
+			ad_ids.sort_unstable();
+			ad_ids
+				.into_iter()
+				.take(limit as usize)
+				.filter_map(|ad_id| Ads::<T>::get(ad_id).map(|ad| (ad_id, ad)))
+				.collect()
+		}
+
+		/// Ads waiting for a moderation decision, pending or appealed, with an id of at least
+		/// `start`, in id order, at most `limit` of them.
+		pub fn ads_under_review(start: u32, limit: u32) -> Vec<(u32, AdMetadata<T>)> {
+			let mut ad_ids: Vec<u32> = Moderation::<T>::iter()
+				.filter(|(ad_id, status)| {
+					*ad_id >= start &&
+						matches!(
+							status,
+							ModerationStatus::Pending | ModerationStatus::Appealed { .. }
+						)
+				})
+				.map(|(ad_id, _)| ad_id)
+				.collect();
+			ad_ids.sort_unstable();
+			ad_ids
+				.into_iter()
+				.filter_map(|ad_id| {
+					Ads::<T>::get(ad_id)
+						.filter(|ad| Self::is_placed(ad_id, ad))
+						.map(|ad| (ad_id, ad))
+				})
+				.take(limit as usize)
+				.collect()
+		}
+
+		/// Available ad spots with an id of at least `start`, in id order, at most `limit` of them.
+		pub fn available_spots(start: u32, limit: u32) -> Vec<AdSpot> {
+			(start..NextSpotId::<T>::get())
+				.filter_map(|spot_id| AdSpots::<T>::get(spot_id).filter(|spot| spot.available))
+				.take(limit as usize)
+				.collect()
+		}
+
+		/// Pay `amount` out of an ad's escrowed budget.
+		///
+		/// The funds are taken from the advertiser's reserved balance and moved to
+		/// `beneficiary`, or slashed when no beneficiary is given (e.g. to cover a fee).
+		pub fn spend_budget(
+			ad_id: u32,
+			amount: BalanceOf<T>,
+			beneficiary: Option<&T::AccountId>,
+		) -> DispatchResult {
+			Self::debit_budget(ad_id, amount)?;
+			Self::settle_budget(ad_id, amount, beneficiary)
+		}
+
+		/// Take `amount` out of the remaining budget of an ad without releasing the escrow.
+		pub fn debit_budget(ad_id: u32, amount: BalanceOf<T>) -> DispatchResult {
+			Ads::<T>::try_mutate(ad_id, |maybe_ad| -> DispatchResult {
+				let ad = maybe_ad.as_mut().ok_or(Error::<T>::AdNotFound)?;
+
+				ensure!(Self::is_payable(ad_id, ad), Error::<T>::AdNotActive);
+				ensure!(ad.remaining_budget >= amount, Error::<T>::InsufficientAdBudget);
+				ad.remaining_budget = ad.remaining_budget.saturating_sub(amount);
+
+				Ok(())
+			})
+		}
+
+		/// Give a debited `amount` back to an ad.
+		///
+		/// If the budget of the ad has been withdrawn in the meantime, the amount is released
+		/// to the advertiser instead.
+		pub fn credit_budget(ad_id: u32, amount: BalanceOf<T>) -> DispatchResult {
+			Ads::<T>::try_mutate(ad_id, |maybe_ad| -> DispatchResult {
+				let ad = maybe_ad.as_mut().ok_or(Error::<T>::AdNotFound)?;
+
+				if Self::is_placed(ad_id, ad) || BudgetUnlocksAt::<T>::contains_key(ad_id) {
+					ad.remaining_budget = ad.remaining_budget.saturating_add(amount);
+				} else {
+					T::Currency::unreserve(&ad.advertiser, amount);
+				}
+
+				Ok(())
+			})
+		}
+
+		/// Pay a debited `amount` out of the advertiser's escrow.
+		///
+		/// An ad whose budget is used up by the payment is deactivated.
+		pub fn settle_budget(
+			ad_id: u32,
+			amount: BalanceOf<T>,
+			beneficiary: Option<&T::AccountId>,
+		) -> DispatchResult {
+			Ads::<T>::try_mutate(ad_id, |maybe_ad| -> DispatchResult {
+				let ad = maybe_ad.as_mut().ok_or(Error::<T>::AdNotFound)?;
+
+				match beneficiary {
+					Some(dest) => {
+						let unmoved = T::Currency::repatriate_reserved(
+							&ad.advertiser,
+							dest,
+							amount,
+							BalanceStatus::Free,
+						)?;
+						ensure!(unmoved.is_zero(), Error::<T>::InsufficientAdBudget);
+					},
+					None => {
+						let (_, unslashed) = T::Currency::slash_reserved(&ad.advertiser, amount);
+						ensure!(unslashed.is_zero(), Error::<T>::InsufficientAdBudget);
+					},
+				}
+
+				Self::deposit_event(Event::AdBudgetSpent {
+					ad_id,
+					amount,
+					remaining: ad.remaining_budget,
+				});
+
+				if ad.remaining_budget.is_zero() && Self::is_placed(ad_id, ad) {
+					Self::deposit_event(Event::AdBudgetExhausted { ad_id });
+					Self::do_deactivate_ad(ad_id, ad);
+				}
+				Ok(())
+			})
+		}
+	}
+
+	impl<T: Config> crate::AdLookup for Pallet<T> {
+		fn is_servable(ad_id: u32) -> bool {
+			Ads::<T>::get(ad_id).map_or(false, |ad| {
+				ad.active &&
+					!ad.remaining_budget.is_zero() &&
+					matches!(Moderation::<T>::get(ad_id), ModerationStatus::Approved)
+			})
+		}
+
+		fn creative_duration(ad_id: u32) -> u32 {
+			CreativeDurations::<T>::get(ad_id)
+		}
+
+		fn creative_version(ad_id: u32) -> Option<u32> {
+			Ads::<T>::get(ad_id).map(|ad| ad.creative_version)
+		}
+	}
+
+	impl<T: Config> crate::AdBudgetProvider<T::AccountId, BalanceOf<T>> for Pallet<T> {
+		fn is_active(ad_id: u32) -> bool {
+			Ads::<T>::get(ad_id).map_or(false, |ad| ad.active)
+		}
+
+		fn is_payable(ad_id: u32) -> bool {
+			Ads::<T>::get(ad_id).map_or(false, |ad| Self::is_payable(ad_id, &ad))
+		}
+
+		fn remaining_budget(ad_id: u32) -> Option<BalanceOf<T>> {
+			Ads::<T>::get(ad_id).map(|ad| ad.remaining_budget)
+		}
+
+		fn debit(ad_id: u32, amount: BalanceOf<T>) -> DispatchResult {
+			Self::debit_budget(ad_id, amount)
+		}
+
+		fn credit(ad_id: u32, amount: BalanceOf<T>) -> DispatchResult {
+			Self::credit_budget(ad_id, amount)
+		}
+
+		fn settle(
+			ad_id: u32,
+			amount: BalanceOf<T>,
+			beneficiary: Option<&T::AccountId>,
+		) -> DispatchResult {
+			Self::settle_budget(ad_id, amount, beneficiary)
+		}
 	}
 }
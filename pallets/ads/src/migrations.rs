@@ -0,0 +1,563 @@
+//! Storage migrations for pallet-ads
+
+use super::*;
+use frame_support::{
+	migrations::VersionedMigration, pallet_prelude::*, traits::UncheckedOnRuntimeUpgrade,
+};
+use frame_system::pallet_prelude::BlockNumberFor;
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+use sp_std::vec::Vec;
+
+/// Log target of the pallet-ads migrations.
+const LOG_TARGET: &str = "runtime::ads";
+
+/// Layouts of the unversioned baseline pallet.
+///
+/// Baseline budgets were plain `u128`s rather than the balance of `Config::Currency`.
+mod v0 {
+	use super::*;
+
+	/// Ad spot before it recorded the ad placed in it.
+	#[derive(Encode, Decode)]
+	pub(super) struct AdSpot {
+		pub spot_id: u32,
+		pub available: bool,
+	}
+
+	/// Ad metadata before ads recorded their spot.
+	#[derive(Encode, Decode)]
+	pub(super) struct AdMetadata<T: Config> {
+		pub advertiser: T::AccountId,
+		pub name: BoundedVec<u8, T::MaxAdNameLength>,
+		pub description: BoundedVec<u8, T::MaxAdDescriptionLength>,
+		pub ipfs_cid: BoundedVec<u8, T::MaxCidLength>,
+		pub funding: u128,
+		pub remaining_budget: u128,
+		pub views: u64,
+		pub active: bool,
+	}
+
+	/// Advertiser profile before `active_ads` was added.
+	#[derive(Encode, Decode)]
+	pub(super) struct AdvertiserProfile<T: Config> {
+		pub account_id: T::AccountId,
+		pub name: BoundedVec<u8, T::MaxAdvertiserNameLength>,
+		pub registration_block: BlockNumberFor<T>,
+		pub deposit: BalanceOf<T>,
+		pub active: bool,
+		pub total_funded: u128,
+		pub total_ads: u32,
+	}
+
+	/// `AdSpots` with the baseline layout.
+	#[frame_support::storage_alias]
+	pub(super) type AdSpots<T: Config> = StorageMap<Pallet<T>, Blake2_128Concat, u32, AdSpot>;
+
+	/// `Ads` with the baseline layout.
+	#[frame_support::storage_alias]
+	pub(super) type Ads<T: Config> = StorageMap<Pallet<T>, Blake2_128Concat, u32, AdMetadata<T>>;
+}
+
+/// Version 1 links ads and spots, schedules existing ads, indexes ads by advertiser and counts
+/// the active ads of each advertiser.
+pub mod v1 {
+	use super::*;
+	use sp_runtime::{
+		traits::{Saturating, Zero},
+		SaturatedConversion,
+	};
+	use sp_std::collections::btree_map::BTreeMap;
+
+	/// `AdMetadata::spot_id` of baseline ads, which didn't record the spot they took.
+	pub const NO_SPOT: u32 = u32::MAX;
+
+	/// Ad metadata once ads recorded their spot, before campaigns were scheduled.
+	#[derive(Encode, Decode)]
+	struct SpotAdMetadata<T: Config> {
+		advertiser: T::AccountId,
+		name: BoundedVec<u8, T::MaxAdNameLength>,
+		description: BoundedVec<u8, T::MaxAdDescriptionLength>,
+		ipfs_cid: BoundedVec<u8, T::MaxCidLength>,
+		spot_id: u32,
+		funding: BalanceOf<T>,
+		remaining_budget: BalanceOf<T>,
+		views: u64,
+		active: bool,
+	}
+
+	/// `Ads` once ads recorded their spot.
+	#[frame_support::storage_alias]
+	type SpotAds<T: Config> = StorageMap<Pallet<T>, Blake2_128Concat, u32, SpotAdMetadata<T>>;
+
+	/// Advertiser profile at storage version 1.
+	#[derive(Encode, Decode)]
+	pub(super) struct AdvertiserProfile<T: Config> {
+		pub account_id: T::AccountId,
+		pub name: BoundedVec<u8, T::MaxAdvertiserNameLength>,
+		pub registration_block: BlockNumberFor<T>,
+		pub deposit: BalanceOf<T>,
+		pub active: bool,
+		pub total_funded: BalanceOf<T>,
+		pub total_ads: u32,
+		pub active_ads: u32,
+	}
+
+	/// Ad metadata at storage versions 1 and 2.
+	#[derive(Encode, Decode)]
+	pub(super) struct AdMetadata<T: Config> {
+		pub advertiser: T::AccountId,
+		pub name: BoundedVec<u8, T::MaxAdNameLength>,
+		pub description: BoundedVec<u8, T::MaxAdDescriptionLength>,
+		pub ipfs_cid: BoundedVec<u8, T::MaxCidLength>,
+		pub spot_id: u32,
+		pub funding: BalanceOf<T>,
+		pub remaining_budget: BalanceOf<T>,
+		pub views: u64,
+		pub active: bool,
+		pub starts_at: BlockNumberFor<T>,
+		pub ends_at: Option<BlockNumberFor<T>>,
+	}
+
+	/// `Ads` at storage versions 1 and 2.
+	#[frame_support::storage_alias]
+	pub(super) type Ads<T: Config> = StorageMap<Pallet<T>, Blake2_128Concat, u32, AdMetadata<T>>;
+
+	/// Whether `ad` still holds its spot.
+	fn is_placed<T: Config>(ad_id: u32, ad: &AdMetadata<T>) -> bool {
+		crate::AdSpots::<T>::get(ad.spot_id).map_or(false, |spot| spot.ad_id == Some(ad_id))
+	}
+
+	/// Add `AdSpot::ad_id` and `AdMetadata::spot_id`.
+	///
+	/// Baseline ads didn't record the spot they took, so they can't be linked back to it: they
+	/// get [`NO_SPOT`] and every spot is released. Their budgets were never reserved from the
+	/// advertiser either, so they are zeroed and the ads deactivated. Ads that don't decode are
+	/// logged and removed.
+	fn link_spots<T: Config>() -> Weight {
+		let mut reads = 0u64;
+		let mut writes = 0u64;
+
+		crate::AdSpots::<T>::translate::<v0::AdSpot, _>(|_, old| {
+			reads.saturating_inc();
+			writes.saturating_inc();
+			Some(AdSpot { spot_id: old.spot_id, available: true, ad_id: None })
+		});
+
+		let mut skipped = 0u32;
+		let mut unfunded = 0u32;
+		for ad_id in v0::Ads::<T>::iter_keys().collect::<Vec<_>>() {
+			reads.saturating_inc();
+			writes.saturating_inc();
+			let Some(old) = v0::Ads::<T>::get(ad_id) else {
+				log::warn!(target: LOG_TARGET, "removing undecodable ad {ad_id}");
+				v0::Ads::<T>::remove(ad_id);
+				skipped.saturating_inc();
+				continue;
+			};
+			if old.remaining_budget != 0 || old.active {
+				unfunded.saturating_inc();
+			}
+			SpotAds::<T>::insert(
+				ad_id,
+				SpotAdMetadata {
+					advertiser: old.advertiser,
+					name: old.name,
+					description: old.description,
+					ipfs_cid: old.ipfs_cid,
+					spot_id: NO_SPOT,
+					funding: old.funding.saturated_into(),
+					remaining_budget: Zero::zero(),
+					views: old.views,
+					active: false,
+				},
+			);
+		}
+		if skipped > 0 {
+			log::warn!(target: LOG_TARGET, "removed {skipped} undecodable ads");
+		}
+		if unfunded > 0 {
+			log::info!(target: LOG_TARGET, "zeroed the unreserved budgets of {unfunded} ads");
+		}
+
+		T::DbWeight::get().reads_writes(reads, writes)
+	}
+
+	/// Add `AdMetadata::starts_at` and `AdMetadata::ends_at`.
+	///
+	/// Existing campaigns have been running since genesis and don't end.
+	fn schedule_ads<T: Config>() -> Weight {
+		let mut count = 0u64;
+
+		Ads::<T>::translate::<SpotAdMetadata<T>, _>(|_, old| {
+			count.saturating_inc();
+			Some(AdMetadata {
+				advertiser: old.advertiser,
+				name: old.name,
+				description: old.description,
+				ipfs_cid: old.ipfs_cid,
+				spot_id: old.spot_id,
+				funding: old.funding,
+				remaining_budget: old.remaining_budget,
+				views: old.views,
+				active: old.active,
+				starts_at: Zero::zero(),
+				ends_at: None,
+			})
+		});
+
+		T::DbWeight::get().reads_writes(count, count)
+	}
+
+	/// `AdvertiserProfiles` at storage version 1.
+	#[frame_support::storage_alias]
+	pub(super) type AdvertiserProfiles<T: Config> = StorageMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		<T as frame_system::Config>::AccountId,
+		AdvertiserProfile<T>,
+	>;
+
+	/// Link spots and schedule ads, then fill `AdsByAdvertiser` from `Ads` and set
+	/// `AdvertiserProfile::active_ads`.
+	pub struct InnerMigrateV0ToV1<T>(PhantomData<T>);
+
+	impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV0ToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let mut weight = link_spots::<T>();
+			weight.saturating_accrue(schedule_ads::<T>());
+			let mut reads = 0u64;
+			let mut writes = 0u64;
+
+			let mut active_ads = BTreeMap::<T::AccountId, u32>::new();
+			for (ad_id, ad) in Ads::<T>::iter() {
+				reads.saturating_accrue(2);
+				writes.saturating_accrue(1);
+				AdsByAdvertiser::<T>::insert(&ad.advertiser, ad_id, ());
+				if is_placed(ad_id, &ad) {
+					active_ads.entry(ad.advertiser).or_default().saturating_inc();
+				}
+			}
+
+			AdvertiserProfiles::<T>::translate::<v0::AdvertiserProfile<T>, _>(|who, old| {
+				reads.saturating_inc();
+				writes.saturating_inc();
+				Some(AdvertiserProfile {
+					account_id: old.account_id,
+					name: old.name,
+					registration_block: old.registration_block,
+					deposit: old.deposit,
+					active: old.active,
+					total_funded: old.total_funded.saturated_into(),
+					total_ads: old.total_ads,
+					active_ads: active_ads.get(&who).copied().unwrap_or_default(),
+				})
+			});
+
+			weight.saturating_accrue(T::DbWeight::get().reads_writes(reads, writes));
+			weight
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			let ads = v0::Ads::<T>::iter_values().count() as u32;
+			let profiles = AdvertiserProfiles::<T>::iter_keys().count() as u32;
+			let spots = v0::AdSpots::<T>::iter_keys().count() as u32;
+			Ok((ads, profiles, spots).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			let (ads, profiles, spots) = <(u32, u32, u32)>::decode(&mut &state[..])
+				.map_err(|_| "pre-upgrade state must decode")?;
+
+			ensure!(
+				crate::AdSpots::<T>::iter_values()
+					.filter(|spot| spot.available && spot.ad_id.is_none())
+					.count() as u32 == spots,
+				"every spot must decode and be released"
+			);
+			ensure!(
+				Ads::<T>::iter_values().count() as u32 == ads,
+				"every decodable ad must be scheduled"
+			);
+			ensure!(
+				Ads::<T>::iter_keys().count() as u32 == ads,
+				"undecodable ads must be removed"
+			);
+			ensure!(
+				Ads::<T>::iter_values().all(|ad| !ad.active && ad.remaining_budget.is_zero()),
+				"unreserved budgets must be zeroed"
+			);
+			ensure!(
+				AdsByAdvertiser::<T>::iter_keys().count() as u32 == ads,
+				"every ad must be indexed by its advertiser"
+			);
+			ensure!(
+				AdvertiserProfiles::<T>::iter_values().count() as u32 == profiles,
+				"every advertiser profile must decode"
+			);
+			for (who, profile) in AdvertiserProfiles::<T>::iter() {
+				let active_ads = AdsByAdvertiser::<T>::iter_key_prefix(&who)
+					.filter(|ad_id| {
+						Ads::<T>::get(ad_id).map_or(false, |ad| is_placed(*ad_id, &ad))
+					})
+					.count() as u32;
+				ensure!(profile.active_ads == active_ads, "active ad count must match the ads");
+			}
+
+			Ok(())
+		}
+	}
+
+	/// Migrate pallet-ads from storage version 0 to 1.
+	pub type MigrateV0ToV1<T> = VersionedMigration<
+		0,
+		1,
+		InnerMigrateV0ToV1<T>,
+		Pallet<T>,
+		<T as frame_system::Config>::DbWeight,
+	>;
+}
+
+/// Version 2 drops the legacy `Advertisers` flags in favour of `AdvertiserProfiles`.
+pub mod v2 {
+	use super::*;
+	use sp_runtime::traits::{Saturating, Zero};
+
+	/// Legacy advertiser flags, written next to `AdvertiserProfiles` until version 2.
+	#[frame_support::storage_alias]
+	pub(super) type Advertisers<T: Config> = StorageMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		<T as frame_system::Config>::AccountId,
+		bool,
+		ValueQuery,
+	>;
+
+	/// Add `AdvertiserProfile::needs_top_up`, turn advertisers that only have a legacy flag
+	/// into profiles without deposit that need a top-up, and remove `Advertisers`.
+	pub struct InnerMigrateV1ToV2<T>(PhantomData<T>);
+
+	impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV1ToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let mut reads = 0u64;
+			let mut writes = 0u64;
+
+			AdvertiserProfiles::<T>::translate::<v1::AdvertiserProfile<T>, _>(|_, old| {
+				reads.saturating_inc();
+				writes.saturating_inc();
+				Some(AdvertiserProfile {
+					account_id: old.account_id,
+					name: old.name,
+					registration_block: old.registration_block,
+					deposit: old.deposit,
+					active: old.active,
+					total_funded: old.total_funded,
+					total_ads: old.total_ads,
+					active_ads: old.active_ads,
+					needs_top_up: false,
+				})
+			});
+
+			let now = frame_system::Pallet::<T>::block_number();
+			for (who, registered) in Advertisers::<T>::drain() {
+				reads.saturating_accrue(2);
+				writes.saturating_inc();
+				if registered && !AdvertiserProfiles::<T>::contains_key(&who) {
+					writes.saturating_inc();
+					AdvertiserProfiles::<T>::insert(
+						&who,
+						AdvertiserProfile {
+							account_id: who.clone(),
+							name: BoundedVec::new(),
+							registration_block: now,
+							deposit: Zero::zero(),
+							active: true,
+							total_funded: Zero::zero(),
+							total_ads: 0,
+							active_ads: 0,
+							needs_top_up: true,
+						},
+					);
+				}
+			}
+
+			T::DbWeight::get().reads_writes(reads, writes)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			let profiles = v1::AdvertiserProfiles::<T>::iter_keys().count() as u32;
+			let legacy_only = Advertisers::<T>::iter()
+				.filter(|(who, registered)| {
+					*registered && !v1::AdvertiserProfiles::<T>::contains_key(who)
+				})
+				.count() as u32;
+			Ok((profiles, legacy_only).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			let (profiles, legacy_only) = <(u32, u32)>::decode(&mut &state[..])
+				.map_err(|_| "pre-upgrade state must decode")?;
+
+			ensure!(Advertisers::<T>::iter_keys().next().is_none(), "legacy flags must be removed");
+			ensure!(
+				AdvertiserProfiles::<T>::iter_values().count() as u32 ==
+					profiles.saturating_add(legacy_only),
+				"every advertiser must have a profile that decodes"
+			);
+			ensure!(
+				AdvertiserProfiles::<T>::iter_values().filter(|profile| profile.needs_top_up).count()
+					as u32 == legacy_only,
+				"only legacy advertisers must need a top-up"
+			);
+
+			Ok(())
+		}
+	}
+
+	/// Migrate pallet-ads from storage version 1 to 2.
+	pub type MigrateV1ToV2<T> = VersionedMigration<
+		1,
+		2,
+		InnerMigrateV1ToV2<T>,
+		Pallet<T>,
+		<T as frame_system::Config>::DbWeight,
+	>;
+}
+
+/// Version 3 numbers ad creatives.
+pub mod v3 {
+	use super::*;
+	use sp_runtime::traits::Saturating;
+
+	/// Set `AdMetadata::creative_version` of existing ads to their first version.
+	pub struct InnerMigrateV2ToV3<T>(PhantomData<T>);
+
+	impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV2ToV3<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let mut translated = 0u64;
+
+			Ads::<T>::translate::<v1::AdMetadata<T>, _>(|_, old| {
+				translated.saturating_inc();
+				Some(AdMetadata {
+					advertiser: old.advertiser,
+					name: old.name,
+					description: old.description,
+					ipfs_cid: old.ipfs_cid,
+					creative_version: 1,
+					spot_id: old.spot_id,
+					funding: old.funding,
+					remaining_budget: old.remaining_budget,
+					views: old.views,
+					active: old.active,
+					starts_at: old.starts_at,
+					ends_at: old.ends_at,
+				})
+			});
+
+			T::DbWeight::get().reads_writes(translated, translated)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			Ok((v1::Ads::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			let ads = u32::decode(&mut &state[..]).map_err(|_| "pre-upgrade state must decode")?;
+
+			ensure!(
+				Ads::<T>::iter_values().filter(|ad| ad.creative_version == 1).count() as u32 == ads,
+				"every ad must decode at its first creative version"
+			);
+
+			Ok(())
+		}
+	}
+
+	/// Migrate pallet-ads from storage version 2 to 3.
+	pub type MigrateV2ToV3<T> = VersionedMigration<
+		2,
+		3,
+		InnerMigrateV2ToV3<T>,
+		Pallet<T>,
+		<T as frame_system::Config>::DbWeight,
+	>;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{new_test_ext, Test, ALICE, BOB};
+	use frame_support::{storage::unhashed, traits::OnRuntimeUpgrade};
+
+	/// Baseline ad of `ALICE` with `funding`, all of it left.
+	fn v0_ad(funding: u128, active: bool) -> v0::AdMetadata<Test> {
+		v0::AdMetadata {
+			advertiser: ALICE,
+			name: BoundedVec::truncate_from(b"Ad".to_vec()),
+			description: BoundedVec::truncate_from(b"An ad".to_vec()),
+			ipfs_cid: BoundedVec::truncate_from(b"QmCid".to_vec()),
+			funding,
+			remaining_budget: funding,
+			views: 7,
+			active,
+		}
+	}
+
+	#[test]
+	fn baseline_storage_migrates_to_v3() {
+		new_test_ext().execute_with(|| {
+			StorageVersion::new(0).put::<Pallet<Test>>();
+			v0::AdSpots::<Test>::insert(0, v0::AdSpot { spot_id: 0, available: false });
+			v0::AdSpots::<Test>::insert(1, v0::AdSpot { spot_id: 1, available: true });
+			v0::Ads::<Test>::insert(0, v0_ad(1_000, true));
+			v0::Ads::<Test>::insert(1, v0_ad(500, false));
+			unhashed::put_raw(&v0::Ads::<Test>::hashed_key_for(2), &[1, 2, 3]);
+			unhashed::put(
+				&AdvertiserProfiles::<Test>::hashed_key_for(ALICE),
+				&v0::AdvertiserProfile::<Test> {
+					account_id: ALICE,
+					name: BoundedVec::truncate_from(b"Alice".to_vec()),
+					registration_block: 1,
+					deposit: 100,
+					active: true,
+					total_funded: 1_500,
+					total_ads: 2,
+				},
+			);
+			v2::Advertisers::<Test>::insert(ALICE, true);
+			v2::Advertisers::<Test>::insert(BOB, true);
+
+			v1::MigrateV0ToV1::<Test>::on_runtime_upgrade();
+			v2::MigrateV1ToV2::<Test>::on_runtime_upgrade();
+			v3::MigrateV2ToV3::<Test>::on_runtime_upgrade();
+
+			assert_eq!(StorageVersion::get::<Pallet<Test>>(), 3);
+			for spot_id in 0..2 {
+				assert_eq!(
+					crate::AdSpots::<Test>::get(spot_id),
+					Some(AdSpot { spot_id, available: true, ad_id: None })
+				);
+			}
+			let ad = crate::Ads::<Test>::get(0).unwrap();
+			assert_eq!((ad.funding, ad.remaining_budget, ad.active), (1_000, 0, false));
+			assert_eq!((ad.spot_id, ad.starts_at, ad.ends_at), (v1::NO_SPOT, 0, None));
+			assert_eq!((ad.views, ad.creative_version), (7, 1));
+			assert_eq!(crate::Ads::<Test>::get(1).map(|ad| ad.funding), Some(500));
+			assert!(!crate::Ads::<Test>::contains_key(2));
+			assert_eq!(AdsByAdvertiser::<Test>::iter_key_prefix(ALICE).count(), 2);
+
+			let alice = AdvertiserProfiles::<Test>::get(ALICE).unwrap();
+			assert_eq!((alice.deposit, alice.total_funded, alice.total_ads), (100, 1_500, 2));
+			assert_eq!((alice.active_ads, alice.needs_top_up), (0, false));
+			let bob = AdvertiserProfiles::<Test>::get(BOB).unwrap();
+			assert_eq!((bob.deposit, bob.needs_top_up), (0, true));
+			assert_eq!(v2::Advertisers::<Test>::iter_keys().count(), 0);
+		});
+	}
+}
@@ -0,0 +1,89 @@
+//! Test environment for pallet-ads
+
+use crate as pallet_ads;
+use frame_support::{
+	derive_impl,
+	traits::{ConstU32, ConstU64, Hooks},
+};
+use frame_system::EnsureRoot;
+use sp_runtime::BuildStorage;
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+pub const ALICE: u64 = 1;
+pub const BOB: u64 = 2;
+
+/// Free balance of every test account at genesis.
+pub const INITIAL_BALANCE: u64 = 10_000;
+
+/// Minimum advertiser deposit.
+pub const MIN_DEPOSIT: u64 = 100;
+
+/// Bond reserved to appeal a rejection.
+pub const APPEAL_BOND: u64 = 10;
+
+/// Blocks the budget of a deactivated ad stays locked.
+pub const WITHDRAWAL_COOLDOWN: u64 = 10;
+
+frame_support::construct_runtime!(
+	pub enum Test {
+		System: frame_system,
+		Balances: pallet_balances,
+		Ads: pallet_ads,
+	}
+);
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+impl frame_system::Config for Test {
+	type Block = Block;
+	type AccountData = pallet_balances::AccountData<u64>;
+}
+
+#[derive_impl(pallet_balances::config_preludes::TestDefaultConfig)]
+impl pallet_balances::Config for Test {
+	type AccountStore = System;
+}
+
+impl pallet_ads::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type MaxAdNameLength = ConstU32<32>;
+	type MaxAdDescriptionLength = ConstU32<64>;
+	type MaxCidLength = ConstU32<64>;
+	type MaxAdvertiserNameLength = ConstU32<32>;
+	type MinAdvertiserDeposit = ConstU64<MIN_DEPOSIT>;
+	type AuctionRevenue = ();
+	type MaxAuctionBids = ConstU32<4>;
+	type MaxAuctionsPerBlock = ConstU32<4>;
+	type MaxScheduledAdsPerBlock = ConstU32<4>;
+	type ModeratorOrigin = EnsureRoot<u64>;
+	type MaxRejectionReasonLength = ConstU32<32>;
+	type AppealBond = ConstU64<APPEAL_BOND>;
+	type GovernanceOrigin = EnsureRoot<u64>;
+	type OnSlash = ();
+	type MaxSuspensionsPerBlock = ConstU32<4>;
+	type MaxCreativeHistory = ConstU32<2>;
+	type WithdrawalCooldown = ConstU64<WITHDRAWAL_COOLDOWN>;
+	type WeightInfo = ();
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(ALICE, INITIAL_BALANCE), (BOB, INITIAL_BALANCE)],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// Advance to block `n`, running the `on_initialize` hook of every block on the way.
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		Ads::on_initialize(System::block_number());
+	}
+}
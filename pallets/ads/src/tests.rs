@@ -0,0 +1,713 @@
+use crate::{
+	mock::*, AdMetadata, AdSpot, AdSpots, AdsStartingAt, AdvertiserProfile, AdvertiserProfiles,
+	BudgetUnlocksAt, CreativeDurations, CreativeHistory, Error, Event, Moderation,
+	ModerationRecord, ModerationRecords, ModerationStatus, NextAdId, NextSpotId, SpotLeases,
+	Suspensions, SuspensionsEndingAt,
+};
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::{DispatchError, Perbill};
+
+fn register(who: u64, deposit: u64) {
+	assert_ok!(Ads::register_advertiser(
+		RuntimeOrigin::signed(who),
+		b"Advertiser".to_vec(),
+		deposit
+	));
+}
+
+fn create_spot() -> u32 {
+	let spot_id = NextSpotId::<Test>::get();
+	assert_ok!(Ads::create_ad_spot(RuntimeOrigin::root()));
+	spot_id
+}
+
+fn submit(
+	who: u64,
+	spot_id: u32,
+	funding: u64,
+	starts_at: Option<u64>,
+	ends_at: Option<u64>,
+) -> u32 {
+	let ad_id = NextAdId::<Test>::get();
+	assert_ok!(Ads::submit_ad(
+		RuntimeOrigin::signed(who),
+		spot_id,
+		b"Ad".to_vec(),
+		b"An ad".to_vec(),
+		b"QmCid".to_vec(),
+		30_000,
+		funding,
+		starts_at,
+		ends_at,
+	));
+	ad_id
+}
+
+/// Register `who`, submit an ad with `funding` in a new spot and approve it.
+fn live_ad(who: u64, funding: u64) -> u32 {
+	if !AdvertiserProfiles::<Test>::contains_key(who) {
+		register(who, MIN_DEPOSIT);
+	}
+	let ad_id = submit(who, create_spot(), funding, None, None);
+	assert_ok!(Ads::approve_ad(RuntimeOrigin::root(), ad_id));
+	ad_id
+}
+
+fn ad(ad_id: u32) -> AdMetadata<Test> {
+	crate::Ads::<Test>::get(ad_id).expect("ad exists")
+}
+
+fn profile(who: u64) -> AdvertiserProfile<Test> {
+	AdvertiserProfiles::<Test>::get(who).expect("advertiser is registered")
+}
+
+#[test]
+fn create_ad_spot_requires_root() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Ads::create_ad_spot(RuntimeOrigin::signed(ALICE)), DispatchError::BadOrigin);
+
+		let spot_id = create_spot();
+		assert_eq!(
+			AdSpots::<Test>::get(spot_id),
+			Some(AdSpot { spot_id, available: true, ad_id: None })
+		);
+	});
+}
+
+#[test]
+fn register_advertiser_reserves_deposit() {
+	new_test_ext().execute_with(|| {
+		register(ALICE, MIN_DEPOSIT);
+
+		assert_eq!(Balances::reserved_balance(ALICE), MIN_DEPOSIT);
+		assert_eq!(profile(ALICE).deposit, MIN_DEPOSIT);
+		assert_noop!(
+			Ads::register_advertiser(RuntimeOrigin::signed(ALICE), b"Alice".to_vec(), MIN_DEPOSIT),
+			Error::<Test>::AdvertiserAlreadyRegistered
+		);
+		assert_noop!(
+			Ads::register_advertiser(RuntimeOrigin::signed(BOB), b"Bob".to_vec(), MIN_DEPOSIT - 1),
+			Error::<Test>::DepositTooLow
+		);
+	});
+}
+
+#[test]
+fn deregister_advertiser_requires_no_active_ads() {
+	new_test_ext().execute_with(|| {
+		let ad_id = live_ad(ALICE, 500);
+		assert_noop!(
+			Ads::deregister_advertiser(RuntimeOrigin::signed(ALICE)),
+			Error::<Test>::CannotWithdrawWithActiveAds
+		);
+
+		assert_ok!(Ads::deactivate_ad(RuntimeOrigin::signed(ALICE), ad_id));
+		assert_ok!(Ads::deregister_advertiser(RuntimeOrigin::signed(ALICE)));
+		assert!(!AdvertiserProfiles::<Test>::contains_key(ALICE));
+		// The locked ad budget stays reserved
+		assert_eq!(Balances::reserved_balance(ALICE), 500);
+	});
+}
+
+#[test]
+fn submit_ad_escrows_funding_and_waits_for_moderation() {
+	new_test_ext().execute_with(|| {
+		register(ALICE, MIN_DEPOSIT);
+		let spot_id = create_spot();
+		let ad_id = submit(ALICE, spot_id, 500, None, None);
+
+		assert_eq!(Balances::reserved_balance(ALICE), MIN_DEPOSIT + 500);
+		assert_eq!(ad(ad_id).remaining_budget, 500);
+		assert!(!ad(ad_id).active);
+		assert_eq!(Moderation::<Test>::get(ad_id), ModerationStatus::Pending);
+		assert_eq!(AdSpots::<Test>::get(spot_id).unwrap().ad_id, Some(ad_id));
+		assert_eq!(profile(ALICE).active_ads, 1);
+
+		assert_ok!(Ads::approve_ad(RuntimeOrigin::root(), ad_id));
+		assert!(ad(ad_id).active);
+		System::assert_last_event(Event::AdActivated { ad_id }.into());
+	});
+}
+
+#[test]
+fn submit_ad_checks_spot_and_funding() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Ads::submit_ad(
+				RuntimeOrigin::signed(ALICE),
+				0,
+				b"Ad".to_vec(),
+				b"An ad".to_vec(),
+				b"QmCid".to_vec(),
+				0,
+				500,
+				None,
+				None,
+			),
+			Error::<Test>::AdvertiserNotRegistered
+		);
+
+		let ad_id = live_ad(ALICE, 500);
+		let spot_id = ad(ad_id).spot_id;
+		let free_spot_id = create_spot();
+		register(BOB, MIN_DEPOSIT);
+		assert_noop!(
+			Ads::submit_ad(
+				RuntimeOrigin::signed(BOB),
+				spot_id,
+				b"Ad".to_vec(),
+				b"An ad".to_vec(),
+				b"QmCid".to_vec(),
+				0,
+				500,
+				None,
+				None,
+			),
+			Error::<Test>::AdSpotNotAvailable
+		);
+		assert_noop!(
+			Ads::submit_ad(
+				RuntimeOrigin::signed(BOB),
+				free_spot_id,
+				b"Ad".to_vec(),
+				b"An ad".to_vec(),
+				b"QmCid".to_vec(),
+				0,
+				0,
+				None,
+				None,
+			),
+			Error::<Test>::InsufficientFunding
+		);
+	});
+}
+
+#[test]
+fn deactivated_ad_releases_spot_for_reuse() {
+	new_test_ext().execute_with(|| {
+		let ad_id = live_ad(ALICE, 500);
+		let spot_id = ad(ad_id).spot_id;
+
+		assert_noop!(
+			Ads::deactivate_ad(RuntimeOrigin::signed(BOB), ad_id),
+			Error::<Test>::Unauthorized
+		);
+		assert_ok!(Ads::deactivate_ad(RuntimeOrigin::signed(ALICE), ad_id));
+		assert!(!ad(ad_id).active);
+		assert_eq!(
+			AdSpots::<Test>::get(spot_id),
+			Some(AdSpot { spot_id, available: true, ad_id: None })
+		);
+		assert_noop!(
+			Ads::deactivate_ad(RuntimeOrigin::signed(ALICE), ad_id),
+			Error::<Test>::AdNotActive
+		);
+
+		register(BOB, MIN_DEPOSIT);
+		let next = submit(BOB, spot_id, 300, None, None);
+		assert_eq!(AdSpots::<Test>::get(spot_id).unwrap().ad_id, Some(next));
+	});
+}
+
+#[test]
+fn reclaim_ad_spot_takes_down_the_ad() {
+	new_test_ext().execute_with(|| {
+		let ad_id = live_ad(ALICE, 500);
+		let spot_id = ad(ad_id).spot_id;
+
+		assert_noop!(
+			Ads::reclaim_ad_spot(RuntimeOrigin::signed(ALICE), spot_id),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Ads::reclaim_ad_spot(RuntimeOrigin::root(), spot_id));
+		assert!(!ad(ad_id).active);
+		assert!(AdSpots::<Test>::get(spot_id).unwrap().available);
+		assert_noop!(
+			Ads::reclaim_ad_spot(RuntimeOrigin::root(), spot_id),
+			Error::<Test>::AdSpotNotOccupied
+		);
+	});
+}
+
+#[test]
+fn scheduled_ad_starts_and_expires() {
+	new_test_ext().execute_with(|| {
+		register(ALICE, MIN_DEPOSIT);
+		let spot_id = create_spot();
+		assert_noop!(
+			Ads::submit_ad(
+				RuntimeOrigin::signed(ALICE),
+				spot_id,
+				b"Ad".to_vec(),
+				b"An ad".to_vec(),
+				b"QmCid".to_vec(),
+				0,
+				500,
+				Some(5),
+				Some(5),
+			),
+			Error::<Test>::InvalidSchedule
+		);
+
+		let ad_id = submit(ALICE, spot_id, 500, Some(5), Some(10));
+		assert_ok!(Ads::approve_ad(RuntimeOrigin::root(), ad_id));
+		assert!(!ad(ad_id).active);
+
+		run_to_block(5);
+		assert!(ad(ad_id).active);
+
+		run_to_block(10);
+		assert!(!ad(ad_id).active);
+		assert!(AdSpots::<Test>::get(spot_id).unwrap().available);
+		System::assert_has_event(Event::AdExpired { ad_id }.into());
+	});
+}
+
+#[test]
+fn pending_ads_starting_now_are_not_scheduled() {
+	new_test_ext().execute_with(|| {
+		register(ALICE, MIN_DEPOSIT);
+		let ad_ids: Vec<u32> =
+			(0..6).map(|_| submit(ALICE, create_spot(), 100, None, None)).collect();
+		assert!(AdsStartingAt::<Test>::get(1).is_empty());
+
+		let scheduled = submit(ALICE, create_spot(), 100, Some(5), None);
+		assert_eq!(AdsStartingAt::<Test>::get(5).into_inner(), vec![scheduled]);
+
+		run_to_block(2);
+		assert_ok!(Ads::approve_ad(RuntimeOrigin::root(), ad_ids[0]));
+		assert!(ad(ad_ids[0]).active);
+		System::assert_last_event(Event::AdActivated { ad_id: ad_ids[0] }.into());
+	});
+}
+
+#[test]
+fn remaining_budget_is_withdrawn_after_cooldown() {
+	new_test_ext().execute_with(|| {
+		let ad_id = live_ad(ALICE, 500);
+		assert_noop!(
+			Ads::withdraw_remaining_budget(RuntimeOrigin::signed(ALICE), ad_id),
+			Error::<Test>::AdStillActive
+		);
+
+		assert_ok!(Ads::deactivate_ad(RuntimeOrigin::signed(ALICE), ad_id));
+		assert_eq!(BudgetUnlocksAt::<Test>::get(ad_id), Some(1 + WITHDRAWAL_COOLDOWN));
+		assert_noop!(
+			Ads::withdraw_remaining_budget(RuntimeOrigin::signed(ALICE), ad_id),
+			Error::<Test>::BudgetStillLocked
+		);
+
+		run_to_block(1 + WITHDRAWAL_COOLDOWN);
+		assert_ok!(Ads::withdraw_remaining_budget(RuntimeOrigin::signed(ALICE), ad_id));
+		assert_eq!(ad(ad_id).remaining_budget, 0);
+		assert_eq!(Balances::reserved_balance(ALICE), MIN_DEPOSIT);
+		assert_noop!(
+			Ads::withdraw_remaining_budget(RuntimeOrigin::signed(ALICE), ad_id),
+			Error::<Test>::NothingToWithdraw
+		);
+	});
+}
+
+#[test]
+fn top_up_ad_adds_to_the_budget() {
+	new_test_ext().execute_with(|| {
+		let ad_id = live_ad(ALICE, 500);
+
+		assert_noop!(
+			Ads::top_up_ad(RuntimeOrigin::signed(BOB), ad_id, 100),
+			Error::<Test>::Unauthorized
+		);
+		assert_ok!(Ads::top_up_ad(RuntimeOrigin::signed(ALICE), ad_id, 100));
+		assert_eq!(ad(ad_id).funding, 600);
+		assert_eq!(ad(ad_id).remaining_budget, 600);
+		assert_eq!(Balances::reserved_balance(ALICE), MIN_DEPOSIT + 600);
+	});
+}
+
+#[test]
+fn spend_budget_pays_out_of_escrow() {
+	new_test_ext().execute_with(|| {
+		let ad_id = live_ad(ALICE, 500);
+
+		assert_ok!(Ads::spend_budget(ad_id, 200, Some(&BOB)));
+		assert_eq!(ad(ad_id).remaining_budget, 300);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE + 200);
+		assert_noop!(Ads::spend_budget(ad_id, 301, None), Error::<Test>::InsufficientAdBudget);
+
+		assert_ok!(Ads::spend_budget(ad_id, 300, None));
+		assert!(!ad(ad_id).active);
+		assert_eq!(Balances::reserved_balance(ALICE), MIN_DEPOSIT);
+		System::assert_has_event(Event::AdBudgetExhausted { ad_id }.into());
+	});
+}
+
+#[test]
+fn bid_for_spot_takes_bid_from_deposit() {
+	new_test_ext().execute_with(|| {
+		let spot_id = create_spot();
+		assert_ok!(Ads::open_spot_auction(RuntimeOrigin::root(), spot_id, 5, 20));
+		register(ALICE, MIN_DEPOSIT + 100);
+
+		assert_ok!(Ads::bid_for_spot(RuntimeOrigin::signed(ALICE), spot_id, 60));
+		assert_eq!(profile(ALICE).deposit, MIN_DEPOSIT + 40);
+		assert_eq!(Balances::reserved_balance(ALICE), MIN_DEPOSIT + 100);
+
+		// Raising a bid only takes the difference
+		assert_ok!(Ads::bid_for_spot(RuntimeOrigin::signed(ALICE), spot_id, 90));
+		assert_eq!(profile(ALICE).deposit, MIN_DEPOSIT + 10);
+		assert_eq!(Balances::reserved_balance(ALICE), MIN_DEPOSIT + 100);
+	});
+}
+
+#[test]
+fn bid_for_spot_keeps_minimum_deposit() {
+	new_test_ext().execute_with(|| {
+		let spot_id = create_spot();
+		assert_ok!(Ads::open_spot_auction(RuntimeOrigin::root(), spot_id, 5, 20));
+		register(ALICE, MIN_DEPOSIT + 50);
+
+		assert_noop!(
+			Ads::bid_for_spot(RuntimeOrigin::signed(ALICE), spot_id, 51),
+			Error::<Test>::DepositTooLow
+		);
+		assert_ok!(Ads::bid_for_spot(RuntimeOrigin::signed(ALICE), spot_id, 50));
+		assert_eq!(profile(ALICE).deposit, MIN_DEPOSIT);
+	});
+}
+
+#[test]
+fn bid_for_spot_must_outbid() {
+	new_test_ext().execute_with(|| {
+		let spot_id = create_spot();
+		assert_noop!(
+			Ads::bid_for_spot(RuntimeOrigin::signed(ALICE), spot_id, 10),
+			Error::<Test>::AdvertiserNotRegistered
+		);
+		register(ALICE, MIN_DEPOSIT + 100);
+		register(BOB, MIN_DEPOSIT + 100);
+		assert_noop!(
+			Ads::bid_for_spot(RuntimeOrigin::signed(ALICE), spot_id, 10),
+			Error::<Test>::AuctionNotFound
+		);
+
+		assert_ok!(Ads::open_spot_auction(RuntimeOrigin::root(), spot_id, 5, 20));
+		assert_ok!(Ads::bid_for_spot(RuntimeOrigin::signed(ALICE), spot_id, 50));
+		assert_noop!(
+			Ads::bid_for_spot(RuntimeOrigin::signed(BOB), spot_id, 50),
+			Error::<Test>::BidTooLow
+		);
+
+		run_to_block(6);
+		assert_noop!(
+			Ads::bid_for_spot(RuntimeOrigin::signed(BOB), spot_id, 60),
+			Error::<Test>::AuctionNotFound
+		);
+	});
+}
+
+#[test]
+fn auction_winner_leases_spot() {
+	new_test_ext().execute_with(|| {
+		let spot_id = create_spot();
+		assert_noop!(
+			Ads::open_spot_auction(RuntimeOrigin::root(), spot_id, 0, 20),
+			Error::<Test>::InvalidAuctionPeriod
+		);
+		assert_ok!(Ads::open_spot_auction(RuntimeOrigin::root(), spot_id, 5, 20));
+		register(ALICE, MIN_DEPOSIT + 100);
+		register(BOB, MIN_DEPOSIT + 100);
+		assert_ok!(Ads::bid_for_spot(RuntimeOrigin::signed(ALICE), spot_id, 50));
+		assert_ok!(Ads::bid_for_spot(RuntimeOrigin::signed(BOB), spot_id, 70));
+
+		run_to_block(6);
+		System::assert_has_event(
+			Event::SpotAuctionClosed { spot_id, winner: Some(BOB), amount: 70, lease_ends_at: 26 }
+				.into(),
+		);
+		// The winning bid is burned and the losing bid returned
+		assert_eq!(Balances::reserved_balance(BOB), MIN_DEPOSIT + 30);
+		assert_eq!(Balances::reserved_balance(ALICE), MIN_DEPOSIT + 50);
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE - MIN_DEPOSIT - 50);
+
+		// Only the lease holder can place an ad in the spot
+		assert_noop!(
+			Ads::submit_ad(
+				RuntimeOrigin::signed(ALICE),
+				spot_id,
+				b"Ad".to_vec(),
+				b"An ad".to_vec(),
+				b"QmCid".to_vec(),
+				0,
+				500,
+				None,
+				None,
+			),
+			Error::<Test>::AdSpotNotAvailable
+		);
+		let ad_id = submit(BOB, spot_id, 500, None, None);
+		assert_ok!(Ads::approve_ad(RuntimeOrigin::root(), ad_id));
+
+		run_to_block(26);
+		assert!(!ad(ad_id).active);
+		assert!(!SpotLeases::<Test>::contains_key(spot_id));
+		assert!(AdSpots::<Test>::get(spot_id).unwrap().available);
+	});
+}
+
+#[test]
+fn auction_without_bids_frees_the_spot() {
+	new_test_ext().execute_with(|| {
+		let spot_id = create_spot();
+		assert_ok!(Ads::open_spot_auction(RuntimeOrigin::root(), spot_id, 5, 20));
+		assert!(!AdSpots::<Test>::get(spot_id).unwrap().available);
+
+		run_to_block(6);
+		assert!(AdSpots::<Test>::get(spot_id).unwrap().available);
+		assert!(!SpotLeases::<Test>::contains_key(spot_id));
+	});
+}
+
+#[test]
+fn appealed_rejection_can_be_approved() {
+	new_test_ext().execute_with(|| {
+		register(ALICE, MIN_DEPOSIT);
+		let ad_id = submit(ALICE, create_spot(), 500, None, None);
+
+		assert_noop!(
+			Ads::reject_ad(RuntimeOrigin::signed(ALICE), ad_id, b"Spam".to_vec()),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Ads::reject_ad(RuntimeOrigin::root(), ad_id, b"Spam".to_vec()));
+		assert_eq!(ModerationRecords::<Test>::get(ALICE).rejected, 1);
+		assert_noop!(
+			Ads::approve_ad(RuntimeOrigin::root(), ad_id),
+			Error::<Test>::AdNotUnderReview
+		);
+
+		assert_ok!(Ads::appeal_rejection(RuntimeOrigin::signed(ALICE), ad_id));
+		assert_eq!(Balances::reserved_balance(ALICE), MIN_DEPOSIT + 500 + APPEAL_BOND);
+		assert_noop!(
+			Ads::appeal_rejection(RuntimeOrigin::signed(ALICE), ad_id),
+			Error::<Test>::AdNotRejected
+		);
+
+		assert_ok!(Ads::approve_ad(RuntimeOrigin::root(), ad_id));
+		assert!(ad(ad_id).active);
+		assert_eq!(Balances::reserved_balance(ALICE), MIN_DEPOSIT + 500);
+		assert_eq!(
+			ModerationRecords::<Test>::get(ALICE),
+			ModerationRecord { approved: 1, rejected: 0 }
+		);
+	});
+}
+
+#[test]
+fn rejected_appeal_burns_bond_and_takes_down_the_ad() {
+	new_test_ext().execute_with(|| {
+		register(ALICE, MIN_DEPOSIT);
+		let ad_id = submit(ALICE, create_spot(), 500, None, None);
+		assert_ok!(Ads::reject_ad(RuntimeOrigin::root(), ad_id, b"Spam".to_vec()));
+		assert_ok!(Ads::appeal_rejection(RuntimeOrigin::signed(ALICE), ad_id));
+
+		assert_ok!(Ads::reject_ad(RuntimeOrigin::root(), ad_id, b"Still spam".to_vec()));
+		assert_eq!(Balances::reserved_balance(ALICE), MIN_DEPOSIT + 500);
+		assert_eq!(Balances::total_balance(&ALICE), INITIAL_BALANCE - APPEAL_BOND);
+		assert_eq!(profile(ALICE).active_ads, 0);
+		assert_noop!(
+			Ads::appeal_rejection(RuntimeOrigin::signed(ALICE), ad_id),
+			Error::<Test>::AdNotActive
+		);
+	});
+}
+
+#[test]
+fn auto_approve_skips_moderation() {
+	new_test_ext().execute_with(|| {
+		live_ad(ALICE, 500);
+		assert_ok!(Ads::set_auto_approve(RuntimeOrigin::root(), Some(1)));
+
+		let ad_id = submit(ALICE, create_spot(), 500, None, None);
+		assert!(ad(ad_id).active);
+		assert_eq!(Moderation::<Test>::get(ad_id), ModerationStatus::Approved);
+
+		register(BOB, MIN_DEPOSIT);
+		let ad_id = submit(BOB, create_spot(), 500, None, None);
+		assert!(!ad(ad_id).active);
+		assert_eq!(Moderation::<Test>::get(ad_id), ModerationStatus::Pending);
+	});
+}
+
+#[test]
+fn slash_advertiser_slashes_deposit_and_takes_down_ads() {
+	new_test_ext().execute_with(|| {
+		register(ALICE, MIN_DEPOSIT * 2);
+		let live = live_ad(ALICE, 500);
+		let pending = submit(ALICE, create_spot(), 300, None, None);
+
+		assert_noop!(
+			Ads::slash_advertiser(
+				RuntimeOrigin::signed(BOB),
+				ALICE,
+				Perbill::from_percent(60),
+				None,
+				2
+			),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Ads::slash_advertiser(RuntimeOrigin::root(), ALICE, Perbill::from_percent(60), None, 1),
+			Error::<Test>::InvalidAdCountWitness
+		);
+		assert_ok!(Ads::slash_advertiser(
+			RuntimeOrigin::root(),
+			ALICE,
+			Perbill::from_percent(60),
+			None,
+			2
+		));
+
+		System::assert_last_event(
+			Event::AdvertiserSlashed {
+				advertiser: ALICE,
+				amount: 120,
+				deactivated_ads: 2,
+				suspended_until: None,
+			}
+			.into(),
+		);
+		assert!(!ad(live).active);
+		assert!(!Ads::is_placed(pending, &ad(pending)));
+		assert_eq!(profile(ALICE).deposit, 80);
+		assert!(profile(ALICE).needs_top_up);
+		assert_eq!(Balances::total_balance(&ALICE), INITIAL_BALANCE - 120);
+
+		// A deposit below the minimum must be topped up before the next ad
+		let spot_id = create_spot();
+		assert_noop!(
+			Ads::submit_ad(
+				RuntimeOrigin::signed(ALICE),
+				spot_id,
+				b"Ad".to_vec(),
+				b"An ad".to_vec(),
+				b"QmCid".to_vec(),
+				0,
+				500,
+				None,
+				None,
+			),
+			Error::<Test>::DepositTooLow
+		);
+		assert_ok!(Ads::increase_advertiser_deposit(RuntimeOrigin::signed(ALICE), 20));
+		assert!(!profile(ALICE).needs_top_up);
+	});
+}
+
+#[test]
+fn slashed_advertiser_is_suspended() {
+	new_test_ext().execute_with(|| {
+		live_ad(ALICE, 500);
+		assert_noop!(
+			Ads::slash_advertiser(RuntimeOrigin::root(), ALICE, Perbill::zero(), Some(0), 1),
+			Error::<Test>::InvalidSuspensionPeriod
+		);
+		assert_ok!(Ads::slash_advertiser(
+			RuntimeOrigin::root(),
+			ALICE,
+			Perbill::zero(),
+			Some(5),
+			1
+		));
+
+		assert_eq!(Suspensions::<Test>::get(ALICE), Some(6));
+		assert!(!profile(ALICE).active);
+		assert_noop!(
+			Ads::deregister_advertiser(RuntimeOrigin::signed(ALICE)),
+			Error::<Test>::AdvertiserSuspended
+		);
+
+		run_to_block(6);
+		assert!(profile(ALICE).active);
+		assert!(!Suspensions::<Test>::contains_key(ALICE));
+		System::assert_has_event(Event::AdvertiserSuspensionEnded { advertiser: ALICE }.into());
+	});
+}
+
+#[test]
+fn suspension_is_only_queued_when_extended() {
+	new_test_ext().execute_with(|| {
+		live_ad(ALICE, 500);
+		let slash = |period| {
+			Ads::slash_advertiser(RuntimeOrigin::root(), ALICE, Perbill::zero(), Some(period), 1)
+		};
+		assert_ok!(slash(5));
+
+		// Shorter or equal suspensions keep the current end without queueing another one,
+		// more often than `MaxSuspensionsPerBlock`
+		for _ in 0..5 {
+			assert_ok!(slash(5));
+		}
+		assert_ok!(slash(2));
+		assert_eq!(Suspensions::<Test>::get(ALICE), Some(6));
+		assert_eq!(SuspensionsEndingAt::<Test>::get(6).to_vec(), vec![ALICE]);
+		assert!(SuspensionsEndingAt::<Test>::get(3).is_empty());
+
+		assert_ok!(slash(10));
+		assert_eq!(Suspensions::<Test>::get(ALICE), Some(11));
+		assert_eq!(SuspensionsEndingAt::<Test>::get(11).to_vec(), vec![ALICE]);
+
+		run_to_block(6);
+		assert!(!profile(ALICE).active);
+		run_to_block(11);
+		assert!(profile(ALICE).active);
+	});
+}
+
+#[test]
+fn update_ad_keeps_creative_history() {
+	new_test_ext().execute_with(|| {
+		let ad_id = live_ad(ALICE, 500);
+
+		assert_noop!(
+			Ads::update_ad(
+				RuntimeOrigin::signed(BOB),
+				ad_id,
+				b"Ad".to_vec(),
+				b"".to_vec(),
+				b"Qm2".to_vec(),
+				0
+			),
+			Error::<Test>::Unauthorized
+		);
+		assert_ok!(Ads::update_ad(
+			RuntimeOrigin::signed(ALICE),
+			ad_id,
+			b"Ad".to_vec(),
+			b"An ad".to_vec(),
+			b"Qm2".to_vec(),
+			15_000
+		));
+
+		assert_eq!(ad(ad_id).creative_version, 2);
+		assert_eq!(ad(ad_id).ipfs_cid.to_vec(), b"Qm2".to_vec());
+		assert_eq!(CreativeDurations::<Test>::get(ad_id), 15_000);
+		assert_eq!(CreativeHistory::<Test>::get(ad_id)[0].ipfs_cid.to_vec(), b"QmCid".to_vec());
+		// The new creative goes back to moderation
+		assert!(!ad(ad_id).active);
+		assert_eq!(Moderation::<Test>::get(ad_id), ModerationStatus::Pending);
+
+		// Only the latest `MaxCreativeHistory` creatives are kept
+		for cid in [b"Qm3", b"Qm4"] {
+			assert_ok!(Ads::update_ad(
+				RuntimeOrigin::signed(ALICE),
+				ad_id,
+				b"Ad".to_vec(),
+				b"An ad".to_vec(),
+				cid.to_vec(),
+				15_000
+			));
+		}
+		let versions: Vec<u32> =
+			CreativeHistory::<Test>::get(ad_id).iter().map(|creative| creative.version).collect();
+		assert_eq!(versions, vec![2, 3]);
+	});
+}
@@ -0,0 +1,472 @@
+//! Weights for `pallet_ads`
+//!
+//! Hand estimates from the storage accesses of each call, listed above each function, and a
+//! rough execution time. Replace them with the output of `polkaads-node benchmark pallet`
+//! once the benchmarks have been run on reference hardware.
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+#![allow(missing_docs)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use core::marker::PhantomData;
+
+/// Weight functions needed for `pallet_ads`.
+pub trait WeightInfo {
+	fn create_ad_spot() -> Weight;
+	fn register_advertiser() -> Weight;
+	fn increase_advertiser_deposit() -> Weight;
+	fn deregister_advertiser() -> Weight;
+	fn submit_ad() -> Weight;
+	fn deactivate_ad() -> Weight;
+	fn reclaim_ad_spot() -> Weight;
+	fn open_spot_auction() -> Weight;
+	fn bid_for_spot() -> Weight;
+	fn close_spot_auction(b: u32, ) -> Weight;
+	fn end_spot_lease() -> Weight;
+	fn start_scheduled_ad() -> Weight;
+	fn expire_ad() -> Weight;
+	fn approve_ad() -> Weight;
+	fn reject_ad() -> Weight;
+	fn appeal_rejection() -> Weight;
+	fn set_auto_approve() -> Weight;
+	fn slash_advertiser(a: u32, ) -> Weight;
+	fn end_suspension() -> Weight;
+	fn update_ad() -> Weight;
+	fn top_up_ad() -> Weight;
+	fn withdraw_remaining_budget() -> Weight;
+}
+
+/// Estimated weights for `pallet_ads`.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: `Ads::NextSpotId` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:0 w:1)
+	fn create_ad_spot() -> Weight {
+		Weight::from_parts(10_000_000, 1489)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn register_advertiser() -> Weight {
+		Weight::from_parts(44_000_000, 3637)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn increase_advertiser_deposit() -> Weight {
+		Weight::from_parts(40_000_000, 3637)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn deregister_advertiser() -> Weight {
+		Weight::from_parts(41_000_000, 3641)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Storage: `Ads::NextAdId` (r:1 w:1)
+	/// Storage: `Ads::AdsStartingAt` (r:1 w:1)
+	/// Storage: `Ads::AdsEndingAt` (r:1 w:1)
+	/// Storage: `Ads::Ads` (r:0 w:1)
+	/// Storage: `Ads::AutoApproveAfter` (r:1 w:0)
+	/// Storage: `Ads::ModerationRecords` (r:1 w:0)
+	/// Storage: `Ads::Moderation` (r:0 w:1)
+	/// Storage: `Ads::CreativeDurations` (r:0 w:1)
+	fn submit_ad() -> Weight {
+		Weight::from_parts(67_000_000, 3637)
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().writes(10_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::BudgetUnlocksAt` (r:0 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:1)
+	/// Storage: `Ads::Moderation` (r:1 w:1)
+	fn deactivate_ad() -> Weight {
+		Weight::from_parts(48_000_000, 4299)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(5_u64))
+	}
+	/// Storage: `Ads::AdSpots` (r:1 w:1)
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::BudgetUnlocksAt` (r:0 w:1)
+	/// Storage: `Ads::Moderation` (r:1 w:1)
+	fn reclaim_ad_spot() -> Weight {
+		Weight::from_parts(49_000_000, 4299)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(5_u64))
+	}
+	/// Storage: `Ads::AdSpots` (r:1 w:1)
+	/// Storage: `Ads::AuctionsEndingAt` (r:1 w:1)
+	/// Storage: `Ads::LeasesEndingAt` (r:1 w:1)
+	/// Storage: `Ads::SpotAuctions` (r:0 w:1)
+	fn open_spot_auction() -> Weight {
+		Weight::from_parts(19_000_000, 3545)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	/// Storage: `Ads::SpotAuctions` (r:1 w:1)
+	fn bid_for_spot() -> Weight {
+		Weight::from_parts(30_000_000, 5030)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Ads::SpotAuctions` (r:1 w:1)
+	/// Storage: `System::Account` (r:32 w:32)
+	/// Storage: `Ads::SpotLeases` (r:0 w:1)
+	/// The range of component `b` is `[0, 32]`.
+	fn close_spot_auction(b: u32, ) -> Weight {
+		Weight::from_parts(16_000_000, 5030)
+			.saturating_add(Weight::from_parts(24_000_000, 0).saturating_mul(b.into()))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(b.into())))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(b.into())))
+			.saturating_add(Weight::from_parts(0, 2603).saturating_mul(b.into()))
+	}
+	/// Storage: `Ads::SpotLeases` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:1)
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::BudgetUnlocksAt` (r:0 w:1)
+	/// Storage: `Ads::Moderation` (r:1 w:1)
+	fn end_spot_lease() -> Weight {
+		Weight::from_parts(52_000_000, 4299)
+			.saturating_add(T::DbWeight::get().reads(6_u64))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:0)
+	/// Storage: `Ads::Moderation` (r:1 w:0)
+	fn start_scheduled_ad() -> Weight {
+		Weight::from_parts(18_000_000, 4308)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::BudgetUnlocksAt` (r:0 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:1)
+	/// Storage: `Ads::SpotLeases` (r:1 w:0)
+	/// Storage: `Ads::Moderation` (r:1 w:1)
+	fn expire_ad() -> Weight {
+		Weight::from_parts(49_000_000, 4308)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(5_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:0)
+	/// Storage: `Ads::Moderation` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Storage: `Ads::ModerationRecords` (r:1 w:1)
+	fn approve_ad() -> Weight {
+		Weight::from_parts(40_000_000, 4308)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:1)
+	/// Storage: `Ads::Moderation` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Storage: `Balances::TotalIssuance` (r:1 w:1)
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	/// Storage: `Ads::SpotLeases` (r:1 w:0)
+	fn reject_ad() -> Weight {
+		Weight::from_parts(64_000_000, 4308)
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:0)
+	/// Storage: `Ads::AdSpots` (r:1 w:0)
+	/// Storage: `Ads::Moderation` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn appeal_rejection() -> Weight {
+		Weight::from_parts(36_000_000, 4308)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Ads::AutoApproveAfter` (r:0 w:1)
+	fn set_auto_approve() -> Weight {
+		Weight::from_parts(8_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	/// Storage: `Ads::AdsByAdvertiser` (r:101 w:0)
+	/// Storage: `Ads::SuspensionsEndingAt` (r:1 w:1)
+	/// Storage: `Ads::Suspensions` (r:1 w:1)
+	/// Storage: `Ads::Ads` (r:100 w:100)
+	/// Storage: `Ads::AdSpots` (r:100 w:100)
+	/// Storage: `Ads::SpotLeases` (r:100 w:0)
+	/// Storage: `Ads::Moderation` (r:100 w:100)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Storage: `Balances::TotalIssuance` (r:1 w:1)
+	fn slash_advertiser(a: u32, ) -> Weight {
+		Weight::from_parts(54_000_000, 4515)
+			.saturating_add(Weight::from_parts(31_000_000, 0).saturating_mul(a.into()))
+			.saturating_add(T::DbWeight::get().reads(6_u64))
+			.saturating_add(T::DbWeight::get().reads((5_u64).saturating_mul(a.into())))
+			.saturating_add(T::DbWeight::get().writes(5_u64))
+			.saturating_add(T::DbWeight::get().writes((3_u64).saturating_mul(a.into())))
+			.saturating_add(Weight::from_parts(0, 3318).saturating_mul(a.into()))
+	}
+	/// Storage: `Ads::Suspensions` (r:1 w:1)
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	fn end_suspension() -> Weight {
+		Weight::from_parts(16_000_000, 3642)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:0)
+	/// Storage: `Ads::CreativeHistory` (r:1 w:1)
+	/// Storage: `Ads::AutoApproveAfter` (r:1 w:0)
+	/// Storage: `Ads::ModerationRecords` (r:1 w:0)
+	/// Storage: `Ads::Moderation` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Storage: `Ads::CreativeDurations` (r:0 w:1)
+	fn update_ad() -> Weight {
+		Weight::from_parts(48_000_000, 4576)
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().writes(5_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:0)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	fn top_up_ad() -> Weight {
+		Weight::from_parts(40_000_000, 4312)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:0)
+	/// Storage: `Ads::BudgetUnlocksAt` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn withdraw_remaining_budget() -> Weight {
+		Weight::from_parts(36_000_000, 4312)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+}
+
+// For backwards compatibility and tests.
+impl WeightInfo for () {
+	/// Storage: `Ads::NextSpotId` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:0 w:1)
+	fn create_ad_spot() -> Weight {
+		Weight::from_parts(10_000_000, 1489)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn register_advertiser() -> Weight {
+		Weight::from_parts(44_000_000, 3637)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn increase_advertiser_deposit() -> Weight {
+		Weight::from_parts(40_000_000, 3637)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn deregister_advertiser() -> Weight {
+		Weight::from_parts(41_000_000, 3641)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Storage: `Ads::NextAdId` (r:1 w:1)
+	/// Storage: `Ads::AdsStartingAt` (r:1 w:1)
+	/// Storage: `Ads::AdsEndingAt` (r:1 w:1)
+	/// Storage: `Ads::Ads` (r:0 w:1)
+	/// Storage: `Ads::AutoApproveAfter` (r:1 w:0)
+	/// Storage: `Ads::ModerationRecords` (r:1 w:0)
+	/// Storage: `Ads::Moderation` (r:0 w:1)
+	/// Storage: `Ads::CreativeDurations` (r:0 w:1)
+	fn submit_ad() -> Weight {
+		Weight::from_parts(67_000_000, 3637)
+			.saturating_add(RocksDbWeight::get().reads(8_u64))
+			.saturating_add(RocksDbWeight::get().writes(10_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::BudgetUnlocksAt` (r:0 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:1)
+	/// Storage: `Ads::Moderation` (r:1 w:1)
+	fn deactivate_ad() -> Weight {
+		Weight::from_parts(48_000_000, 4299)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(5_u64))
+	}
+	/// Storage: `Ads::AdSpots` (r:1 w:1)
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::BudgetUnlocksAt` (r:0 w:1)
+	/// Storage: `Ads::Moderation` (r:1 w:1)
+	fn reclaim_ad_spot() -> Weight {
+		Weight::from_parts(49_000_000, 4299)
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(5_u64))
+	}
+	/// Storage: `Ads::AdSpots` (r:1 w:1)
+	/// Storage: `Ads::AuctionsEndingAt` (r:1 w:1)
+	/// Storage: `Ads::LeasesEndingAt` (r:1 w:1)
+	/// Storage: `Ads::SpotAuctions` (r:0 w:1)
+	fn open_spot_auction() -> Weight {
+		Weight::from_parts(19_000_000, 3545)
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	/// Storage: `Ads::SpotAuctions` (r:1 w:1)
+	fn bid_for_spot() -> Weight {
+		Weight::from_parts(30_000_000, 5030)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Ads::SpotAuctions` (r:1 w:1)
+	/// Storage: `System::Account` (r:32 w:32)
+	/// Storage: `Ads::SpotLeases` (r:0 w:1)
+	/// The range of component `b` is `[0, 32]`.
+	fn close_spot_auction(b: u32, ) -> Weight {
+		Weight::from_parts(16_000_000, 5030)
+			.saturating_add(Weight::from_parts(24_000_000, 0).saturating_mul(b.into()))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(b.into())))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(b.into())))
+			.saturating_add(Weight::from_parts(0, 2603).saturating_mul(b.into()))
+	}
+	/// Storage: `Ads::SpotLeases` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:1)
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::BudgetUnlocksAt` (r:0 w:1)
+	/// Storage: `Ads::Moderation` (r:1 w:1)
+	fn end_spot_lease() -> Weight {
+		Weight::from_parts(52_000_000, 4299)
+			.saturating_add(RocksDbWeight::get().reads(6_u64))
+			.saturating_add(RocksDbWeight::get().writes(6_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:0)
+	/// Storage: `Ads::Moderation` (r:1 w:0)
+	fn start_scheduled_ad() -> Weight {
+		Weight::from_parts(18_000_000, 4308)
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::BudgetUnlocksAt` (r:0 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:1)
+	/// Storage: `Ads::SpotLeases` (r:1 w:0)
+	/// Storage: `Ads::Moderation` (r:1 w:1)
+	fn expire_ad() -> Weight {
+		Weight::from_parts(49_000_000, 4308)
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(5_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:0)
+	/// Storage: `Ads::Moderation` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Storage: `Ads::ModerationRecords` (r:1 w:1)
+	fn approve_ad() -> Weight {
+		Weight::from_parts(40_000_000, 4308)
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:1)
+	/// Storage: `Ads::Moderation` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Storage: `Balances::TotalIssuance` (r:1 w:1)
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	/// Storage: `Ads::SpotLeases` (r:1 w:0)
+	fn reject_ad() -> Weight {
+		Weight::from_parts(64_000_000, 4308)
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().writes(6_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:0)
+	/// Storage: `Ads::AdSpots` (r:1 w:0)
+	/// Storage: `Ads::Moderation` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn appeal_rejection() -> Weight {
+		Weight::from_parts(36_000_000, 4308)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Ads::AutoApproveAfter` (r:0 w:1)
+	fn set_auto_approve() -> Weight {
+		Weight::from_parts(8_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	/// Storage: `Ads::AdsByAdvertiser` (r:101 w:0)
+	/// Storage: `Ads::SuspensionsEndingAt` (r:1 w:1)
+	/// Storage: `Ads::Suspensions` (r:1 w:1)
+	/// Storage: `Ads::Ads` (r:100 w:100)
+	/// Storage: `Ads::AdSpots` (r:100 w:100)
+	/// Storage: `Ads::SpotLeases` (r:100 w:0)
+	/// Storage: `Ads::Moderation` (r:100 w:100)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Storage: `Balances::TotalIssuance` (r:1 w:1)
+	fn slash_advertiser(a: u32, ) -> Weight {
+		Weight::from_parts(54_000_000, 4515)
+			.saturating_add(Weight::from_parts(31_000_000, 0).saturating_mul(a.into()))
+			.saturating_add(RocksDbWeight::get().reads(6_u64))
+			.saturating_add(RocksDbWeight::get().reads((5_u64).saturating_mul(a.into())))
+			.saturating_add(RocksDbWeight::get().writes(5_u64))
+			.saturating_add(RocksDbWeight::get().writes((3_u64).saturating_mul(a.into())))
+			.saturating_add(Weight::from_parts(0, 3318).saturating_mul(a.into()))
+	}
+	/// Storage: `Ads::Suspensions` (r:1 w:1)
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	fn end_suspension() -> Weight {
+		Weight::from_parts(16_000_000, 3642)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:0)
+	/// Storage: `Ads::CreativeHistory` (r:1 w:1)
+	/// Storage: `Ads::AutoApproveAfter` (r:1 w:0)
+	/// Storage: `Ads::ModerationRecords` (r:1 w:0)
+	/// Storage: `Ads::Moderation` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Storage: `Ads::CreativeDurations` (r:0 w:1)
+	fn update_ad() -> Weight {
+		Weight::from_parts(48_000_000, 4576)
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().writes(5_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:0)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// Storage: `Ads::AdvertiserProfiles` (r:1 w:1)
+	fn top_up_ad() -> Weight {
+		Weight::from_parts(40_000_000, 4312)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `Ads::AdSpots` (r:1 w:0)
+	/// Storage: `Ads::BudgetUnlocksAt` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn withdraw_remaining_budget() -> Weight {
+		Weight::from_parts(36_000_000, 4312)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+}
@@ -0,0 +1,179 @@
+//! Benchmarking setup for pallet-fee-sponsorship
+
+use super::*;
+
+use frame_benchmarking::v2::*;
+use frame_support::pallet_prelude::{BoundedVec, EnsureOrigin, Get};
+use frame_system::RawOrigin;
+use sp_runtime::traits::Zero;
+use sp_std::vec::Vec;
+
+type RuntimeOriginOf<T> = <T as frame_system::Config>::RuntimeOrigin;
+
+/// Create an ad that can sponsor `requests` minimum sized sponsorships.
+fn sponsoring_ad<T: Config>(requests: u32) -> u32 {
+	T::BenchmarkHelper::create_ad(
+		T::MinSponsorshipAmount::get().saturating_mul(requests.max(1).into()),
+	)
+}
+
+/// Request a minimum sized sponsorship of `ad_id` for `user`.
+fn sponsorship_request<T: Config>(user: &T::AccountId, ad_id: u32) -> u32 {
+	let request_id = NextRequestId::<T>::get();
+	Pallet::<T>::sponsor_transaction(
+		RawOrigin::Signed(user.clone()).into(),
+		ad_id,
+		T::MinSponsorshipAmount::get(),
+	)
+	.expect("ad can sponsor the request; qed");
+	request_id
+}
+
+/// Register the account behind a successful `VerifierOrigin` as verifier.
+///
+/// With `busy` its unsettled verifications are filled up to one below the limit, the worst
+/// case for pushing to and removing from the list.
+fn verifier<T: Config>(busy: bool) -> Result<(RuntimeOriginOf<T>, T::AccountId), BenchmarkError> {
+	let origin =
+		T::VerifierOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+	let verifier =
+		T::VerifierOrigin::ensure_origin(origin.clone()).map_err(|_| BenchmarkError::Weightless)?;
+
+	Verifiers::<T>::insert(
+		&verifier,
+		VerifierStats { added_at: Zero::zero(), verified: 0, settled: 0 },
+	);
+	if busy {
+		let filler: Vec<u32> = (0..T::MaxUnsettledVerifications::get().saturating_sub(1))
+			.map(|i| u32::MAX - i)
+			.collect();
+		UnsettledVerifications::<T>::insert(&verifier, BoundedVec::truncate_from(filler));
+	}
+
+	Ok((origin, verifier))
+}
+
+#[benchmarks]
+mod benchmarks {
+	use super::*;
+
+	#[benchmark]
+	fn sponsor_transaction() {
+		let caller: T::AccountId = whitelisted_caller();
+		let ad_id = sponsoring_ad::<T>(1);
+		let fee = T::MinSponsorshipAmount::get();
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller.clone()), ad_id, fee);
+
+		assert!(PendingSponsorships::<T>::contains_key(&caller));
+	}
+
+	#[benchmark]
+	fn verify_ad_view() -> Result<(), BenchmarkError> {
+		let user: T::AccountId = account("user", 0, 0);
+		let request_id = sponsorship_request::<T>(&user, sponsoring_ad::<T>(1));
+		let (origin, verifier) = verifier::<T>(true)?;
+
+		#[extrinsic_call]
+		_(origin as RuntimeOriginOf<T>, request_id);
+
+		let request = SponsorshipRequests::<T>::get(request_id).expect("requested above; qed");
+		assert_eq!(request.verified_by, Some(verifier));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn reimburse_fee() -> Result<(), BenchmarkError> {
+		let user: T::AccountId = whitelisted_caller();
+		let request_id = sponsorship_request::<T>(&user, sponsoring_ad::<T>(1));
+		let (origin, _) = verifier::<T>(true)?;
+		Pallet::<T>::verify_ad_view(origin, request_id)?;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(user), request_id);
+
+		let request = SponsorshipRequests::<T>::get(request_id).expect("requested above; qed");
+		assert!(request.sponsored);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn cancel_sponsorship() -> Result<(), BenchmarkError> {
+		let user: T::AccountId = whitelisted_caller();
+		let request_id = sponsorship_request::<T>(&user, sponsoring_ad::<T>(1));
+		let (origin, _) = verifier::<T>(true)?;
+		Pallet::<T>::verify_ad_view(origin, request_id)?;
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(user), request_id);
+
+		assert!(!SponsorshipRequests::<T>::contains_key(request_id));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn add_verifier() -> Result<(), BenchmarkError> {
+		let origin =
+			T::GovernanceOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		let verifier: T::AccountId = account("verifier", 0, 0);
+
+		#[extrinsic_call]
+		_(origin as RuntimeOriginOf<T>, verifier.clone());
+
+		assert!(Verifiers::<T>::contains_key(&verifier));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn remove_verifier() -> Result<(), BenchmarkError> {
+		let origin =
+			T::GovernanceOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		let (_, verifier) = verifier::<T>(true)?;
+
+		#[extrinsic_call]
+		_(origin as RuntimeOriginOf<T>, verifier.clone());
+
+		assert!(!Verifiers::<T>::contains_key(&verifier));
+		Ok(())
+	}
+
+	/// `u` is the number of unsettled verifications that are rolled back.
+	#[benchmark]
+	fn revoke_verifier(
+		u: Linear<0, { T::MaxUnsettledVerifications::get() }>,
+	) -> Result<(), BenchmarkError> {
+		let origin =
+			T::GovernanceOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		let (verifier_origin, verifier) = verifier::<T>(false)?;
+		let ad_id = sponsoring_ad::<T>(u);
+		for i in 0..u {
+			let user: T::AccountId = account("user", i, 0);
+			let request_id = sponsorship_request::<T>(&user, ad_id);
+			Pallet::<T>::verify_ad_view(verifier_origin.clone(), request_id)?;
+		}
+
+		#[extrinsic_call]
+		_(origin as RuntimeOriginOf<T>, verifier.clone());
+
+		assert!(!Verifiers::<T>::contains_key(&verifier));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn verify_from_view() {
+		let caller: T::AccountId = whitelisted_caller();
+		let user: T::AccountId = account("user", 0, 0);
+		let ad_id = sponsoring_ad::<T>(1);
+		T::BenchmarkHelper::complete_view(&user, ad_id, frame_system::Pallet::<T>::block_number());
+		let request_id = sponsorship_request::<T>(&user, ad_id);
+
+		#[extrinsic_call]
+		_(RawOrigin::Signed(caller), request_id);
+
+		let request = SponsorshipRequests::<T>::get(request_id).expect("requested above; qed");
+		assert!(request.verified);
+	}
+
+	impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
+}
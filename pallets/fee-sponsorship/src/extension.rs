@@ -0,0 +1,233 @@
+//! Signed extension charging transaction fees to a sponsoring ad.
+
+use crate::{Config, Event, Pallet, PendingSponsorships, SponsorshipRequests, TotalSponsored};
+use codec::{Decode, Encode};
+use frame_support::dispatch::{DispatchInfo, PostDispatchInfo};
+use pallet_ads::AdBudgetProvider;
+use pallet_transaction_payment::{BalanceOf as PaymentBalanceOf, ChargeTransactionPayment};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{
+		DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SignedExtension, UniqueSaturatedInto,
+		Zero,
+	},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+	DispatchResult,
+};
+use sp_std::{fmt, vec};
+
+/// `InvalidTransaction::Custom` code: the referenced sponsorship request does not exist.
+pub const SPONSORSHIP_NOT_FOUND: u8 = 1;
+/// `InvalidTransaction::Custom` code: the ad view of the sponsorship has not been verified.
+pub const SPONSORSHIP_NOT_VERIFIED: u8 = 2;
+/// `InvalidTransaction::Custom` code: a sponsored transaction carries a tip.
+pub const SPONSORED_TIP: u8 = 3;
+
+/// Pays the transaction fee out of an ad budget when the signer references one of their
+/// verified sponsorship requests, and falls back to [`ChargeTransactionPayment`] otherwise.
+///
+/// The full fee is debited from the ad in `pre_dispatch` and the unused portion is
+/// credited back in `post_dispatch` once the actual weight is known. The charged fee is
+/// slashed from the advertiser's escrow, burning it like the runtime burns the fees that
+/// [`ChargeTransactionPayment`] withdraws. Sponsored transactions can't carry a tip.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct ChargeSponsoredTransactionPayment<T: Config> {
+	#[codec(compact)]
+	tip: PaymentBalanceOf<T>,
+	sponsorship: Option<u32>,
+}
+
+impl<T: Config> ChargeSponsoredTransactionPayment<T> {
+	/// Charge the fee and `tip` to the signer.
+	pub fn from(tip: PaymentBalanceOf<T>) -> Self {
+		Self { tip, sponsorship: None }
+	}
+
+	/// Charge the fee to the ad of sponsorship request `request_id`.
+	pub fn sponsored(request_id: u32) -> Self {
+		Self { tip: Zero::zero(), sponsorship: Some(request_id) }
+	}
+}
+
+impl<T: Config> fmt::Debug for ChargeSponsoredTransactionPayment<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "ChargeSponsoredTransactionPayment<{:?}, {:?}>", self.tip, self.sponsorship)
+	}
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+		Ok(())
+	}
+}
+
+/// Pre-dispatch state carried to `post_dispatch`.
+pub enum Pre<T: Config>
+where
+	PaymentBalanceOf<T>: Send + Sync,
+	<T as frame_system::Config>::RuntimeCall:
+		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+{
+	/// The fee was withdrawn from the signer by [`ChargeTransactionPayment`].
+	Charged(<ChargeTransactionPayment<T> as SignedExtension>::Pre),
+	/// The fee was debited from the budget of `ad_id`.
+	Sponsored { request_id: u32, who: T::AccountId, ad_id: u32, fee: PaymentBalanceOf<T> },
+}
+
+impl<T: Config> ChargeSponsoredTransactionPayment<T>
+where
+	PaymentBalanceOf<T>: Send + Sync,
+	<T as frame_system::Config>::RuntimeCall:
+		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+{
+	/// Check that `who` may charge this transaction to sponsorship `request_id`.
+	///
+	/// Returns the sponsoring ad and the fee that will be debited from it.
+	fn check_sponsorship(
+		&self,
+		who: &T::AccountId,
+		request_id: u32,
+		info: &DispatchInfoOf<<T as frame_system::Config>::RuntimeCall>,
+		len: usize,
+	) -> Result<(u32, PaymentBalanceOf<T>), TransactionValidityError> {
+		if !self.tip.is_zero() {
+			return Err(InvalidTransaction::Custom(SPONSORED_TIP).into());
+		}
+
+		let request = SponsorshipRequests::<T>::get(request_id)
+			.ok_or(InvalidTransaction::Custom(SPONSORSHIP_NOT_FOUND))?;
+
+		if request.user != *who {
+			return Err(InvalidTransaction::BadSigner.into());
+		}
+		if !request.verified {
+			return Err(InvalidTransaction::Custom(SPONSORSHIP_NOT_VERIFIED).into());
+		}
+		if request.sponsored {
+			return Err(InvalidTransaction::Stale.into());
+		}
+
+		let fee =
+			pallet_transaction_payment::Pallet::<T>::compute_fee(len as u32, info, Zero::zero());
+		let fee_amount: u128 = fee.unique_saturated_into();
+		if fee_amount > request.fee_amount {
+			return Err(InvalidTransaction::Payment.into());
+		}
+		Pallet::<T>::ensure_ad_can_pay(request.ad_id, fee_amount)
+			.map_err(|_| InvalidTransaction::Payment)?;
+
+		Ok((request.ad_id, fee))
+	}
+}
+
+impl<T: Config> SignedExtension for ChargeSponsoredTransactionPayment<T>
+where
+	PaymentBalanceOf<T>: Send + Sync,
+	<T as frame_system::Config>::RuntimeCall:
+		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+{
+	const IDENTIFIER: &'static str = "ChargeSponsoredTransactionPayment";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::RuntimeCall;
+	type AdditionalSigned = ();
+	type Pre = Pre<T>;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		let Some(request_id) = self.sponsorship else {
+			return ChargeTransactionPayment::<T>::from(self.tip).validate(who, call, info, len);
+		};
+
+		let (_, fee) = self.check_sponsorship(who, request_id, info, len)?;
+
+		Ok(ValidTransaction {
+			priority: ChargeTransactionPayment::<T>::get_priority(info, len, Zero::zero(), fee),
+			provides: vec![(b"sponsorship", request_id).encode()],
+			..Default::default()
+		})
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		let Some(request_id) = self.sponsorship else {
+			return ChargeTransactionPayment::<T>::from(self.tip)
+				.pre_dispatch(who, call, info, len)
+				.map(Pre::Charged);
+		};
+
+		let (ad_id, fee) = self.check_sponsorship(who, request_id, info, len)?;
+
+		T::AdBudget::debit(ad_id, fee.unique_saturated_into())
+			.map_err(|_| InvalidTransaction::Payment)?;
+
+		let verified_by = SponsorshipRequests::<T>::mutate(request_id, |maybe_request| {
+			maybe_request.as_mut().and_then(|request| {
+				request.sponsored = true;
+				request.verified_by.clone()
+			})
+		});
+		Pallet::<T>::settle_verification(request_id, verified_by.as_ref(), true);
+		PendingSponsorships::<T>::remove(who);
+
+		Ok(Pre::Sponsored { request_id, who: who.clone(), ad_id, fee })
+	}
+
+	fn post_dispatch(
+		maybe_pre: Option<Self::Pre>,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		match maybe_pre {
+			Some(Pre::Charged(pre)) =>
+				ChargeTransactionPayment::<T>::post_dispatch(Some(pre), info, post_info, len, result),
+			Some(Pre::Sponsored { request_id, who, ad_id, fee }) => {
+				let actual_fee = pallet_transaction_payment::Pallet::<T>::compute_actual_fee(
+					len as u32,
+					info,
+					post_info,
+					Zero::zero(),
+				)
+				.min(fee);
+
+				let debited: u128 = fee.unique_saturated_into();
+				let charged: u128 = actual_fee.unique_saturated_into();
+
+				T::AdBudget::credit(ad_id, debited.saturating_sub(charged))
+					.map_err(|_| InvalidTransaction::Payment)?;
+				T::AdBudget::settle(ad_id, charged, None)
+					.map_err(|_| InvalidTransaction::Payment)?;
+
+				TotalSponsored::<T>::mutate(ad_id, |total| {
+					*total = total.saturating_add(charged);
+				});
+
+				Pallet::<T>::deposit_event(Event::FeeSponsored {
+					request_id,
+					user: who,
+					amount: charged,
+				});
+
+				Ok(())
+			},
+			None => Ok(()),
+		}
+	}
+}
@@ -7,24 +7,79 @@
 
 pub use pallet::*;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+mod extension;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+pub mod weights;
+pub use extension::ChargeSponsoredTransactionPayment;
+pub use weights::*;
+
+/// Sets up state owned by other pallets that the benchmarks of this pallet depend on.
+#[cfg(feature = "runtime-benchmarks")]
+pub trait BenchmarkHelper<AccountId, BlockNumber> {
+	/// Create an active ad with at least `budget` left to sponsor and return its id.
+	fn create_ad(budget: u128) -> u32;
+
+	/// Record that `who` completed a view of `ad_id` at block `at`.
+	fn complete_view(who: &AccountId, ad_id: u32, at: BlockNumber);
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use frame_support::pallet_prelude::*;
 	use frame_system::pallet_prelude::*;
+	use pallet_ad_tracking::CompletedViewProvider;
+	use pallet_ads::AdBudgetProvider;
+	use sp_runtime::traits::{Saturating, Zero};
+	use sp_std::vec::Vec;
+	use crate::WeightInfo;
 
 	#[pallet::pallet]
 	pub struct Pallet<T>(_);
 
 	#[pallet::config]
-	pub trait Config: frame_system::Config {
+	pub trait Config: frame_system::Config + pallet_transaction_payment::Config {
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 		
 		/// Minimum fee amount that can be sponsored
 		#[pallet::constant]
 		type MinSponsorshipAmount: Get<u128>;
 		
-		/// Reference to Ads pallet for checking ad budgets
-		type AdsPallet: frame_support::traits::PalletInfo;
+		/// Maximum fee amount that can be sponsored
+		#[pallet::constant]
+		type MaxSponsoredFee: Get<u128>;
+		
+		/// Ad budgets that sponsorships are checked against and paid from
+		type AdBudget: AdBudgetProvider<Self::AccountId, u128>;
+		
+		/// Origin that verifies ad views; the resolved account must be a registered verifier
+		type VerifierOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
+		
+		/// Origin that can add, remove and revoke verifiers
+		type GovernanceOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		
+		/// Maximum number of unsettled verifications tracked per verifier
+		#[pallet::constant]
+		type MaxUnsettledVerifications: Get<u32>;
+		
+		/// Source of completed ad views used for automatic verification
+		type CompletedViews: CompletedViewProvider<Self::AccountId, BlockNumberFor<Self>>;
+		
+		/// How many blocks before or after a sponsorship request a completed view may have
+		/// happened and still verify it
+		#[pallet::constant]
+		type ViewVerificationWindow: Get<BlockNumberFor<Self>>;
+		
+		/// Weight information for extrinsics in this pallet
+		type WeightInfo: WeightInfo;
+		
+		/// Creates ads and completed views for benchmarks
+		#[cfg(feature = "runtime-benchmarks")]
+		type BenchmarkHelper: crate::BenchmarkHelper<Self::AccountId, BlockNumberFor<Self>>;
 	}
 
 	/// Sponsorship request structure
@@ -36,6 +91,22 @@ pub mod pallet {
 		pub fee_amount: u128,
 		pub verified: bool,
 		pub sponsored: bool,
+		/// Verifier that confirmed the ad view, if any
+		pub verified_by: Option<T::AccountId>,
+		/// Block number when the sponsorship was requested
+		pub requested_at: BlockNumberFor<T>,
+	}
+
+	/// Statistics kept for each registered verifier
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct VerifierStats<T: Config> {
+		/// Block number when the verifier was added
+		pub added_at: BlockNumberFor<T>,
+		/// Total number of ad views verified
+		pub verified: u32,
+		/// Verifications whose sponsorship has been paid
+		pub settled: u32,
 	}
 
 	/// Storage: Sponsorship requests by ID
@@ -58,6 +129,43 @@ pub mod pallet {
 	#[pallet::getter(fn total_sponsored)]
 	pub type TotalSponsored<T: Config> = StorageMap<_, Blake2_128Concat, u32, u128, ValueQuery>;
 
+	/// Storage: Authorized verifiers and their statistics
+	#[pallet::storage]
+	#[pallet::getter(fn verifiers)]
+	pub type Verifiers<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, VerifierStats<T>>;
+
+	/// Storage: Verified requests per verifier that have not been sponsored yet
+	#[pallet::storage]
+	#[pallet::getter(fn unsettled_verifications)]
+	pub type UnsettledVerifications<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		BoundedVec<u32, T::MaxUnsettledVerifications>,
+		ValueQuery,
+	>;
+
+	#[pallet::genesis_config]
+	#[derive(frame_support::DefaultNoBound)]
+	pub struct GenesisConfig<T: Config> {
+		/// Verifiers registered at genesis
+		pub verifiers: Vec<T::AccountId>,
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+		fn build(&self) {
+			for verifier in &self.verifiers {
+				assert!(
+					!Verifiers::<T>::contains_key(verifier),
+					"Verifiers must be unique in the genesis config"
+				);
+				let stats = VerifierStats { added_at: Zero::zero(), verified: 0, settled: 0 };
+				Verifiers::<T>::insert(verifier, stats);
+			}
+		}
+	}
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
@@ -69,6 +177,12 @@ pub mod pallet {
 		FeeSponsored { request_id: u32, user: T::AccountId, amount: u128 },
 		/// Sponsorship cancelled
 		SponsorshipCancelled { request_id: u32 },
+		/// Verifier added
+		VerifierAdded { verifier: T::AccountId },
+		/// Verifier removed
+		VerifierRemoved { verifier: T::AccountId },
+		/// Verifier revoked and their unsettled verifications rolled back
+		VerifierRevoked { verifier: T::AccountId, rolled_back: u32 },
 	}
 
 	#[pallet::error]
@@ -83,15 +197,32 @@ pub mod pallet {
 		InsufficientAdBudget,
 		/// Fee amount too low
 		FeeAmountTooLow,
+		/// Fee amount above `MaxSponsoredFee`
+		FeeAmountTooHigh,
 		/// Pending sponsorship exists
 		PendingSponsorshipExists,
+		/// Caller is not a registered verifier
+		NotVerifier,
+		/// Verifier already registered
+		VerifierAlreadyRegistered,
+		/// Ad view already verified
+		AlreadyVerified,
+		/// Verifier has too many unsettled verifications
+		TooManyUnsettledVerifications,
+		/// User has no completed view of the ad
+		NoCompletedView,
+		/// Completed view happened too long before or after the request
+		ViewOutsideWindow,
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		/// Request transaction fee sponsorship
+		///
+		/// `fee_amount` caps the fee of the transaction that `ChargeSponsoredTransactionPayment`
+		/// may charge to the ad once the request is verified.
 		#[pallet::call_index(0)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::sponsor_transaction())]
 		pub fn sponsor_transaction(
 			origin: OriginFor<T>,
 			ad_id: u32,
@@ -103,6 +234,9 @@ pub mod pallet {
 				fee_amount >= T::MinSponsorshipAmount::get(),
 				Error::<T>::FeeAmountTooLow
 			);
+			ensure!(fee_amount <= T::MaxSponsoredFee::get(), Error::<T>::FeeAmountTooHigh);
+			
+			Self::ensure_ad_can_sponsor(ad_id, fee_amount)?;
 			
 			ensure!(
 				!PendingSponsorships::<T>::contains_key(&who),
@@ -116,6 +250,8 @@ pub mod pallet {
 				fee_amount,
 				verified: false,
 				sponsored: false,
+				verified_by: None,
+				requested_at: frame_system::Pallet::<T>::block_number(),
 			};
 			
 			SponsorshipRequests::<T>::insert(request_id, request);
@@ -133,15 +269,34 @@ pub mod pallet {
 		}
 
 		/// Verify that user watched the ad
+		///
+		/// Must be called through `VerifierOrigin` by a registered verifier. The
+		/// verification stays unsettled until the sponsorship is paid and is rolled
+		/// back if the verifier is revoked in the meantime.
 		#[pallet::call_index(1)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::verify_ad_view())]
 		pub fn verify_ad_view(origin: OriginFor<T>, request_id: u32) -> DispatchResult {
-			// In production, this would be called by an oracle or trusted source
-			ensure_root(origin)?;
+			let verifier = T::VerifierOrigin::ensure_origin(origin)?;
+			ensure!(Verifiers::<T>::contains_key(&verifier), Error::<T>::NotVerifier);
 			
 			SponsorshipRequests::<T>::try_mutate(request_id, |maybe_request| -> DispatchResult {
 				let request = maybe_request.as_mut().ok_or(Error::<T>::RequestNotFound)?;
+				ensure!(!request.verified, Error::<T>::AlreadyVerified);
+				ensure!(!request.sponsored, Error::<T>::AlreadySponsored);
+				
+				UnsettledVerifications::<T>::try_mutate(&verifier, |unsettled| {
+					unsettled
+						.try_push(request_id)
+						.map_err(|_| Error::<T>::TooManyUnsettledVerifications)
+				})?;
+				Verifiers::<T>::mutate(&verifier, |maybe_stats| {
+					if let Some(stats) = maybe_stats {
+						stats.verified = stats.verified.saturating_add(1);
+					}
+				});
+				
 				request.verified = true;
+				request.verified_by = Some(verifier);
 				
 				Self::deposit_event(Event::AdViewVerified {
 					request_id,
@@ -153,8 +308,11 @@ pub mod pallet {
 		}
 
 		/// Execute fee reimbursement after verification
+		///
+		/// Pays the requested fee, at most `MaxSponsoredFee`, back to the user out of the
+		/// budget of the sponsoring ad, which must still be active and cover the fee.
 		#[pallet::call_index(2)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::reimburse_fee())]
 		pub fn reimburse_fee(origin: OriginFor<T>, request_id: u32) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 			
@@ -165,13 +323,15 @@ pub mod pallet {
 				ensure!(request.verified, Error::<T>::AdViewNotVerified);
 				ensure!(!request.sponsored, Error::<T>::AlreadySponsored);
 				
-				// Check if ad has sufficient budget
-				// Note: This requires accessing the Ads pallet storage
-				// In a real implementation, you'd use a trait bound or storage access
-				// For now, we'll just track the sponsorship
+				// Pay the fee back to the user out of the ad budget
+				Self::ensure_ad_can_sponsor(request.ad_id, request.fee_amount)?;
+				T::AdBudget::debit(request.ad_id, request.fee_amount)
+					.map_err(|_| Error::<T>::InsufficientAdBudget)?;
+				T::AdBudget::settle(request.ad_id, request.fee_amount, Some(&who))?;
 				
 				// Mark as sponsored
 				request.sponsored = true;
+				Self::settle_verification(request_id, request.verified_by.as_ref(), true);
 				
 				// Update total sponsored amount
 				TotalSponsored::<T>::mutate(request.ad_id, |total| {
@@ -193,7 +353,7 @@ pub mod pallet {
 
 		/// Cancel a sponsorship request
 		#[pallet::call_index(3)]
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::cancel_sponsorship())]
 		pub fn cancel_sponsorship(origin: OriginFor<T>, request_id: u32) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 			
@@ -203,6 +363,7 @@ pub mod pallet {
 			ensure!(request.user == who, DispatchError::BadOrigin);
 			ensure!(!request.sponsored, Error::<T>::AlreadySponsored);
 			
+			Self::settle_verification(request_id, request.verified_by.as_ref(), false);
 			SponsorshipRequests::<T>::remove(request_id);
 			PendingSponsorships::<T>::remove(&who);
 			
@@ -210,5 +371,155 @@ pub mod pallet {
 			
 			Ok(())
 		}
+
+		/// Authorize an account to verify ad views
+		#[pallet::call_index(4)]
+		#[pallet::weight(T::WeightInfo::add_verifier())]
+		pub fn add_verifier(origin: OriginFor<T>, verifier: T::AccountId) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			
+			ensure!(
+				!Verifiers::<T>::contains_key(&verifier),
+				Error::<T>::VerifierAlreadyRegistered
+			);
+			
+			let stats = VerifierStats {
+				added_at: frame_system::Pallet::<T>::block_number(),
+				verified: 0,
+				settled: 0,
+			};
+			Verifiers::<T>::insert(&verifier, stats);
+			
+			Self::deposit_event(Event::VerifierAdded { verifier });
+			
+			Ok(())
+		}
+
+		/// Remove a verifier, keeping the verifications they already made
+		#[pallet::call_index(5)]
+		#[pallet::weight(T::WeightInfo::remove_verifier())]
+		pub fn remove_verifier(origin: OriginFor<T>, verifier: T::AccountId) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			
+			ensure!(Verifiers::<T>::contains_key(&verifier), Error::<T>::NotVerifier);
+			
+			Verifiers::<T>::remove(&verifier);
+			UnsettledVerifications::<T>::remove(&verifier);
+			
+			Self::deposit_event(Event::VerifierRemoved { verifier });
+			
+			Ok(())
+		}
+
+		/// Revoke a verifier and roll back all of their unsettled verifications
+		///
+		/// Requests verified by the revoked account that have not been sponsored yet
+		/// return to the unverified state and need to be verified again.
+		#[pallet::call_index(6)]
+		#[pallet::weight(T::WeightInfo::revoke_verifier(T::MaxUnsettledVerifications::get()))]
+		pub fn revoke_verifier(origin: OriginFor<T>, verifier: T::AccountId) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			
+			ensure!(Verifiers::<T>::contains_key(&verifier), Error::<T>::NotVerifier);
+			
+			let mut rolled_back: u32 = 0;
+			for request_id in UnsettledVerifications::<T>::take(&verifier) {
+				SponsorshipRequests::<T>::mutate(request_id, |maybe_request| {
+					if let Some(request) = maybe_request {
+						if !request.sponsored && request.verified_by.as_ref() == Some(&verifier) {
+							request.verified = false;
+							request.verified_by = None;
+							rolled_back = rolled_back.saturating_add(1);
+						}
+					}
+				});
+			}
+			Verifiers::<T>::remove(&verifier);
+			
+			Self::deposit_event(Event::VerifierRevoked { verifier, rolled_back });
+			
+			Ok(())
+		}
+
+		/// Verify a request from a completed view recorded by ad tracking
+		///
+		/// Callable by anyone. Succeeds when the requesting user's latest unused view of
+		/// the sponsoring ad completed within `ViewVerificationWindow` blocks of the request.
+		/// The view is consumed, so each completed view verifies at most one request.
+		#[pallet::call_index(7)]
+		#[pallet::weight(T::WeightInfo::verify_from_view())]
+		pub fn verify_from_view(origin: OriginFor<T>, request_id: u32) -> DispatchResult {
+			let _ = ensure_signed(origin)?;
+			
+			SponsorshipRequests::<T>::try_mutate(request_id, |maybe_request| -> DispatchResult {
+				let request = maybe_request.as_mut().ok_or(Error::<T>::RequestNotFound)?;
+				ensure!(!request.verified, Error::<T>::AlreadyVerified);
+				ensure!(!request.sponsored, Error::<T>::AlreadySponsored);
+				
+				let completed_at =
+					T::CompletedViews::take_completed_view(&request.user, request.ad_id)
+						.ok_or(Error::<T>::NoCompletedView)?;
+				let window = T::ViewVerificationWindow::get();
+				ensure!(
+					completed_at.saturating_add(window) >= request.requested_at &&
+						completed_at <= request.requested_at.saturating_add(window),
+					Error::<T>::ViewOutsideWindow
+				);
+				
+				request.verified = true;
+				
+				Self::deposit_event(Event::AdViewVerified {
+					request_id,
+					user: request.user.clone(),
+				});
+				
+				Ok(())
+			})
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Stop tracking `request_id` as unsettled for the verifier that verified it.
+		///
+		/// `sponsored` counts the verification as settled in the verifier statistics.
+		pub fn settle_verification(
+			request_id: u32,
+			verified_by: Option<&T::AccountId>,
+			sponsored: bool,
+		) {
+			let Some(verifier) = verified_by else { return };
+			
+			UnsettledVerifications::<T>::mutate(verifier, |unsettled| {
+				unsettled.retain(|id| *id != request_id);
+			});
+			if sponsored {
+				Verifiers::<T>::mutate(verifier, |maybe_stats| {
+					if let Some(stats) = maybe_stats {
+						stats.settled = stats.settled.saturating_add(1);
+					}
+				});
+			}
+		}
+
+		/// Ensure `ad_id` is active and its remaining budget covers `amount`.
+		pub fn ensure_ad_can_sponsor(ad_id: u32, amount: u128) -> DispatchResult {
+			ensure!(T::AdBudget::is_active(ad_id), Error::<T>::InsufficientAdBudget);
+			ensure!(
+				T::AdBudget::remaining_budget(ad_id).unwrap_or_default() >= amount,
+				Error::<T>::InsufficientAdBudget
+			);
+			Ok(())
+		}
+
+		/// Ensure a verified sponsorship of `ad_id` can still be paid: the ad is active or its
+		/// budget is locked after deactivation, and the remaining budget covers `amount`.
+		pub fn ensure_ad_can_pay(ad_id: u32, amount: u128) -> DispatchResult {
+			ensure!(T::AdBudget::is_payable(ad_id), Error::<T>::InsufficientAdBudget);
+			ensure!(
+				T::AdBudget::remaining_budget(ad_id).unwrap_or_default() >= amount,
+				Error::<T>::InsufficientAdBudget
+			);
+			Ok(())
+		}
 	}
 }
@@ -0,0 +1,233 @@
+//! Test environment for pallet-fee-sponsorship
+
+use crate as pallet_fee_sponsorship;
+use frame_support::{
+	assert_ok, derive_impl,
+	dispatch::DispatchClass,
+	parameter_types,
+	traits::{ConstU128, ConstU32, ConstU64, ConstU8, Currency},
+	weights::{IdentityFee, Weight},
+};
+use frame_system::{EnsureRoot, EnsureSigned};
+use pallet_transaction_payment::FungibleAdapter;
+use sp_runtime::{BuildStorage, Perbill};
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+pub const ALICE: u64 = 1;
+pub const BOB: u64 = 2;
+pub const VERIFIER: u64 = 3;
+
+/// Free balance of every test account at genesis.
+pub const INITIAL_BALANCE: u128 = 10_000;
+
+/// Weight, and fee, of the base extrinsic.
+pub const BASE_FEE: u64 = 10;
+
+/// Smallest fee that can be sponsored.
+pub const MIN_SPONSORSHIP: u128 = 100;
+
+/// Largest fee that can be sponsored.
+pub const MAX_SPONSORED_FEE: u128 = 1_000;
+
+/// Blocks between a completed view and the request it verifies.
+pub const VERIFICATION_WINDOW: u64 = 10;
+
+frame_support::construct_runtime!(
+	pub enum Test {
+		System: frame_system,
+		Timestamp: pallet_timestamp,
+		Balances: pallet_balances,
+		TransactionPayment: pallet_transaction_payment,
+		Ads: pallet_ads,
+		AdTracking: pallet_ad_tracking,
+		FeeSponsorship: pallet_fee_sponsorship,
+	}
+);
+
+parameter_types! {
+	pub BlockWeights: frame_system::limits::BlockWeights =
+		frame_system::limits::BlockWeights::builder()
+			.base_block(Weight::zero())
+			.for_class(DispatchClass::all(), |weights| {
+				weights.base_extrinsic = Weight::from_parts(BASE_FEE, 0);
+			})
+			.for_class(DispatchClass::non_mandatory(), |weights| {
+				weights.max_total = Weight::from_parts(1024, u64::MAX).into();
+			})
+			.build_or_panic();
+}
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+impl frame_system::Config for Test {
+	type Block = Block;
+	type BlockWeights = BlockWeights;
+	type AccountData = pallet_balances::AccountData<u128>;
+}
+
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = ConstU64<1>;
+	type WeightInfo = ();
+}
+
+#[derive_impl(pallet_balances::config_preludes::TestDefaultConfig)]
+impl pallet_balances::Config for Test {
+	type Balance = u128;
+	type ExistentialDeposit = ConstU128<1>;
+	type AccountStore = System;
+}
+
+impl pallet_transaction_payment::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type OnChargeTransaction = FungibleAdapter<Balances, ()>;
+	type OperationalFeeMultiplier = ConstU8<5>;
+	type WeightToFee = IdentityFee<u128>;
+	type LengthToFee = IdentityFee<u128>;
+	type FeeMultiplierUpdate = ();
+}
+
+impl pallet_ads::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type MaxAdNameLength = ConstU32<32>;
+	type MaxAdDescriptionLength = ConstU32<64>;
+	type MaxCidLength = ConstU32<64>;
+	type MaxAdvertiserNameLength = ConstU32<32>;
+	type MinAdvertiserDeposit = ConstU128<100>;
+	type AuctionRevenue = ();
+	type MaxAuctionBids = ConstU32<4>;
+	type MaxAuctionsPerBlock = ConstU32<4>;
+	type MaxScheduledAdsPerBlock = ConstU32<4>;
+	type ModeratorOrigin = EnsureRoot<u64>;
+	type MaxRejectionReasonLength = ConstU32<32>;
+	type AppealBond = ConstU128<10>;
+	type GovernanceOrigin = EnsureRoot<u64>;
+	type OnSlash = ();
+	type MaxSuspensionsPerBlock = ConstU32<4>;
+	type MaxCreativeHistory = ConstU32<2>;
+	type WithdrawalCooldown = ConstU64<10>;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const MinWatchRatio: Perbill = Perbill::from_percent(80);
+}
+
+impl pallet_ad_tracking::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Ads = Ads;
+	type MinWatchDuration = ConstU64<5_000>;
+	type MinWatchRatio = MinWatchRatio;
+	type ViewCooldown = ConstU64<10>;
+	type ClickCooldown = ConstU64<5>;
+	type MaxInteractionsPerBlock = ConstU32<2>;
+	type MaxViewsPerAccountPerDay = ConstU32<3>;
+	type BlocksPerDay = ConstU64<100>;
+	type ClientKeyOrigin = EnsureRoot<u64>;
+	type MaxAttestationLifetime = ConstU64<10>;
+	type MaxAttestationsPerBlock = ConstU32<2>;
+	type Currency = Balances;
+	type AggregatorOrigin = EnsureSigned<u64>;
+	type AggregatorBond = ConstU128<1_000>;
+	type BatchPeriod = ConstU64<10>;
+	type ChallengePeriod = ConstU64<20>;
+	type MaxAdsPerBatch = ConstU32<4>;
+	type MaxProofLength = ConstU32<8>;
+	type OnSlash = ();
+	type WeightInfo = ();
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = BenchmarkHelper;
+}
+
+impl pallet_fee_sponsorship::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type MinSponsorshipAmount = ConstU128<MIN_SPONSORSHIP>;
+	type MaxSponsoredFee = ConstU128<MAX_SPONSORED_FEE>;
+	type AdBudget = Ads;
+	type VerifierOrigin = EnsureSigned<u64>;
+	type GovernanceOrigin = EnsureRoot<u64>;
+	type MaxUnsettledVerifications = ConstU32<2>;
+	type CompletedViews = AdTracking;
+	type ViewVerificationWindow = ConstU64<VERIFICATION_WINDOW>;
+	type WeightInfo = ();
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = BenchmarkHelper;
+}
+
+/// Sets up the ads and completed views the benchmarks depend on.
+#[cfg(feature = "runtime-benchmarks")]
+pub struct BenchmarkHelper;
+
+#[cfg(feature = "runtime-benchmarks")]
+impl crate::BenchmarkHelper<u64, u64> for BenchmarkHelper {
+	fn create_ad(budget: u128) -> u32 {
+		create_ad(budget)
+	}
+
+	fn complete_view(who: &u64, ad_id: u32, at: u64) {
+		complete_view(who, ad_id, at);
+	}
+}
+
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_ad_tracking::BenchmarkHelper for BenchmarkHelper {
+	fn create_ad() -> u32 {
+		create_ad(1_000_000)
+	}
+}
+
+/// Create an approved ad with `budget` and return its id.
+///
+/// Each ad gets its own advertiser and spot.
+pub fn create_ad(budget: u128) -> u32 {
+	let spot_id = pallet_ads::NextSpotId::<Test>::get();
+	let ad_id = pallet_ads::NextAdId::<Test>::get();
+	let advertiser = advertiser(ad_id);
+	let origin = RuntimeOrigin::signed(advertiser);
+	Balances::make_free_balance_be(&advertiser, budget.saturating_add(1_000));
+
+	assert_ok!(Ads::create_ad_spot(RuntimeOrigin::root()));
+	assert_ok!(Ads::register_advertiser(origin.clone(), b"Advertiser".to_vec(), 100));
+	assert_ok!(Ads::submit_ad(
+		origin,
+		spot_id,
+		b"Ad".to_vec(),
+		b"An ad".to_vec(),
+		b"QmCid".to_vec(),
+		10_000,
+		budget,
+		None,
+		None,
+	));
+	assert_ok!(Ads::approve_ad(RuntimeOrigin::root(), ad_id));
+	ad_id
+}
+
+/// Advertiser of the ads created by [`create_ad`].
+pub fn advertiser(ad_id: u32) -> u64 {
+	1_000 + u64::from(ad_id)
+}
+
+/// Record that `who` completed a view of `ad_id` at block `at`.
+pub fn complete_view(who: &u64, ad_id: u32, at: u64) {
+	pallet_ad_tracking::LastCompletedView::<Test>::insert(who, ad_id, at);
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![
+			(ALICE, INITIAL_BALANCE),
+			(BOB, INITIAL_BALANCE),
+			(VERIFIER, INITIAL_BALANCE),
+		],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,524 @@
+use crate::{
+	extension::{SPONSORED_TIP, SPONSORSHIP_NOT_FOUND, SPONSORSHIP_NOT_VERIFIED},
+	mock::*,
+	ChargeSponsoredTransactionPayment, Error, Event, PendingSponsorships, SponsorshipRequests,
+	TotalSponsored, UnsettledVerifications, Verifiers,
+};
+use codec::{Compact, Decode, Encode};
+use frame_support::{
+	assert_noop, assert_ok,
+	dispatch::{DispatchInfo, Pays, PostDispatchInfo},
+	weights::Weight,
+};
+use sp_runtime::{
+	traits::SignedExtension,
+	transaction_validity::{InvalidTransaction, TransactionValidityError},
+	DispatchError,
+};
+
+/// Encoded length of the test transactions.
+const LEN: usize = 10;
+
+/// Declared weight of the test call.
+const CALL_WEIGHT: u64 = 100;
+
+/// Weight the test call actually used.
+const ACTUAL_WEIGHT: u64 = 40;
+
+/// Fee of the test transaction for its declared weight.
+const FEE: u128 = (BASE_FEE + LEN as u64 + CALL_WEIGHT) as u128;
+
+/// Fee of the test transaction for the weight it actually used.
+const ACTUAL_FEE: u128 = (BASE_FEE + LEN as u64 + ACTUAL_WEIGHT) as u128;
+
+fn call() -> RuntimeCall {
+	RuntimeCall::System(frame_system::Call::remark { remark: vec![] })
+}
+
+fn info() -> DispatchInfo {
+	DispatchInfo { weight: Weight::from_parts(CALL_WEIGHT, 0), ..Default::default() }
+}
+
+fn post_info() -> PostDispatchInfo {
+	PostDispatchInfo {
+		actual_weight: Some(Weight::from_parts(ACTUAL_WEIGHT, 0)),
+		pays_fee: Pays::Yes,
+	}
+}
+
+fn add_verifier() {
+	assert_ok!(FeeSponsorship::add_verifier(RuntimeOrigin::root(), VERIFIER));
+}
+
+/// Request a sponsorship of `fee_amount` from `ad_id` for `who` and return its id.
+fn request(who: u64, ad_id: u32, fee_amount: u128) -> u32 {
+	let request_id = crate::NextRequestId::<Test>::get();
+	assert_ok!(FeeSponsorship::sponsor_transaction(RuntimeOrigin::signed(who), ad_id, fee_amount));
+	request_id
+}
+
+/// Request a sponsorship and have `VERIFIER` verify it.
+fn verified_request(who: u64, ad_id: u32, fee_amount: u128) -> u32 {
+	let request_id = request(who, ad_id, fee_amount);
+	assert_ok!(FeeSponsorship::verify_ad_view(RuntimeOrigin::signed(VERIFIER), request_id));
+	request_id
+}
+
+/// Sponsored extension for `request_id` that also carries a tip.
+fn tipped(request_id: u32) -> ChargeSponsoredTransactionPayment<Test> {
+	let encoded = (Compact(1u128), Some(request_id)).encode();
+	ChargeSponsoredTransactionPayment::decode(&mut &encoded[..]).unwrap()
+}
+
+fn validity_error(
+	ext: ChargeSponsoredTransactionPayment<Test>,
+	who: u64,
+) -> Option<TransactionValidityError> {
+	ext.validate(&who, &call(), &info(), LEN).err()
+}
+
+#[test]
+fn sponsor_transaction_works() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad(1_000);
+
+		let request_id = request(ALICE, ad_id, 200);
+
+		let request = SponsorshipRequests::<Test>::get(request_id).unwrap();
+		assert_eq!(request.user, ALICE);
+		assert_eq!(request.fee_amount, 200);
+		assert!(!request.verified);
+		assert_eq!(request.requested_at, 1);
+		assert_eq!(PendingSponsorships::<Test>::get(ALICE), Some(request_id));
+		System::assert_last_event(
+			Event::SponsorshipRequested { request_id, user: ALICE, ad_id, fee_amount: 200 }.into(),
+		);
+	});
+}
+
+#[test]
+fn sponsor_transaction_checks_fee_amount() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad(10_000);
+
+		assert_noop!(
+			FeeSponsorship::sponsor_transaction(
+				RuntimeOrigin::signed(ALICE),
+				ad_id,
+				MIN_SPONSORSHIP - 1
+			),
+			Error::<Test>::FeeAmountTooLow
+		);
+		assert_noop!(
+			FeeSponsorship::sponsor_transaction(
+				RuntimeOrigin::signed(ALICE),
+				ad_id,
+				MAX_SPONSORED_FEE + 1
+			),
+			Error::<Test>::FeeAmountTooHigh
+		);
+		assert_ok!(FeeSponsorship::sponsor_transaction(
+			RuntimeOrigin::signed(ALICE),
+			ad_id,
+			MAX_SPONSORED_FEE
+		));
+	});
+}
+
+#[test]
+fn sponsor_transaction_requires_ad_budget() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad(150);
+
+		assert_noop!(
+			FeeSponsorship::sponsor_transaction(RuntimeOrigin::signed(ALICE), ad_id, 200),
+			Error::<Test>::InsufficientAdBudget
+		);
+		assert_noop!(
+			FeeSponsorship::sponsor_transaction(RuntimeOrigin::signed(ALICE), ad_id + 1, 100),
+			Error::<Test>::InsufficientAdBudget
+		);
+	});
+}
+
+#[test]
+fn only_one_pending_sponsorship_per_user() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad(1_000);
+		request(ALICE, ad_id, 100);
+
+		assert_noop!(
+			FeeSponsorship::sponsor_transaction(RuntimeOrigin::signed(ALICE), ad_id, 100),
+			Error::<Test>::PendingSponsorshipExists
+		);
+		request(BOB, ad_id, 100);
+	});
+}
+
+#[test]
+fn governance_manages_verifiers() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			FeeSponsorship::add_verifier(RuntimeOrigin::signed(ALICE), VERIFIER),
+			DispatchError::BadOrigin
+		);
+		add_verifier();
+		assert_noop!(
+			FeeSponsorship::add_verifier(RuntimeOrigin::root(), VERIFIER),
+			Error::<Test>::VerifierAlreadyRegistered
+		);
+		assert_eq!(Verifiers::<Test>::get(VERIFIER).unwrap().added_at, 1);
+
+		assert_ok!(FeeSponsorship::remove_verifier(RuntimeOrigin::root(), VERIFIER));
+		assert_noop!(
+			FeeSponsorship::remove_verifier(RuntimeOrigin::root(), VERIFIER),
+			Error::<Test>::NotVerifier
+		);
+		assert_noop!(
+			FeeSponsorship::revoke_verifier(RuntimeOrigin::root(), VERIFIER),
+			Error::<Test>::NotVerifier
+		);
+	});
+}
+
+#[test]
+fn verify_ad_view_requires_verifier() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad(1_000);
+		let request_id = request(ALICE, ad_id, 100);
+
+		assert_noop!(
+			FeeSponsorship::verify_ad_view(RuntimeOrigin::signed(VERIFIER), request_id),
+			Error::<Test>::NotVerifier
+		);
+
+		add_verifier();
+		assert_noop!(
+			FeeSponsorship::verify_ad_view(RuntimeOrigin::signed(VERIFIER), request_id + 1),
+			Error::<Test>::RequestNotFound
+		);
+		assert_ok!(FeeSponsorship::verify_ad_view(RuntimeOrigin::signed(VERIFIER), request_id));
+		assert_noop!(
+			FeeSponsorship::verify_ad_view(RuntimeOrigin::signed(VERIFIER), request_id),
+			Error::<Test>::AlreadyVerified
+		);
+
+		let request = SponsorshipRequests::<Test>::get(request_id).unwrap();
+		assert!(request.verified);
+		assert_eq!(request.verified_by, Some(VERIFIER));
+		assert_eq!(UnsettledVerifications::<Test>::get(VERIFIER).into_inner(), vec![request_id]);
+		assert_eq!(Verifiers::<Test>::get(VERIFIER).unwrap().verified, 1);
+	});
+}
+
+#[test]
+fn unsettled_verifications_are_bounded() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad(1_000);
+		add_verifier();
+		verified_request(10, ad_id, 100);
+		verified_request(11, ad_id, 100);
+
+		let request_id = request(12, ad_id, 100);
+		assert_noop!(
+			FeeSponsorship::verify_ad_view(RuntimeOrigin::signed(VERIFIER), request_id),
+			Error::<Test>::TooManyUnsettledVerifications
+		);
+	});
+}
+
+#[test]
+fn remove_verifier_keeps_verifications() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad(1_000);
+		add_verifier();
+		let request_id = verified_request(ALICE, ad_id, 100);
+
+		assert_ok!(FeeSponsorship::remove_verifier(RuntimeOrigin::root(), VERIFIER));
+
+		assert!(SponsorshipRequests::<Test>::get(request_id).unwrap().verified);
+		assert!(UnsettledVerifications::<Test>::get(VERIFIER).is_empty());
+		System::assert_last_event(Event::VerifierRemoved { verifier: VERIFIER }.into());
+	});
+}
+
+#[test]
+fn revoke_verifier_rolls_back_unsettled_verifications() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad(1_000);
+		add_verifier();
+		let first = verified_request(ALICE, ad_id, 100);
+		let second = verified_request(BOB, ad_id, 100);
+
+		assert_ok!(FeeSponsorship::revoke_verifier(RuntimeOrigin::root(), VERIFIER));
+
+		for request_id in [first, second] {
+			let request = SponsorshipRequests::<Test>::get(request_id).unwrap();
+			assert!(!request.verified);
+			assert_eq!(request.verified_by, None);
+		}
+		assert!(!Verifiers::<Test>::contains_key(VERIFIER));
+		assert!(UnsettledVerifications::<Test>::get(VERIFIER).is_empty());
+		System::assert_last_event(
+			Event::VerifierRevoked { verifier: VERIFIER, rolled_back: 2 }.into(),
+		);
+	});
+}
+
+#[test]
+fn revoke_verifier_keeps_sponsored_verifications() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad(1_000);
+		add_verifier();
+		let request_id = verified_request(ALICE, ad_id, 200);
+		assert_ok!(ChargeSponsoredTransactionPayment::<Test>::sponsored(request_id)
+			.pre_dispatch(&ALICE, &call(), &info(), LEN)
+			.map(|_| ()));
+
+		assert_ok!(FeeSponsorship::revoke_verifier(RuntimeOrigin::root(), VERIFIER));
+
+		assert!(SponsorshipRequests::<Test>::get(request_id).unwrap().verified);
+		System::assert_last_event(
+			Event::VerifierRevoked { verifier: VERIFIER, rolled_back: 0 }.into(),
+		);
+	});
+}
+
+#[test]
+fn cancel_sponsorship_works() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad(1_000);
+		add_verifier();
+		let request_id = verified_request(ALICE, ad_id, 100);
+
+		assert_noop!(
+			FeeSponsorship::cancel_sponsorship(RuntimeOrigin::signed(BOB), request_id),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(FeeSponsorship::cancel_sponsorship(RuntimeOrigin::signed(ALICE), request_id));
+
+		assert!(!SponsorshipRequests::<Test>::contains_key(request_id));
+		assert!(!PendingSponsorships::<Test>::contains_key(ALICE));
+		assert!(UnsettledVerifications::<Test>::get(VERIFIER).is_empty());
+		assert_eq!(Verifiers::<Test>::get(VERIFIER).unwrap().settled, 0);
+		System::assert_last_event(Event::SponsorshipCancelled { request_id }.into());
+
+		request(ALICE, ad_id, 100);
+	});
+}
+
+#[test]
+fn reimburse_fee_pays_user_out_of_ad_budget() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad(1_000);
+		add_verifier();
+		let request_id = request(ALICE, ad_id, 200);
+		let reserved = Balances::reserved_balance(advertiser(ad_id));
+
+		assert_noop!(
+			FeeSponsorship::reimburse_fee(RuntimeOrigin::signed(ALICE), request_id),
+			Error::<Test>::AdViewNotVerified
+		);
+		assert_ok!(FeeSponsorship::verify_ad_view(RuntimeOrigin::signed(VERIFIER), request_id));
+		assert_noop!(
+			FeeSponsorship::reimburse_fee(RuntimeOrigin::signed(BOB), request_id),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(FeeSponsorship::reimburse_fee(RuntimeOrigin::signed(ALICE), request_id));
+
+		assert_eq!(pallet_ads::Ads::<Test>::get(ad_id).unwrap().remaining_budget, 800);
+		assert_eq!(Balances::reserved_balance(advertiser(ad_id)), reserved - 200);
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE + 200);
+		assert_eq!(TotalSponsored::<Test>::get(ad_id), 200);
+		assert!(SponsorshipRequests::<Test>::get(request_id).unwrap().sponsored);
+		assert!(!PendingSponsorships::<Test>::contains_key(ALICE));
+		assert_eq!(Verifiers::<Test>::get(VERIFIER).unwrap().settled, 1);
+		System::assert_last_event(
+			Event::FeeSponsored { request_id, user: ALICE, amount: 200 }.into(),
+		);
+		assert_noop!(
+			FeeSponsorship::reimburse_fee(RuntimeOrigin::signed(ALICE), request_id),
+			Error::<Test>::AlreadySponsored
+		);
+	});
+}
+
+#[test]
+fn reimburse_fee_rejects_inactive_or_underfunded_ad() {
+	new_test_ext().execute_with(|| {
+		add_verifier();
+
+		let ad_id = create_ad(1_000);
+		let request_id = verified_request(ALICE, ad_id, 200);
+		assert_ok!(Ads::debit_budget(ad_id, 900));
+		assert_noop!(
+			FeeSponsorship::reimburse_fee(RuntimeOrigin::signed(ALICE), request_id),
+			Error::<Test>::InsufficientAdBudget
+		);
+
+		let ad_id = create_ad(1_000);
+		let request_id = verified_request(BOB, ad_id, 200);
+		assert_ok!(Ads::deactivate_ad(RuntimeOrigin::signed(advertiser(ad_id)), ad_id));
+		assert!(pallet_ads::Ads::<Test>::get(ad_id).unwrap().remaining_budget >= 200);
+		assert_noop!(
+			FeeSponsorship::reimburse_fee(RuntimeOrigin::signed(BOB), request_id),
+			Error::<Test>::InsufficientAdBudget
+		);
+	});
+}
+
+#[test]
+fn verify_from_view_consumes_the_view() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad(1_000);
+		let request_id = request(ALICE, ad_id, 100);
+
+		assert_noop!(
+			FeeSponsorship::verify_from_view(RuntimeOrigin::signed(BOB), request_id),
+			Error::<Test>::NoCompletedView
+		);
+
+		complete_view(&ALICE, ad_id, 1);
+		assert_ok!(FeeSponsorship::verify_from_view(RuntimeOrigin::signed(BOB), request_id));
+
+		let request = SponsorshipRequests::<Test>::get(request_id).unwrap();
+		assert!(request.verified);
+		assert_eq!(request.verified_by, None);
+		assert!(!pallet_ad_tracking::LastCompletedView::<Test>::contains_key(ALICE, ad_id));
+		System::assert_last_event(Event::AdViewVerified { request_id, user: ALICE }.into());
+
+		assert_ok!(FeeSponsorship::cancel_sponsorship(RuntimeOrigin::signed(ALICE), request_id));
+		let request_id = request(ALICE, ad_id, 100);
+		assert_noop!(
+			FeeSponsorship::verify_from_view(RuntimeOrigin::signed(BOB), request_id),
+			Error::<Test>::NoCompletedView
+		);
+	});
+}
+
+#[test]
+fn verify_from_view_requires_view_within_window() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad(1_000);
+		complete_view(&ALICE, ad_id, 1);
+
+		System::set_block_number(2 + VERIFICATION_WINDOW);
+		let request_id = request(ALICE, ad_id, 100);
+		assert_noop!(
+			FeeSponsorship::verify_from_view(RuntimeOrigin::signed(ALICE), request_id),
+			Error::<Test>::ViewOutsideWindow
+		);
+
+		complete_view(&ALICE, ad_id, 3 + 2 * VERIFICATION_WINDOW);
+		assert_noop!(
+			FeeSponsorship::verify_from_view(RuntimeOrigin::signed(ALICE), request_id),
+			Error::<Test>::ViewOutsideWindow
+		);
+
+		complete_view(&ALICE, ad_id, 2 + 2 * VERIFICATION_WINDOW);
+		assert_ok!(FeeSponsorship::verify_from_view(RuntimeOrigin::signed(ALICE), request_id));
+	});
+}
+
+#[test]
+fn extension_rejects_invalid_sponsorships() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad(1_000);
+		add_verifier();
+
+		assert_eq!(
+			validity_error(ChargeSponsoredTransactionPayment::sponsored(0), ALICE),
+			Some(InvalidTransaction::Custom(SPONSORSHIP_NOT_FOUND).into())
+		);
+
+		let request_id = request(ALICE, ad_id, 200);
+		assert_eq!(
+			validity_error(ChargeSponsoredTransactionPayment::sponsored(request_id), ALICE),
+			Some(InvalidTransaction::Custom(SPONSORSHIP_NOT_VERIFIED).into())
+		);
+
+		assert_ok!(FeeSponsorship::verify_ad_view(RuntimeOrigin::signed(VERIFIER), request_id));
+		assert_eq!(
+			validity_error(ChargeSponsoredTransactionPayment::sponsored(request_id), BOB),
+			Some(InvalidTransaction::BadSigner.into())
+		);
+		assert_eq!(
+			validity_error(tipped(request_id), ALICE),
+			Some(InvalidTransaction::Custom(SPONSORED_TIP).into())
+		);
+		assert_ok!(ChargeSponsoredTransactionPayment::<Test>::sponsored(request_id).validate(
+			&ALICE,
+			&call(),
+			&info(),
+			LEN
+		));
+
+		let request_id = verified_request(BOB, ad_id, MIN_SPONSORSHIP);
+		assert_eq!(
+			validity_error(ChargeSponsoredTransactionPayment::sponsored(request_id), BOB),
+			Some(InvalidTransaction::Payment.into())
+		);
+	});
+}
+
+#[test]
+fn extension_charges_sponsored_fee_to_ad() {
+	new_test_ext().execute_with(|| {
+		let ad_id = create_ad(1_000);
+		add_verifier();
+		let request_id = verified_request(ALICE, ad_id, 200);
+		let reserved = Balances::reserved_balance(advertiser(ad_id));
+
+		let pre = ChargeSponsoredTransactionPayment::<Test>::sponsored(request_id)
+			.pre_dispatch(&ALICE, &call(), &info(), LEN)
+			.unwrap();
+
+		assert_eq!(pallet_ads::Ads::<Test>::get(ad_id).unwrap().remaining_budget, 1_000 - FEE);
+		assert!(SponsorshipRequests::<Test>::get(request_id).unwrap().sponsored);
+		assert!(!PendingSponsorships::<Test>::contains_key(ALICE));
+		assert!(UnsettledVerifications::<Test>::get(VERIFIER).is_empty());
+		assert_eq!(Verifiers::<Test>::get(VERIFIER).unwrap().settled, 1);
+		assert_eq!(
+			validity_error(ChargeSponsoredTransactionPayment::sponsored(request_id), ALICE),
+			Some(InvalidTransaction::Stale.into())
+		);
+
+		assert_ok!(ChargeSponsoredTransactionPayment::<Test>::post_dispatch(
+			Some(pre),
+			&info(),
+			&post_info(),
+			LEN,
+			&Ok(())
+		));
+
+		assert_eq!(
+			pallet_ads::Ads::<Test>::get(ad_id).unwrap().remaining_budget,
+			1_000 - ACTUAL_FEE
+		);
+		assert_eq!(Balances::reserved_balance(advertiser(ad_id)), reserved - ACTUAL_FEE);
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE);
+		assert_eq!(TotalSponsored::<Test>::get(ad_id), ACTUAL_FEE);
+		System::assert_last_event(
+			Event::FeeSponsored { request_id, user: ALICE, amount: ACTUAL_FEE }.into(),
+		);
+	});
+}
+
+#[test]
+fn extension_charges_signer_without_sponsorship() {
+	new_test_ext().execute_with(|| {
+		let pre = ChargeSponsoredTransactionPayment::<Test>::from(0)
+			.pre_dispatch(&ALICE, &call(), &info(), LEN)
+			.unwrap();
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE - FEE);
+
+		assert_ok!(ChargeSponsoredTransactionPayment::<Test>::post_dispatch(
+			Some(pre),
+			&info(),
+			&post_info(),
+			LEN,
+			&Ok(())
+		));
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE - ACTUAL_FEE);
+	});
+}
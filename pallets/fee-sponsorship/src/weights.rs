@@ -0,0 +1,174 @@
+//! Weights for `pallet_fee_sponsorship`
+//!
+//! Hand estimates from the storage accesses of each call, listed above each function, and a
+//! rough execution time. Replace them with the output of `polkaads-node benchmark pallet`
+//! once the benchmarks have been run on reference hardware.
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+#![allow(missing_docs)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use core::marker::PhantomData;
+
+/// Weight functions needed for `pallet_fee_sponsorship`.
+pub trait WeightInfo {
+	fn sponsor_transaction() -> Weight;
+	fn verify_ad_view() -> Weight;
+	fn reimburse_fee() -> Weight;
+	fn cancel_sponsorship() -> Weight;
+	fn add_verifier() -> Weight;
+	fn remove_verifier() -> Weight;
+	fn revoke_verifier(u: u32, ) -> Weight;
+	fn verify_from_view() -> Weight;
+}
+
+/// Estimated weights for `pallet_fee_sponsorship`.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: `Ads::Ads` (r:1 w:0)
+	/// Storage: `FeeSponsorship::PendingSponsorships` (r:1 w:1)
+	/// Storage: `FeeSponsorship::NextRequestId` (r:1 w:1)
+	/// Storage: `FeeSponsorship::SponsorshipRequests` (r:0 w:1)
+	fn sponsor_transaction() -> Weight {
+		Weight::from_parts(26_000_000, 4295)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	/// Storage: `FeeSponsorship::Verifiers` (r:1 w:1)
+	/// Storage: `FeeSponsorship::SponsorshipRequests` (r:1 w:1)
+	/// Storage: `FeeSponsorship::UnsettledVerifications` (r:1 w:1)
+	fn verify_ad_view() -> Weight {
+		Weight::from_parts(32_000_000, 4539)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	/// Storage: `FeeSponsorship::SponsorshipRequests` (r:1 w:1)
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `System::Account` (r:2 w:2)
+	/// Storage: `FeeSponsorship::UnsettledVerifications` (r:1 w:1)
+	/// Storage: `FeeSponsorship::Verifiers` (r:1 w:1)
+	/// Storage: `FeeSponsorship::TotalSponsored` (r:1 w:1)
+	/// Storage: `FeeSponsorship::PendingSponsorships` (r:0 w:1)
+	fn reimburse_fee() -> Weight {
+		Weight::from_parts(86_000_000, 6196)
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().writes(8_u64))
+	}
+	/// Storage: `FeeSponsorship::SponsorshipRequests` (r:1 w:1)
+	/// Storage: `FeeSponsorship::UnsettledVerifications` (r:1 w:1)
+	/// Storage: `FeeSponsorship::PendingSponsorships` (r:0 w:1)
+	fn cancel_sponsorship() -> Weight {
+		Weight::from_parts(25_000_000, 4539)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	/// Storage: `FeeSponsorship::Verifiers` (r:1 w:1)
+	fn add_verifier() -> Weight {
+		Weight::from_parts(12_000_000, 3525)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `FeeSponsorship::Verifiers` (r:1 w:1)
+	/// Storage: `FeeSponsorship::UnsettledVerifications` (r:0 w:1)
+	fn remove_verifier() -> Weight {
+		Weight::from_parts(15_000_000, 3525)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: `FeeSponsorship::Verifiers` (r:1 w:1)
+	/// Storage: `FeeSponsorship::UnsettledVerifications` (r:1 w:1)
+	/// Storage: `FeeSponsorship::SponsorshipRequests` (r:256 w:256)
+	/// The range of component `u` is `[0, 256]`.
+	fn revoke_verifier(u: u32, ) -> Weight {
+		Weight::from_parts(19_000_000, 4539)
+			.saturating_add(Weight::from_parts(6_200_000, 0).saturating_mul(u.into()))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(u.into())))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(u.into())))
+			.saturating_add(Weight::from_parts(0, 2607).saturating_mul(u.into()))
+	}
+	/// Storage: `FeeSponsorship::SponsorshipRequests` (r:1 w:1)
+	/// Storage: `AdTracking::LastCompletedView` (r:1 w:1)
+	fn verify_from_view() -> Weight {
+		Weight::from_parts(25_000_000, 3597)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+}
+
+// For backwards compatibility and tests.
+impl WeightInfo for () {
+	/// Storage: `Ads::Ads` (r:1 w:0)
+	/// Storage: `FeeSponsorship::PendingSponsorships` (r:1 w:1)
+	/// Storage: `FeeSponsorship::NextRequestId` (r:1 w:1)
+	/// Storage: `FeeSponsorship::SponsorshipRequests` (r:0 w:1)
+	fn sponsor_transaction() -> Weight {
+		Weight::from_parts(26_000_000, 4295)
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	/// Storage: `FeeSponsorship::Verifiers` (r:1 w:1)
+	/// Storage: `FeeSponsorship::SponsorshipRequests` (r:1 w:1)
+	/// Storage: `FeeSponsorship::UnsettledVerifications` (r:1 w:1)
+	fn verify_ad_view() -> Weight {
+		Weight::from_parts(32_000_000, 4539)
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	/// Storage: `FeeSponsorship::SponsorshipRequests` (r:1 w:1)
+	/// Storage: `Ads::Ads` (r:1 w:1)
+	/// Storage: `System::Account` (r:2 w:2)
+	/// Storage: `FeeSponsorship::UnsettledVerifications` (r:1 w:1)
+	/// Storage: `FeeSponsorship::Verifiers` (r:1 w:1)
+	/// Storage: `FeeSponsorship::TotalSponsored` (r:1 w:1)
+	/// Storage: `FeeSponsorship::PendingSponsorships` (r:0 w:1)
+	fn reimburse_fee() -> Weight {
+		Weight::from_parts(86_000_000, 6196)
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().writes(8_u64))
+	}
+	/// Storage: `FeeSponsorship::SponsorshipRequests` (r:1 w:1)
+	/// Storage: `FeeSponsorship::UnsettledVerifications` (r:1 w:1)
+	/// Storage: `FeeSponsorship::PendingSponsorships` (r:0 w:1)
+	fn cancel_sponsorship() -> Weight {
+		Weight::from_parts(25_000_000, 4539)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	/// Storage: `FeeSponsorship::Verifiers` (r:1 w:1)
+	fn add_verifier() -> Weight {
+		Weight::from_parts(12_000_000, 3525)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `FeeSponsorship::Verifiers` (r:1 w:1)
+	/// Storage: `FeeSponsorship::UnsettledVerifications` (r:0 w:1)
+	fn remove_verifier() -> Weight {
+		Weight::from_parts(15_000_000, 3525)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: `FeeSponsorship::Verifiers` (r:1 w:1)
+	/// Storage: `FeeSponsorship::UnsettledVerifications` (r:1 w:1)
+	/// Storage: `FeeSponsorship::SponsorshipRequests` (r:256 w:256)
+	/// The range of component `u` is `[0, 256]`.
+	fn revoke_verifier(u: u32, ) -> Weight {
+		Weight::from_parts(19_000_000, 4539)
+			.saturating_add(Weight::from_parts(6_200_000, 0).saturating_mul(u.into()))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(u.into())))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(u.into())))
+			.saturating_add(Weight::from_parts(0, 2607).saturating_mul(u.into()))
+	}
+	/// Storage: `FeeSponsorship::SponsorshipRequests` (r:1 w:1)
+	/// Storage: `AdTracking::LastCompletedView` (r:1 w:1)
+	fn verify_from_view() -> Weight {
+		Weight::from_parts(25_000_000, 3597)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+}
@@ -0,0 +1,169 @@
+//! # PolkaAds Runtime API
+//!
+//! Read-only queries over the PolkaAds pallets for RPC and light clients.
+//! Results use plain types so callers don't need to know the pallet configuration.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Codec, Decode, Encode};
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+
+/// Largest page returned by the paginated queries, regardless of the requested limit.
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// An ad as stored in pallet-ads.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct AdInfo<AccountId, Balance, BlockNumber> {
+	pub ad_id: u32,
+	pub advertiser: AccountId,
+	pub name: Vec<u8>,
+	pub description: Vec<u8>,
+	pub ipfs_cid: Vec<u8>,
+	/// Version of the current creative, starting at 1
+	pub creative_version: u32,
+	/// Length of the current creative in milliseconds, zero if unknown
+	pub creative_duration: u32,
+	/// Ad spot the ad is placed in while active
+	pub spot_id: u32,
+	/// Total budget escrowed at submission and by top-ups
+	pub funding: Balance,
+	/// Budget still available for sponsorships
+	pub remaining_budget: Balance,
+	pub views: u64,
+	pub active: bool,
+	/// Block at which the ad goes live
+	pub starts_at: BlockNumber,
+	/// Block at which the ad expires, if any
+	pub ends_at: Option<BlockNumber>,
+	/// Block from which the unspent budget of the deactivated ad can be withdrawn
+	pub budget_unlocks_at: Option<BlockNumber>,
+	pub moderation: ModerationInfo,
+}
+
+/// A previous creative of an ad.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct CreativeInfo<BlockNumber> {
+	pub version: u32,
+	pub ipfs_cid: Vec<u8>,
+	/// Block at which the creative was replaced
+	pub replaced_at: BlockNumber,
+}
+
+/// Moderation state of an ad.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum ModerationInfo {
+	/// Waiting for the moderator
+	Pending,
+	Approved,
+	Rejected { reason: Vec<u8> },
+	/// Rejection appealed, waiting for the moderator
+	Appealed { reason: Vec<u8> },
+}
+
+/// An ad spot and whether it can take a new ad.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct AdSpotInfo {
+	pub spot_id: u32,
+	pub available: bool,
+	/// Ad currently placed in the spot
+	pub ad_id: Option<u32>,
+}
+
+/// A registered advertiser.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct AdvertiserInfo<AccountId, Balance, BlockNumber> {
+	pub account_id: AccountId,
+	pub name: Vec<u8>,
+	pub registration_block: BlockNumber,
+	pub deposit: Balance,
+	pub active: bool,
+	pub total_funded: Balance,
+	pub total_ads: u32,
+	/// Ads that are active or scheduled to start
+	pub active_ads: u32,
+	/// Deposit must be topped up before the advertiser can submit ads
+	pub needs_top_up: bool,
+	/// Block until which the advertiser is suspended after a slash
+	pub suspended_until: Option<BlockNumber>,
+}
+
+/// Performance metrics of an ad as tracked by pallet-ad-tracking.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, Default)]
+pub struct AdMetricsInfo {
+	/// All views, billable or rejected by the rate limits
+	pub total_views: u64,
+	pub billable_views: u64,
+	pub rejected_views: u64,
+	/// All clicks, billable or rejected by the rate limits
+	pub total_clicks: u64,
+	pub billable_clicks: u64,
+	pub rejected_clicks: u64,
+	pub unique_viewers: u64,
+	/// Completed views attested by a registered client key
+	pub attested_views: u64,
+}
+
+/// Status of a sponsorship request in pallet-fee-sponsorship.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct SponsorshipInfo<AccountId, Balance, BlockNumber> {
+	pub request_id: u32,
+	pub user: AccountId,
+	pub ad_id: u32,
+	pub fee_amount: Balance,
+	pub verified: bool,
+	pub sponsored: bool,
+	pub verified_by: Option<AccountId>,
+	pub requested_at: BlockNumber,
+}
+
+sp_api::decl_runtime_apis! {
+	/// Queries over ads, advertisers, metrics and sponsorships.
+	///
+	/// Paginated queries return items with an id of at least `start`, in ascending id order,
+	/// and at most `limit` (capped at [`MAX_PAGE_SIZE`]) of them. The next page starts one
+	/// past the id of the last returned item.
+	pub trait PolkaAdsApi<AccountId, Balance, BlockNumber> where
+		AccountId: Codec,
+		Balance: Codec,
+		BlockNumber: Codec,
+	{
+		/// Active ads, paginated by ad id.
+		fn active_ads(start: u32, limit: u32) -> Vec<AdInfo<AccountId, Balance, BlockNumber>>;
+
+		/// A single ad, active, scheduled or ended.
+		fn ad(ad_id: u32) -> Option<AdInfo<AccountId, Balance, BlockNumber>>;
+
+		/// Previous creatives of an ad, oldest first, as far as they are kept on chain.
+		fn creative_history(ad_id: u32) -> Vec<CreativeInfo<BlockNumber>>;
+
+		/// Ad spots that can take a new ad, paginated by spot id.
+		fn available_spots(start: u32, limit: u32) -> Vec<AdSpotInfo>;
+
+		/// Ads of an advertiser, active or not, paginated by ad id.
+		fn advertiser_ads(
+			account: AccountId,
+			start: u32,
+			limit: u32,
+		) -> Vec<AdInfo<AccountId, Balance, BlockNumber>>;
+
+		/// Ads waiting for a moderation decision, paginated by ad id.
+		fn ads_under_review(start: u32, limit: u32) -> Vec<AdInfo<AccountId, Balance, BlockNumber>>;
+
+		/// Profile of a registered advertiser.
+		fn advertiser(account: AccountId) -> Option<AdvertiserInfo<AccountId, Balance, BlockNumber>>;
+
+		/// Views, clicks and unique viewers of an ad.
+		fn ad_metrics(ad_id: u32) -> AdMetricsInfo;
+
+		/// A sponsorship request by id.
+		fn sponsorship(request_id: u32) -> Option<SponsorshipInfo<AccountId, Balance, BlockNumber>>;
+
+		/// The sponsorship request `account` is currently waiting on, if any.
+		fn pending_sponsorship(account: AccountId) -> Option<SponsorshipInfo<AccountId, Balance, BlockNumber>>;
+
+		/// Smallest fee amount a sponsorship can request and largest fee it can pay.
+		fn sponsored_fee_bounds() -> (Balance, Balance);
+	}
+}
@@ -1,30 +1,396 @@
-//! Runtime API definitions for PolkaAds runtime
-//! This module provides the runtime API versions used by the node
+//! Runtime API implementations for PolkaAds runtime
+//! This module provides the runtime APIs and their versions used by the node
 
-use crate::Block;
+use frame_support::{
+	genesis_builder_helper::{build_state, get_preset},
+	traits::Get,
+	weights::Weight,
+};
+use polkaads_runtime_api::{
+	AdInfo, AdMetricsInfo, AdSpotInfo, AdvertiserInfo, CreativeInfo, ModerationInfo,
+	SponsorshipInfo, MAX_PAGE_SIZE,
+};
 use sp_api::impl_runtime_apis;
+use sp_consensus_aura::sr25519::AuthorityId as AuraId;
+use sp_consensus_grandpa::AuthorityId as GrandpaId;
+use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
+use sp_runtime::{
+	traits::{Block as BlockT, NumberFor},
+	transaction_validity::{TransactionSource, TransactionValidity},
+	ApplyExtrinsicResult,
+};
+use sp_std::prelude::*;
 use sp_version::RuntimeVersion;
 
-/// Runtime API versions
-pub const RUNTIME_API_VERSIONS: sp_version::RuntimeVersion = RuntimeVersion {
-	spec_name: sp_version::create_runtime_str!("polkaads"),
-	impl_name: sp_version::create_runtime_str!("polkaads"),
-	authoring_version: 1,
-	spec_version: 100,
-	impl_version: 1,
-	apis: sp_version::create_apis_vec!(
-		[sp_api::API_ID_CORE, sp_api::runtime_decl_for_Core::runtime_api_versions]
-		[sp_api::API_ID_BLOCK_BUILDER, sp_api::runtime_decl_for_BlockBuilder::runtime_api_versions]
-		[sp_api::API_ID_TAGGED_TRANSACTION_QUEUE, sp_api::runtime_decl_for_TaggedTransactionQueue::runtime_api_versions]
-		[sp_api::API_ID_OFFCHAIN_WORKER, sp_api::runtime_decl_for_OffchainWorkerApi::runtime_api_versions]
-		[sp_api::API_ID_SESSION_KEYS, sp_api::runtime_decl_for_SessionKeys::runtime_api_versions]
-		[frame_system_rpc_runtime_api::API_ID_SYSTEM, frame_system_rpc_runtime_api::runtime_decl_for_AccountNonceApi::runtime_api_versions]
-		[pallet_transaction_payment_rpc_runtime_api::API_ID_TRANSACTION_PAYMENT, pallet_transaction_payment_rpc_runtime_api::runtime_decl_for_TransactionPaymentApi::runtime_api_versions]
-	),
-	transaction_version: 1,
-	state_version: 1,
+use crate::{
+	AccountId, AdTracking, Ads, Aura, Balance, Block, BlockNumber, Executive, FeeSponsorship,
+	Grandpa, InherentDataExt, Nonce, Runtime, RuntimeGenesisConfig, SessionKeys, System,
+	TransactionPayment, VERSION,
 };
+#[cfg(feature = "runtime-benchmarks")]
+use crate::AllPalletsWithSystem;
+#[cfg(feature = "try-runtime")]
+use crate::RuntimeBlockWeights;
+
+impl_runtime_apis! {
+	impl sp_api::Core<Block> for Runtime {
+		fn version() -> RuntimeVersion {
+			VERSION
+		}
+
+		fn execute_block(block: Block) {
+			Executive::execute_block(block);
+		}
+
+		fn initialize_block(header: &<Block as BlockT>::Header) -> sp_runtime::ExtrinsicInclusionMode {
+			Executive::initialize_block(header)
+		}
+	}
+
+	impl sp_api::Metadata<Block> for Runtime {
+		fn metadata() -> OpaqueMetadata {
+			OpaqueMetadata::new(Runtime::metadata().into())
+		}
+
+		fn metadata_at_version(version: u32) -> Option<OpaqueMetadata> {
+			Runtime::metadata_at_version(version)
+		}
+
+		fn metadata_versions() -> sp_std::vec::Vec<u32> {
+			Runtime::metadata_versions()
+		}
+	}
+
+	impl sp_block_builder::BlockBuilder<Block> for Runtime {
+		fn apply_extrinsic(extrinsic: <Block as BlockT>::Extrinsic) -> ApplyExtrinsicResult {
+			Executive::apply_extrinsic(extrinsic)
+		}
+
+		fn finalize_block() -> <Block as BlockT>::Header {
+			Executive::finalize_block()
+		}
+
+		fn inherent_extrinsics(data: sp_inherents::InherentData) -> Vec<<Block as BlockT>::Extrinsic> {
+			data.create_extrinsics()
+		}
+
+		fn check_inherents(
+			block: Block,
+			data: sp_inherents::InherentData,
+		) -> sp_inherents::CheckInherentsResult {
+			data.check_extrinsics(&block)
+		}
+	}
+
+	impl sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block> for Runtime {
+		fn validate_transaction(
+			source: TransactionSource,
+			tx: <Block as BlockT>::Extrinsic,
+			block_hash: <Block as BlockT>::Hash,
+		) -> TransactionValidity {
+			Executive::validate_transaction(source, tx, block_hash)
+		}
+	}
+
+	impl sp_offchain::OffchainWorkerApi<Block> for Runtime {
+		fn offchain_worker(header: &<Block as BlockT>::Header) {
+			Executive::offchain_worker(header)
+		}
+	}
+
+	impl sp_consensus_aura::AuraApi<Block, AuraId> for Runtime {
+		fn slot_duration() -> sp_consensus_aura::SlotDuration {
+			sp_consensus_aura::SlotDuration::from_millis(Aura::slot_duration())
+		}
+
+		fn authorities() -> Vec<AuraId> {
+			pallet_aura::Authorities::<Runtime>::get().into_inner()
+		}
+	}
+
+	impl sp_session::SessionKeys<Block> for Runtime {
+		fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
+			SessionKeys::generate(seed)
+		}
+
+		fn decode_session_keys(
+			encoded: Vec<u8>,
+		) -> Option<Vec<(Vec<u8>, KeyTypeId)>> {
+			SessionKeys::decode_into_raw_public_keys(&encoded)
+		}
+	}
+
+	impl sp_consensus_grandpa::GrandpaApi<Block> for Runtime {
+		fn grandpa_authorities() -> sp_consensus_grandpa::AuthorityList {
+			Grandpa::grandpa_authorities()
+		}
+
+		fn current_set_id() -> sp_consensus_grandpa::SetId {
+			Grandpa::current_set_id()
+		}
+
+		fn submit_report_equivocation_unsigned_extrinsic(
+			_equivocation_proof: sp_consensus_grandpa::EquivocationProof<
+				<Block as BlockT>::Hash,
+				NumberFor<Block>,
+			>,
+			_key_owner_proof: sp_consensus_grandpa::OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			None
+		}
+
+		fn generate_key_ownership_proof(
+			_set_id: sp_consensus_grandpa::SetId,
+			_authority_id: GrandpaId,
+		) -> Option<sp_consensus_grandpa::OpaqueKeyOwnershipProof> {
+			None
+		}
+	}
+
+	impl frame_system_rpc_runtime_api::AccountNonceApi<Block, AccountId, Nonce> for Runtime {
+		fn account_nonce(account: AccountId) -> Nonce {
+			System::account_nonce(account)
+		}
+	}
+
+	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
+		fn query_info(
+			uxt: <Block as BlockT>::Extrinsic,
+			len: u32,
+		) -> pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo<Balance> {
+			TransactionPayment::query_info(uxt, len)
+		}
+		fn query_fee_details(
+			uxt: <Block as BlockT>::Extrinsic,
+			len: u32,
+		) -> pallet_transaction_payment::FeeDetails<Balance> {
+			TransactionPayment::query_fee_details(uxt, len)
+		}
+		fn query_weight_to_fee(weight: Weight) -> Balance {
+			TransactionPayment::weight_to_fee(weight)
+		}
+		fn query_length_to_fee(length: u32) -> Balance {
+			TransactionPayment::length_to_fee(length)
+		}
+	}
+
+	impl polkaads_runtime_api::PolkaAdsApi<Block, AccountId, Balance, BlockNumber> for Runtime {
+		fn active_ads(start: u32, limit: u32) -> Vec<AdInfo<AccountId, Balance, BlockNumber>> {
+			Ads::active_ads(start, limit.min(MAX_PAGE_SIZE))
+				.into_iter()
+				.map(|(ad_id, ad)| ad_info(ad_id, ad))
+				.collect()
+		}
+
+		fn ad(ad_id: u32) -> Option<AdInfo<AccountId, Balance, BlockNumber>> {
+			pallet_ads::Ads::<Runtime>::get(ad_id).map(|ad| ad_info(ad_id, ad))
+		}
+
+		fn creative_history(ad_id: u32) -> Vec<CreativeInfo<BlockNumber>> {
+			pallet_ads::CreativeHistory::<Runtime>::get(ad_id)
+				.into_iter()
+				.map(|creative| CreativeInfo {
+					version: creative.version,
+					ipfs_cid: creative.ipfs_cid.into_inner(),
+					replaced_at: creative.replaced_at,
+				})
+				.collect()
+		}
+
+		fn available_spots(start: u32, limit: u32) -> Vec<AdSpotInfo> {
+			Ads::available_spots(start, limit.min(MAX_PAGE_SIZE))
+				.into_iter()
+				.map(|spot| AdSpotInfo {
+					spot_id: spot.spot_id,
+					available: spot.available,
+					ad_id: spot.ad_id,
+				})
+				.collect()
+		}
+
+		fn advertiser_ads(
+			account: AccountId,
+			start: u32,
+			limit: u32,
+		) -> Vec<AdInfo<AccountId, Balance, BlockNumber>> {
+			Ads::advertiser_ads(&account, start, limit.min(MAX_PAGE_SIZE))
+				.into_iter()
+				.map(|(ad_id, ad)| ad_info(ad_id, ad))
+				.collect()
+		}
+
+		fn ads_under_review(start: u32, limit: u32) -> Vec<AdInfo<AccountId, Balance, BlockNumber>> {
+			Ads::ads_under_review(start, limit.min(MAX_PAGE_SIZE))
+				.into_iter()
+				.map(|(ad_id, ad)| ad_info(ad_id, ad))
+				.collect()
+		}
+
+		fn advertiser(account: AccountId) -> Option<AdvertiserInfo<AccountId, Balance, BlockNumber>> {
+			let suspended_until = pallet_ads::Suspensions::<Runtime>::get(&account);
+			pallet_ads::AdvertiserProfiles::<Runtime>::get(account).map(|profile| AdvertiserInfo {
+				account_id: profile.account_id,
+				name: profile.name.into_inner(),
+				registration_block: profile.registration_block,
+				deposit: profile.deposit,
+				active: profile.active,
+				total_funded: profile.total_funded,
+				total_ads: profile.total_ads,
+				active_ads: profile.active_ads,
+				needs_top_up: profile.needs_top_up,
+				suspended_until,
+			})
+		}
+
+		fn ad_metrics(ad_id: u32) -> AdMetricsInfo {
+			let metrics = AdTracking::ad_metrics(ad_id);
+			AdMetricsInfo {
+				total_views: metrics.total_views,
+				billable_views: metrics.billable_views,
+				rejected_views: metrics.rejected_views,
+				total_clicks: metrics.total_clicks,
+				billable_clicks: metrics.billable_clicks,
+				rejected_clicks: metrics.rejected_clicks,
+				unique_viewers: metrics.unique_viewers,
+				attested_views: pallet_ad_tracking::AttestedViews::<Runtime>::get(ad_id),
+			}
+		}
+
+		fn sponsorship(request_id: u32) -> Option<SponsorshipInfo<AccountId, Balance, BlockNumber>> {
+			FeeSponsorship::sponsorship_requests(request_id)
+				.map(|request| sponsorship_info(request_id, request))
+		}
+
+		fn pending_sponsorship(account: AccountId) -> Option<SponsorshipInfo<AccountId, Balance, BlockNumber>> {
+			let request_id = FeeSponsorship::pending_sponsorships(account)?;
+			FeeSponsorship::sponsorship_requests(request_id)
+				.map(|request| sponsorship_info(request_id, request))
+		}
+
+		fn sponsored_fee_bounds() -> (Balance, Balance) {
+			(
+				<Runtime as pallet_fee_sponsorship::Config>::MinSponsorshipAmount::get(),
+				<Runtime as pallet_fee_sponsorship::Config>::MaxSponsoredFee::get(),
+			)
+		}
+	}
+
+	impl sp_genesis_builder::GenesisBuilder<Block> for Runtime {
+		fn build_state(config: Vec<u8>) -> sp_genesis_builder::Result {
+			build_state::<RuntimeGenesisConfig>(config)
+		}
+
+		fn get_preset(id: &Option<sp_genesis_builder::PresetId>) -> Option<Vec<u8>> {
+			get_preset::<RuntimeGenesisConfig>(id, crate::genesis_config_presets::get_preset)
+		}
+
+		fn preset_names() -> Vec<sp_genesis_builder::PresetId> {
+			crate::genesis_config_presets::preset_names()
+		}
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	impl frame_benchmarking::Benchmark<Block> for Runtime {
+		fn benchmark_metadata(extra: bool) -> (
+			Vec<frame_benchmarking::BenchmarkList>,
+			Vec<frame_support::traits::StorageInfo>,
+		) {
+			use frame_benchmarking::{baseline, Benchmarking, BenchmarkList};
+			use frame_support::traits::StorageInfoTrait;
+			use frame_system_benchmarking::Pallet as SystemBench;
+			use baseline::Pallet as BaselineBench;
+
+			let mut list = Vec::<BenchmarkList>::new();
+			list_benchmarks!(list, extra);
+
+			let storage_info = AllPalletsWithSystem::storage_info();
+
+			(list, storage_info)
+		}
+
+		fn dispatch_benchmark(
+			config: frame_benchmarking::BenchmarkConfig
+		) -> Result<Vec<frame_benchmarking::BenchmarkBatch>, sp_runtime::RuntimeString> {
+			use frame_benchmarking::{baseline, Benchmarking, BenchmarkBatch};
+			use sp_storage::TrackedStorageKey;
+			use frame_system_benchmarking::Pallet as SystemBench;
+			use baseline::Pallet as BaselineBench;
+
+			impl frame_system_benchmarking::Config for Runtime {}
+			impl baseline::Config for Runtime {}
+
+			use frame_support::traits::WhitelistedStorageKeys;
+			let whitelist: Vec<TrackedStorageKey> = AllPalletsWithSystem::whitelisted_storage_keys();
+
+			let mut batches = Vec::<BenchmarkBatch>::new();
+			let params = (&config, &whitelist);
+			add_benchmarks!(params, batches);
+
+			Ok(batches)
+		}
+	}
+
+	#[cfg(feature = "try-runtime")]
+	impl frame_try_runtime::TryRuntime<Block> for Runtime {
+		fn on_runtime_upgrade(checks: frame_try_runtime::UpgradeCheckSelect) -> (Weight, Weight) {
+			let weight = Executive::try_runtime_upgrade(checks).unwrap();
+			(weight, RuntimeBlockWeights::get().max_block)
+		}
+
+		fn execute_block(
+			block: Block,
+			state_root_check: bool,
+			signature_check: bool,
+			select: frame_try_runtime::TryStateSelect
+		) -> Weight {
+			Executive::try_execute_block(block, state_root_check, signature_check, select).expect("execute-block failed")
+		}
+	}
+}
 
-/// Runtime API type alias for node usage
-pub type RuntimeApi = sp_api::Impl<Block, crate::Runtime>;
+fn ad_info(
+	ad_id: u32,
+	ad: pallet_ads::AdMetadata<Runtime>,
+) -> AdInfo<AccountId, Balance, BlockNumber> {
+	AdInfo {
+		ad_id,
+		advertiser: ad.advertiser,
+		name: ad.name.into_inner(),
+		description: ad.description.into_inner(),
+		ipfs_cid: ad.ipfs_cid.into_inner(),
+		creative_version: ad.creative_version,
+		creative_duration: pallet_ads::CreativeDurations::<Runtime>::get(ad_id),
+		spot_id: ad.spot_id,
+		funding: ad.funding,
+		remaining_budget: ad.remaining_budget,
+		views: ad.views,
+		active: ad.active,
+		starts_at: ad.starts_at,
+		ends_at: ad.ends_at,
+		budget_unlocks_at: pallet_ads::BudgetUnlocksAt::<Runtime>::get(ad_id),
+		moderation: match pallet_ads::Moderation::<Runtime>::get(ad_id) {
+			pallet_ads::ModerationStatus::Pending => ModerationInfo::Pending,
+			pallet_ads::ModerationStatus::Approved => ModerationInfo::Approved,
+			pallet_ads::ModerationStatus::Rejected { reason } =>
+				ModerationInfo::Rejected { reason: reason.into_inner() },
+			pallet_ads::ModerationStatus::Appealed { reason, .. } =>
+				ModerationInfo::Appealed { reason: reason.into_inner() },
+		},
+	}
+}
 
+fn sponsorship_info(
+	request_id: u32,
+	request: pallet_fee_sponsorship::SponsorshipRequest<Runtime>,
+) -> SponsorshipInfo<AccountId, Balance, BlockNumber> {
+	SponsorshipInfo {
+		request_id,
+		user: request.user,
+		ad_id: request.ad_id,
+		fee_amount: request.fee_amount,
+		verified: request.verified,
+		sponsored: request.sponsored,
+		verified_by: request.verified_by,
+		requested_at: request.requested_at,
+	}
+}
@@ -0,0 +1,168 @@
+//! Genesis config presets for the PolkaAds runtime.
+//!
+//! Both presets boot with ad spots, a funded advertiser with live ads and a
+//! registered verifier, so the ad marketplace is usable from the first block.
+
+use crate::{
+	AccountId, AdsConfig, AuraConfig, Balance, BalancesConfig, FeeSponsorshipConfig,
+	GrandpaConfig, RuntimeGenesisConfig, SudoConfig,
+};
+use serde_json::Value;
+use sp_consensus_aura::sr25519::AuthorityId as AuraId;
+use sp_consensus_grandpa::AuthorityId as GrandpaId;
+use sp_genesis_builder::{self, PresetId};
+use sp_keyring::{Ed25519Keyring, Sr25519Keyring};
+use sp_std::{prelude::*, vec};
+
+/// One token, with 9 decimals.
+const UNIT: Balance = 1_000_000_000;
+
+/// Balance given to every endowed account.
+const ENDOWMENT: Balance = 1 << 60;
+
+/// Deposit reserved for each genesis advertiser.
+const ADVERTISER_DEPOSIT: Balance = 10 * UNIT;
+
+/// Budget escrowed for each genesis ad.
+const AD_FUNDING: Balance = 1_000 * UNIT;
+
+/// Number of ad spots created at genesis.
+const AD_SPOTS: u32 = 8;
+
+/// Video CID used by the seeded ads.
+const DEMO_AD_CID: &[u8] = b"bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+
+/// Aura and GRANDPA keys of a well-known development authority.
+fn authority_keys(aura: Sr25519Keyring, grandpa: Ed25519Keyring) -> (AuraId, GrandpaId) {
+	(aura.public().into(), grandpa.public().into())
+}
+
+/// A seeded ad in the shape expected by `pallet_ads::GenesisConfig::ads`.
+fn demo_ad(
+	advertiser: &AccountId,
+	spot_id: u32,
+	name: &[u8],
+	description: &[u8],
+) -> (AccountId, u32, Vec<u8>, Vec<u8>, Vec<u8>, Balance) {
+	(
+		advertiser.clone(),
+		spot_id,
+		name.to_vec(),
+		description.to_vec(),
+		DEMO_AD_CID.to_vec(),
+		AD_FUNDING,
+	)
+}
+
+/// Genesis config shared by the presets.
+fn testnet_genesis(
+	initial_authorities: Vec<(AuraId, GrandpaId)>,
+	root: AccountId,
+	endowed_accounts: Vec<AccountId>,
+	advertisers: Vec<(AccountId, Vec<u8>)>,
+	verifiers: Vec<AccountId>,
+) -> Value {
+	let ads = advertisers
+		.iter()
+		.enumerate()
+		.flat_map(|(index, (advertiser, _))| {
+			let spot_id = index as u32 * 2;
+			[
+				demo_ad(
+					advertiser,
+					spot_id,
+					b"Watch to go gasless",
+					b"Watch this ad to have your next transaction fee sponsored.",
+				),
+				demo_ad(
+					advertiser,
+					spot_id + 1,
+					b"PolkaAds for advertisers",
+					b"Reach Polkadot users by sponsoring their transaction fees.",
+				),
+			]
+		})
+		.collect::<Vec<_>>();
+
+	let config = RuntimeGenesisConfig {
+		balances: BalancesConfig {
+			balances: endowed_accounts.iter().cloned().map(|k| (k, ENDOWMENT)).collect::<Vec<_>>(),
+		},
+		aura: AuraConfig {
+			authorities: initial_authorities.iter().map(|x| x.0.clone()).collect::<Vec<_>>(),
+		},
+		grandpa: GrandpaConfig {
+			authorities: initial_authorities.iter().map(|x| (x.1.clone(), 1)).collect::<Vec<_>>(),
+			..Default::default()
+		},
+		sudo: SudoConfig { key: Some(root) },
+		ads: AdsConfig {
+			ad_spots: AD_SPOTS,
+			advertisers: advertisers
+				.into_iter()
+				.map(|(account, name)| (account, name, ADVERTISER_DEPOSIT))
+				.collect::<Vec<_>>(),
+			ads,
+		},
+		fee_sponsorship: FeeSponsorshipConfig { verifiers },
+		..Default::default()
+	};
+
+	serde_json::to_value(config).expect("Could not build genesis config.")
+}
+
+/// Single-authority development chain with Alice as sudo and verifier and Bob as advertiser.
+pub fn development_config_genesis() -> Value {
+	testnet_genesis(
+		vec![authority_keys(Sr25519Keyring::Alice, Ed25519Keyring::Alice)],
+		Sr25519Keyring::Alice.to_account_id(),
+		vec![
+			Sr25519Keyring::Alice.to_account_id(),
+			Sr25519Keyring::Bob.to_account_id(),
+			Sr25519Keyring::AliceStash.to_account_id(),
+			Sr25519Keyring::BobStash.to_account_id(),
+		],
+		vec![(Sr25519Keyring::Bob.to_account_id(), b"PolkaAds Demo".to_vec())],
+		vec![Sr25519Keyring::Alice.to_account_id()],
+	)
+}
+
+/// Local testnet with Alice and Bob as authorities and verifiers and Charlie and Dave as
+/// advertisers.
+pub fn local_config_genesis() -> Value {
+	testnet_genesis(
+		vec![
+			authority_keys(Sr25519Keyring::Alice, Ed25519Keyring::Alice),
+			authority_keys(Sr25519Keyring::Bob, Ed25519Keyring::Bob),
+		],
+		Sr25519Keyring::Alice.to_account_id(),
+		Sr25519Keyring::iter().map(|keyring| keyring.to_account_id()).collect::<Vec<_>>(),
+		vec![
+			(Sr25519Keyring::Charlie.to_account_id(), b"Charlie Media".to_vec()),
+			(Sr25519Keyring::Dave.to_account_id(), b"Dave Games".to_vec()),
+		],
+		vec![Sr25519Keyring::Alice.to_account_id(), Sr25519Keyring::Bob.to_account_id()],
+	)
+}
+
+/// Provides the JSON representation of predefined genesis config for given `id`.
+pub fn get_preset(id: &PresetId) -> Option<Vec<u8>> {
+	let patch = match id.as_ref() {
+		sp_genesis_builder::DEV_RUNTIME_PRESET => development_config_genesis(),
+		sp_genesis_builder::LOCAL_TESTNET_RUNTIME_PRESET => local_config_genesis(),
+		_ => return None,
+	};
+	Some(
+		serde_json::to_string(&patch)
+			.expect("serialization to json is expected to work. qed.")
+			.into_bytes(),
+	)
+}
+
+/// List of supported presets.
+pub fn preset_names() -> Vec<PresetId> {
+	vec![
+		PresetId::from(sp_genesis_builder::DEV_RUNTIME_PRESET),
+		PresetId::from(sp_genesis_builder::LOCAL_TESTNET_RUNTIME_PRESET),
+	]
+}
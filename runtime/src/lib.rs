@@ -6,16 +6,15 @@
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
 pub mod apis;
+pub mod genesis_config_presets;
+#[cfg(test)]
+mod sponsorship_tests;
 
-use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_consensus_grandpa::AuthorityId as GrandpaId;
-use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
-	traits::{BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, One, Verify},
-	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, MultiSignature,
+	traits::{BlakeTwo256, IdentifyAccount, One, Verify},
+	MultiSignature,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -25,11 +24,10 @@ use sp_version::RuntimeVersion;
 use frame_support::{
 	construct_runtime,
 	derive_impl,
-	genesis_builder_helper::{build_state, get_preset},
 	parameter_types,
 	traits::{ConstBool, ConstU128, ConstU16, ConstU32, ConstU64, ConstU8},
 	weights::{
-		constants::WEIGHT_REF_TIME_PER_SECOND, IdentityFee, Weight,
+		constants::{RocksDbWeight, WEIGHT_REF_TIME_PER_SECOND}, IdentityFee, Weight, WeightToFee,
 	},
 };
 use frame_system::limits::{BlockLength, BlockWeights};
@@ -79,13 +77,23 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_fee_sponsorship::ChargeSponsoredTransactionPayment<Runtime>,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
 
+/// Storage migrations applied on runtime upgrade.
+pub type Migrations = (
+	pallet_ads::migrations::v1::MigrateV0ToV1<Runtime>,
+	pallet_ads::migrations::v2::MigrateV1ToV2<Runtime>,
+	pallet_ads::migrations::v3::MigrateV2ToV3<Runtime>,
+	pallet_ad_tracking::migrations::v1::MigrateV0ToV1<Runtime>,
+	pallet_ad_tracking::migrations::v2::MigrateV1ToV2<Runtime>,
+	pallet_ad_tracking::migrations::v3::MigrateV2ToV3<Runtime>,
+);
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -93,6 +101,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 /// Opaque types. These are used by the CLI to instantiate machinery that don't need to know
@@ -131,7 +140,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_version: 100,
 	impl_version: 1,
 	apis: apis::RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 3,
 	state_version: 1,
 };
 
@@ -182,7 +191,7 @@ impl frame_system::Config for Runtime {
 	type BaseCallFilter = frame_support::traits::Everything;
 	type BlockWeights = RuntimeBlockWeights;
 	type BlockLength = RuntimeBlockLength;
-	type DbWeight = ();
+	type DbWeight = RocksDbWeight;
 	type RuntimeOrigin = RuntimeOrigin;
 	type RuntimeCall = RuntimeCall;
 	type Nonce = Nonce;
@@ -274,16 +283,133 @@ impl pallet_ads::Config for Runtime {
 	type MaxCidLength = ConstU32<100>;
 	type MaxAdvertiserNameLength = ConstU32<50>;
 	type MinAdvertiserDeposit = ConstU128<100_000_000>; // 0.1 token (with 9 decimals)
+	// Winning spot auction bids are burned
+	type AuctionRevenue = ();
+	type MaxAuctionBids = ConstU32<32>;
+	type MaxAuctionsPerBlock = ConstU32<16>;
+	type MaxScheduledAdsPerBlock = ConstU32<32>;
+	type ModeratorOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxRejectionReasonLength = ConstU32<256>;
+	type AppealBond = ConstU128<10_000_000>; // 0.01 token
+	type GovernanceOrigin = frame_system::EnsureRoot<AccountId>;
+	// Slashed advertiser deposits are burned
+	type OnSlash = ();
+	type MaxSuspensionsPerBlock = ConstU32<16>;
+	type MaxCreativeHistory = ConstU32<10>;
+	type WithdrawalCooldown = ConstU32<{ 10 * MINUTES }>;
+	type WeightInfo = pallet_ads::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	/// Fee of an empty extrinsic, the least any transaction pays
+	pub ExtrinsicBaseFee: Balance = IdentityFee::<Balance>::weight_to_fee(
+		&RuntimeBlockWeights::get()
+			.get(frame_support::dispatch::DispatchClass::Normal)
+			.base_extrinsic,
+	);
+	/// Sponsored fees cover the base fee and up to nine times as much call weight and length
+	pub MaxSponsoredFee: Balance = ExtrinsicBaseFee::get().saturating_mul(10);
 }
 
 impl pallet_fee_sponsorship::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
-	type MinSponsorshipAmount = ConstU128<1_000_000>;
-	type AdsPallet = Ads;
+	type MinSponsorshipAmount = ExtrinsicBaseFee;
+	type MaxSponsoredFee = MaxSponsoredFee;
+	type AdBudget = Ads;
+	type VerifierOrigin = frame_system::EnsureSigned<AccountId>;
+	type GovernanceOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxUnsettledVerifications = ConstU32<256>;
+	type CompletedViews = AdTracking;
+	type ViewVerificationWindow = ConstU32<{ 10 * MINUTES }>;
+	type WeightInfo = pallet_fee_sponsorship::weights::SubstrateWeight<Runtime>;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = FeeSponsorshipBenchmarkHelper;
+}
+
+/// Sets up the ads and completed views the fee sponsorship benchmarks depend on.
+#[cfg(feature = "runtime-benchmarks")]
+pub struct FeeSponsorshipBenchmarkHelper;
+
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_fee_sponsorship::BenchmarkHelper<AccountId, BlockNumber>
+	for FeeSponsorshipBenchmarkHelper
+{
+	fn create_ad(budget: u128) -> u32 {
+		use frame_support::traits::{Currency, Get};
+
+		let spot_id = pallet_ads::NextSpotId::<Runtime>::get();
+		let ad_id = pallet_ads::NextAdId::<Runtime>::get();
+		let advertiser: AccountId = frame_benchmarking::account("advertiser", ad_id, 0);
+		let deposit = <Runtime as pallet_ads::Config>::MinAdvertiserDeposit::get();
+		Balances::make_free_balance_be(&advertiser, deposit.saturating_add(budget).saturating_mul(2));
+
+		let origin = RuntimeOrigin::signed(advertiser);
+		Ads::create_ad_spot(RuntimeOrigin::root()).expect("root can create ad spots; qed");
+		Ads::register_advertiser(origin.clone(), b"Benchmark".to_vec(), deposit)
+			.expect("advertiser is funded; qed");
+		Ads::submit_ad(
+			origin,
+			spot_id,
+			b"Benchmark".to_vec(),
+			b"Benchmark ad".to_vec(),
+			b"QmBenchmark".to_vec(),
+			30_000,
+			budget,
+			None,
+			None,
+		)
+		.expect("advertiser is registered and funded; qed");
+		Ads::approve_ad(RuntimeOrigin::root(), ad_id).expect("ad was just submitted; qed");
+		ad_id
+	}
+
+	fn complete_view(who: &AccountId, ad_id: u32, at: BlockNumber) {
+		pallet_ad_tracking::LastCompletedView::<Runtime>::insert(who, ad_id, at);
+	}
+}
+
+parameter_types! {
+	/// Views must last for most of the creative to be completed
+	pub const MinWatchRatio: Perbill = Perbill::from_percent(80);
 }
 
 impl pallet_ad_tracking::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
+	type Ads = Ads;
+	type MinWatchDuration = ConstU64<5_000>; // 5 seconds
+	type MinWatchRatio = MinWatchRatio;
+	type ViewCooldown = ConstU32<HOURS>;
+	type ClickCooldown = ConstU32<{ 10 * MINUTES }>;
+	type MaxInteractionsPerBlock = ConstU32<5>;
+	type MaxViewsPerAccountPerDay = ConstU32<50>;
+	type BlocksPerDay = ConstU32<DAYS>;
+	type ClientKeyOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxAttestationLifetime = ConstU32<{ 10 * MINUTES }>;
+	type MaxAttestationsPerBlock = ConstU32<256>;
+	type Currency = Balances;
+	type AggregatorOrigin = frame_system::EnsureSigned<AccountId>;
+	type AggregatorBond = ConstU128<1_000_000_000>; // 1 token
+	type BatchPeriod = ConstU32<HOURS>;
+	type ChallengePeriod = ConstU32<DAYS>;
+	type MaxAdsPerBatch = ConstU32<100>;
+	type MaxProofLength = ConstU32<32>;
+	type OnSlash = ();
+	type WeightInfo = pallet_ad_tracking::weights::SubstrateWeight<Runtime>;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = AdTrackingBenchmarkHelper;
+}
+
+/// Creates the ads the ad tracking benchmarks record views and clicks of.
+#[cfg(feature = "runtime-benchmarks")]
+pub struct AdTrackingBenchmarkHelper;
+
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_ad_tracking::BenchmarkHelper for AdTrackingBenchmarkHelper {
+	fn create_ad() -> u32 {
+		<FeeSponsorshipBenchmarkHelper as pallet_fee_sponsorship::BenchmarkHelper<_, _>>::create_ad(
+			1_000_000_000,
+		)
+	}
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -323,218 +449,3 @@ mod benches {
 		[pallet_ad_tracking, AdTracking]
 	);
 }
-
-impl_runtime_apis! {
-	impl sp_api::Core<Block> for Runtime {
-		fn version() -> RuntimeVersion {
-			VERSION
-		}
-
-		fn execute_block(block: Block) {
-			Executive::execute_block(block);
-		}
-
-		fn initialize_block(header: &<Block as BlockT>::Header) -> sp_runtime::ExtrinsicInclusionMode {
-			Executive::initialize_block(header)
-		}
-	}
-
-	impl sp_api::Metadata<Block> for Runtime {
-		fn metadata() -> OpaqueMetadata {
-			OpaqueMetadata::new(Runtime::metadata().into())
-		}
-
-		fn metadata_at_version(version: u32) -> Option<OpaqueMetadata> {
-			Runtime::metadata_at_version(version)
-		}
-
-		fn metadata_versions() -> sp_std::vec::Vec<u32> {
-			Runtime::metadata_versions()
-		}
-	}
-
-	impl sp_block_builder::BlockBuilder<Block> for Runtime {
-		fn apply_extrinsic(extrinsic: <Block as BlockT>::Extrinsic) -> ApplyExtrinsicResult {
-			Executive::apply_extrinsic(extrinsic)
-		}
-
-		fn finalize_block() -> <Block as BlockT>::Header {
-			Executive::finalize_block()
-		}
-
-		fn inherent_extrinsics(data: sp_inherents::InherentData) -> Vec<<Block as BlockT>::Extrinsic> {
-			data.create_extrinsics()
-		}
-
-		fn check_inherents(
-			block: Block,
-			data: sp_inherents::InherentData,
-		) -> sp_inherents::CheckInherentsResult {
-			data.check_extrinsics(&block)
-		}
-	}
-
-	impl sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block> for Runtime {
-		fn validate_transaction(
-			source: TransactionSource,
-			tx: <Block as BlockT>::Extrinsic,
-			block_hash: <Block as BlockT>::Hash,
-		) -> TransactionValidity {
-			Executive::validate_transaction(source, tx, block_hash)
-		}
-	}
-
-	impl sp_offchain::OffchainWorkerApi<Block> for Runtime {
-		fn offchain_worker(header: &<Block as BlockT>::Header) {
-			Executive::offchain_worker(header)
-		}
-	}
-
-	impl sp_consensus_aura::AuraApi<Block, AuraId> for Runtime {
-		fn slot_duration() -> sp_consensus_aura::SlotDuration {
-			sp_consensus_aura::SlotDuration::from_millis(Aura::slot_duration())
-		}
-
-		fn authorities() -> Vec<AuraId> {
-			pallet_aura::Authorities::<Runtime>::get().into_inner()
-		}
-	}
-
-	impl sp_session::SessionKeys<Block> for Runtime {
-		fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
-			opaque::SessionKeys::generate(seed)
-		}
-
-		fn decode_session_keys(
-			encoded: Vec<u8>,
-		) -> Option<Vec<(Vec<u8>, KeyTypeId)>> {
-			opaque::SessionKeys::decode_into_raw_public_keys(&encoded)
-		}
-	}
-
-	impl sp_consensus_grandpa::GrandpaApi<Block> for Runtime {
-		fn grandpa_authorities() -> sp_consensus_grandpa::AuthorityList {
-			Grandpa::grandpa_authorities()
-		}
-
-		fn current_set_id() -> sp_consensus_grandpa::SetId {
-			Grandpa::current_set_id()
-		}
-
-		fn submit_report_equivocation_unsigned_extrinsic(
-			_equivocation_proof: sp_consensus_grandpa::EquivocationProof<
-				<Block as BlockT>::Hash,
-				NumberFor<Block>,
-			>,
-			_key_owner_proof: sp_consensus_grandpa::OpaqueKeyOwnershipProof,
-		) -> Option<()> {
-			None
-		}
-
-		fn generate_key_ownership_proof(
-			_set_id: sp_consensus_grandpa::SetId,
-			_authority_id: GrandpaId,
-		) -> Option<sp_consensus_grandpa::OpaqueKeyOwnershipProof> {
-			None
-		}
-	}
-
-	impl frame_system_rpc_runtime_api::AccountNonceApi<Block, AccountId, Nonce> for Runtime {
-		fn account_nonce(account: AccountId) -> Nonce {
-			System::account_nonce(account)
-		}
-	}
-
-	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
-		fn query_info(
-			uxt: <Block as BlockT>::Extrinsic,
-			len: u32,
-		) -> pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo<Balance> {
-			TransactionPayment::query_info(uxt, len)
-		}
-		fn query_fee_details(
-			uxt: <Block as BlockT>::Extrinsic,
-			len: u32,
-		) -> pallet_transaction_payment::FeeDetails<Balance> {
-			TransactionPayment::query_fee_details(uxt, len)
-		}
-		fn query_weight_to_fee(weight: Weight) -> Balance {
-			TransactionPayment::weight_to_fee(weight)
-		}
-		fn query_length_to_fee(length: u32) -> Balance {
-			TransactionPayment::length_to_fee(length)
-		}
-	}
-
-	impl sp_genesis_builder::GenesisBuilder<Block> for Runtime {
-		fn build_state(config: Vec<u8>) -> sp_genesis_builder::Result {
-			build_state::<RuntimeGenesisConfig>(config)
-		}
-
-		fn get_preset(id: &Option<sp_genesis_builder::PresetId>) -> Option<Vec<u8>> {
-			get_preset::<RuntimeGenesisConfig>(id, |_| None)
-		}
-
-		fn preset_names() -> Vec<sp_genesis_builder::PresetId> {
-			vec![]
-		}
-	}
-
-	#[cfg(feature = "runtime-benchmarks")]
-	impl frame_benchmarking::Benchmark<Block> for Runtime {
-		fn benchmark_metadata(extra: bool) -> (
-			Vec<frame_benchmarking::BenchmarkList>,
-			Vec<frame_support::traits::StorageInfo>,
-		) {
-			use frame_benchmarking::{baseline, Benchmarking, BenchmarkList};
-			use frame_support::traits::StorageInfoTrait;
-			use frame_system_benchmarking::Pallet as SystemBench;
-			use baseline::Pallet as BaselineBench;
-
-			let mut list = Vec::<BenchmarkList>::new();
-			list_benchmarks!(list, extra);
-
-			let storage_info = AllPalletsWithSystem::storage_info();
-
-			(list, storage_info)
-		}
-
-		fn dispatch_benchmark(
-			config: frame_benchmarking::BenchmarkConfig
-		) -> Result<Vec<frame_benchmarking::BenchmarkBatch>, sp_runtime::RuntimeString> {
-			use frame_benchmarking::{baseline, Benchmarking, BenchmarkBatch};
-			use sp_storage::TrackedStorageKey;
-			use frame_system_benchmarking::Pallet as SystemBench;
-			use baseline::Pallet as BaselineBench;
-
-			impl frame_system_benchmarking::Config for Runtime {}
-			impl baseline::Config for Runtime {}
-
-			use frame_support::traits::WhitelistedStorageKeys;
-			let whitelist: Vec<TrackedStorageKey> = AllPalletsWithSystem::whitelisted_storage_keys();
-
-			let mut batches = Vec::<BenchmarkBatch>::new();
-			let params = (&config, &whitelist);
-			add_benchmarks!(params, batches);
-
-			Ok(batches)
-		}
-	}
-
-	#[cfg(feature = "try-runtime")]
-	impl frame_try_runtime::TryRuntime<Block> for Runtime {
-		fn on_runtime_upgrade(checks: frame_try_runtime::UpgradeCheckSelect) -> (Weight, Weight) {
-			let weight = Executive::try_runtime_upgrade(checks).unwrap();
-			(weight, RuntimeBlockWeights::get().max_block)
-		}
-
-		fn execute_block(
-			block: Block,
-			state_root_check: bool,
-			signature_check: bool,
-			select: frame_try_runtime::TryStateSelect
-		) -> Weight {
-			Executive::try_execute_block(block, state_root_check, signature_check, select).expect("execute-block failed")
-		}
-	}
-}
@@ -0,0 +1,123 @@
+//! Sponsored transactions dispatched through the runtime's `SignedExtra`.
+
+use crate::*;
+use codec::Encode;
+use frame_support::{assert_ok, dispatch::GetDispatchInfo};
+use sp_keyring::Sr25519Keyring;
+use sp_runtime::{generic::Era, traits::Header as _, MultiAddress};
+
+/// One token, with 9 decimals.
+const UNIT: Balance = 1_000_000_000;
+
+/// Balance given to every endowed account.
+const ENDOWMENT: Balance = 1 << 60;
+
+/// Budget escrowed for the genesis ad.
+const AD_FUNDING: Balance = 1_000 * UNIT;
+
+/// Genesis with Bob advertising ad 0 and Alice as verifier.
+fn new_test_ext() -> sp_io::TestExternalities {
+	let advertiser = Sr25519Keyring::Bob.to_account_id();
+	let endowed =
+		[Sr25519Keyring::Alice, Sr25519Keyring::Bob, Sr25519Keyring::Charlie, Sr25519Keyring::Dave];
+	let storage = RuntimeGenesisConfig {
+		balances: BalancesConfig {
+			balances: endowed.iter().map(|k| (k.to_account_id(), ENDOWMENT)).collect(),
+		},
+		ads: AdsConfig {
+			ad_spots: 1,
+			advertisers: vec![(advertiser.clone(), b"Advertiser".to_vec(), 10 * UNIT)],
+			ads: vec![(
+				advertiser,
+				0,
+				b"Ad".to_vec(),
+				b"An ad".to_vec(),
+				b"QmCid".to_vec(),
+				AD_FUNDING,
+			)],
+		},
+		fee_sponsorship: FeeSponsorshipConfig {
+			verifiers: vec![Sr25519Keyring::Alice.to_account_id()],
+		},
+		..Default::default()
+	}
+	.build_storage()
+	.unwrap();
+	sp_io::TestExternalities::new(storage)
+}
+
+/// `call` signed by `signer` with its fee charged to sponsorship `request_id`.
+fn sponsored_extrinsic(
+	signer: Sr25519Keyring,
+	call: RuntimeCall,
+	request_id: u32,
+) -> UncheckedExtrinsic {
+	let extra: SignedExtra = (
+		frame_system::CheckNonZeroSender::new(),
+		frame_system::CheckSpecVersion::new(),
+		frame_system::CheckTxVersion::new(),
+		frame_system::CheckGenesis::new(),
+		frame_system::CheckEra::from(Era::Immortal),
+		frame_system::CheckNonce::from(System::account_nonce(signer.to_account_id())),
+		frame_system::CheckWeight::new(),
+		pallet_fee_sponsorship::ChargeSponsoredTransactionPayment::sponsored(request_id),
+	);
+	let payload = SignedPayload::new(call.clone(), extra.clone()).unwrap();
+	let signature = payload.using_encoded(|payload| signer.sign(payload));
+	UncheckedExtrinsic::new_signed(
+		call,
+		MultiAddress::Id(signer.to_account_id()),
+		signature.into(),
+		extra,
+	)
+}
+
+#[test]
+fn sponsored_transfer_is_paid_by_the_ad() {
+	new_test_ext().execute_with(|| {
+		Executive::initialize_block(&Header::new(
+			1,
+			Default::default(),
+			Default::default(),
+			System::block_hash(0),
+			Default::default(),
+		));
+
+		let user = Sr25519Keyring::Charlie;
+		let request_id = pallet_fee_sponsorship::NextRequestId::<Runtime>::get();
+		assert_ok!(FeeSponsorship::sponsor_transaction(
+			RuntimeOrigin::signed(user.to_account_id()),
+			0,
+			MaxSponsoredFee::get()
+		));
+		assert_ok!(FeeSponsorship::verify_ad_view(
+			RuntimeOrigin::signed(Sr25519Keyring::Alice.to_account_id()),
+			request_id
+		));
+
+		let call = RuntimeCall::Balances(BalancesCall::transfer_keep_alive {
+			dest: MultiAddress::Id(Sr25519Keyring::Dave.to_account_id()),
+			value: UNIT,
+		});
+		let info = call.get_dispatch_info();
+		let xt = sponsored_extrinsic(user, call, request_id);
+		let fee = TransactionPayment::compute_fee(xt.encoded_size() as u32, &info, 0);
+		assert!(fee >= ExtrinsicBaseFee::get());
+		assert!(fee <= MaxSponsoredFee::get());
+
+		assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+
+		let charged = pallet_fee_sponsorship::TotalSponsored::<Runtime>::get(0);
+		assert!(charged >= ExtrinsicBaseFee::get() && charged <= fee);
+		assert_eq!(Balances::free_balance(user.to_account_id()), ENDOWMENT - UNIT);
+		assert_eq!(
+			pallet_ads::Ads::<Runtime>::get(0).unwrap().remaining_budget,
+			AD_FUNDING - charged
+		);
+		assert!(
+			pallet_fee_sponsorship::SponsorshipRequests::<Runtime>::get(request_id)
+				.unwrap()
+				.sponsored
+		);
+	});
+}
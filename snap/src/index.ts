@@ -41,7 +41,15 @@ async function initApi(): Promise<ApiPromise> {
   if (api) return api;
   
   const provider = new WsProvider(WS_ENDPOINT);
-  api = await ApiPromise.create({ provider });
+  api = await ApiPromise.create({
+    provider,
+    signedExtensions: {
+      ChargeSponsoredTransactionPayment: {
+        extrinsic: { tip: 'Compact<Balance>', sponsorship: 'Option<u32>' },
+        payload: {},
+      },
+    },
+  });
   return api;
 }
 
@@ -52,36 +60,28 @@ async function fetchRelevantAd(context: TransactionContext): Promise<AdData | nu
   const apiInstance = await initApi();
   
   try {
-    // Get all active ads
+    // Get all active ads with budget left from the node's `ads_activeAds` RPC
     const activeAds: AdData[] = [];
-    
-    // Query ads (simplified - in production, use proper iteration)
-    for (let i = 0; i < 100; i++) {
-      try {
-        const ad = await apiInstance.query.ads.ads(i);
-        if (ad.isSome) {
-          const adData = ad.unwrap();
-          if (adData.active) {
-            // Check if ad has remaining budget
-            const remainingBudget = adData.remaining_budget.toString();
-            if (parseInt(remainingBudget) > 0) {
-              const ipfsCid = Buffer.from(adData.ipfs_cid).toString('utf-8');
-              activeAds.push({
-                adId: i,
-                name: Buffer.from(adData.name).toString('utf-8'),
-                description: Buffer.from(adData.description).toString('utf-8'),
-                ipfsCid,
-                advertiser: adData.advertiser.toString(),
-                funding: adData.funding.toString(),
-                remainingBudget,
-                videoUrl: `${CRUST_GATEWAY}${ipfsCid}`,
-              });
-            }
-          }
+    const PAGE_SIZE = 100;
+
+    for (let start = 0; ; ) {
+      const page: any[] = await apiInstance.provider.send('ads_activeAds', [start, PAGE_SIZE]);
+      for (const ad of page) {
+        if (BigInt(ad.remainingBudget) > BigInt(0)) {
+          activeAds.push({
+            adId: ad.adId,
+            name: ad.name,
+            description: ad.description,
+            ipfsCid: ad.ipfsCid,
+            advertiser: ad.advertiser,
+            funding: ad.funding,
+            remainingBudget: ad.remainingBudget,
+            videoUrl: `${CRUST_GATEWAY}${ad.ipfsCid}`,
+          });
         }
-      } catch (e) {
-        break;
       }
+      if (page.length < PAGE_SIZE) break;
+      start = page[page.length - 1].adId + 1;
     }
     
     if (activeAds.length === 0) {
@@ -14,6 +14,7 @@ export default function AdSubmissionForm({ account, isRegistered, setIsRegistere
     name: '',
     description: '',
     ipfsCid: '',
+    videoLength: '',
     funding: '',
     spotId: '0'
   })
@@ -104,6 +105,8 @@ export default function AdSubmissionForm({ account, isRegistered, setIsRegistere
         formData.name,
         formData.description,
         formData.ipfsCid,
+        // Viewers must watch part of the video, derived from its length, for a view to count
+        Math.round(parseFloat(formData.videoLength || '0') * 1000),
         formData.funding
       )
       
@@ -114,6 +117,7 @@ export default function AdSubmissionForm({ account, isRegistered, setIsRegistere
         name: '',
         description: '',
         ipfsCid: '',
+        videoLength: '',
         funding: '',
         spotId: '0'
       })
@@ -236,6 +240,25 @@ export default function AdSubmissionForm({ account, isRegistered, setIsRegistere
           />
         </div>
 
+        {/* Video Length */}
+        <div>
+          <label className="block text-sm font-medium text-gray-700 mb-2">
+            Video Length (seconds)
+          </label>
+          <input
+            type="number"
+            value={formData.videoLength}
+            onChange={(e) => setFormData({ ...formData, videoLength: e.target.value })}
+            placeholder="30"
+            min="0"
+            step="0.1"
+            className="w-full px-4 py-3 bg-white border border-gray-300 rounded-md text-black placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-[#E6007A] focus:border-transparent"
+          />
+          <p className="text-xs text-gray-500 mt-1">
+            Sets how long viewers must watch before a view counts
+          </p>
+        </div>
+
         {/* Funding Amount */}
         <div>
           <label className="block text-sm font-medium text-gray-700 mb-2">
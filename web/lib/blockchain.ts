@@ -6,10 +6,19 @@ const RETRY_DELAY_MS = 1500
 
 let api: any = null
 
+// Replaces ChargeTransactionPayment in the runtime; `sponsorship` references a verified
+// sponsorship request whose ad pays the fee.
+const SIGNED_EXTENSIONS = {
+  ChargeSponsoredTransactionPayment: {
+    extrinsic: { tip: 'Compact<Balance>', sponsorship: 'Option<u32>' },
+    payload: {},
+  },
+}
+
 async function connectOnce() {
   const { ApiPromise, WsProvider } = await import('@polkadot/api')
   const provider = new WsProvider(WS_ENDPOINT)
-  const instance = await ApiPromise.create({ provider })
+  const instance = await ApiPromise.create({ provider, signedExtensions: SIGNED_EXTENSIONS })
   instance.on('disconnected', () => {
     api = null
   })
@@ -41,6 +50,18 @@ export function getWsEndpoint() {
   return WS_ENDPOINT
 }
 
+/**
+ * Call one of the node's `ads_*` / `sponsorship_*` RPC methods. They return plain JSON with
+ * UTF-8 strings and balances as decimal strings, so no SCALE decoding is needed.
+ */
+async function adsRpc<T>(method: string, params: unknown[] = []): Promise<T> {
+  const api = await getApi()
+  return api.provider.send(method, params)
+}
+
+// Page size of `ads_activeAds`
+const ADS_PAGE_SIZE = 100
+
 export async function registerAdvertiser(
   accountAddress: string,
   name: string,
@@ -84,7 +105,12 @@ export async function submitAd(
   name: string,
   description: string,
   ipfsCid: string,
-  funding: string
+  // Length of the video in milliseconds, 0 if unknown
+  creativeDurationMs: number,
+  funding: string,
+  // Optional campaign start and end block numbers
+  startsAt: number | null = null,
+  endsAt: number | null = null
 ) {
   const api = await getApi()
   const { web3FromAddress } = await import('@polkadot/extension-dapp')
@@ -100,7 +126,10 @@ export async function submitAd(
         name,
         description,
         ipfsCid,
-        fundingAmount
+        creativeDurationMs,
+        fundingAmount,
+        startsAt,
+        endsAt
       )
       .signAndSend(accountAddress, { signer: injector.signer }, ({ status, events }) => {
         if (status.isInBlock) {
@@ -125,40 +154,33 @@ export async function submitAd(
 }
 
 export async function getAdvertiserAds(accountAddress: string) {
-  const api = await getApi()
-  
   try {
-    // Get all ads by iterating through ad IDs
-    // Note: This is a simplified approach. In production, you'd use storage iteration
     const ads: any[] = []
-    
-    // Try to get ads up to a reasonable limit
-    // In a real implementation, you'd use storage iteration or an RPC method
-    for (let i = 0; i < 100; i++) {
-      try {
-        const ad = await api.query.ads.ads(i)
-        if (ad.isSome) {
-          const adData = ad.unwrap()
-          // Check if this ad belongs to the advertiser
-          if (adData.advertiser.toString() === accountAddress) {
-            ads.push({
-              id: i,
-              name: Buffer.from(adData.name).toString('utf-8'),
-              description: Buffer.from(adData.description).toString('utf-8'),
-              ipfsCid: Buffer.from(adData.ipfs_cid).toString('utf-8'),
-              funding: adData.funding.toString(),
-              remainingBudget: adData.remaining_budget.toString(),
-              views: adData.views.toNumber(),
-              active: adData.active,
-            })
-          }
-        }
-      } catch (e) {
-        // Ad doesn't exist, continue
-        break
+
+    for (let start = 0; ; ) {
+      const page = await adsRpc<any[]>('ads_advertiserAds', [accountAddress, start, ADS_PAGE_SIZE])
+      for (const ad of page) {
+        ads.push({
+          id: ad.adId,
+          name: ad.name,
+          description: ad.description,
+          ipfsCid: ad.ipfsCid,
+          creativeVersion: ad.creativeVersion,
+          funding: ad.funding,
+          remainingBudget: ad.remainingBudget,
+          views: ad.views,
+          active: ad.active,
+          // Block from which the unspent budget of a deactivated ad can be withdrawn
+          budgetUnlocksAt: ad.budgetUnlocksAt,
+          // 'pending', 'approved', 'rejected' or 'appealed'
+          moderation: ad.moderation,
+          rejectionReason: ad.rejectionReason,
+        })
       }
+      if (page.length < ADS_PAGE_SIZE) break
+      start = page[page.length - 1].adId + 1
     }
-    
+
     return ads
   } catch (error) {
     console.error('Error fetching advertiser ads:', error)
@@ -167,11 +189,9 @@ export async function getAdvertiserAds(accountAddress: string) {
 }
 
 export async function checkAdvertiserRegistration(accountAddress: string): Promise<boolean> {
-  const api = await getApi()
-  
   try {
-    const profile = await api.query.ads.advertiserProfiles(accountAddress)
-    return profile.isSome && profile.unwrap().active
+    const profile = await adsRpc<any>('ads_advertiser', [accountAddress])
+    return !!profile && profile.active
   } catch (error) {
     console.error('Error checking advertiser registration:', error)
     return false
@@ -204,12 +224,40 @@ export async function getAvailableAdSpots(): Promise<number[]> {
 }
 
 export async function getAdMetrics(adId: number) {
-  const api = await getApi()
-  
-  // Query ad tracking metrics
-  const metrics = await api.query.adTracking.adMetrics(adId)
-  
-  return metrics.toJSON()
+  // Totals include views and clicks rejected by the rate limits; only billable ones count
+  return adsRpc<{
+    totalViews: number
+    billableViews: number
+    rejectedViews: number
+    totalClicks: number
+    billableClicks: number
+    rejectedClicks: number
+    uniqueViewers: number
+    // Completed views attested by a registered client key
+    attestedViews: number
+  }>('ads_metrics', [adId])
+}
+
+/**
+ * Get the status of a sponsorship request
+ */
+export async function getSponsorshipRequest(requestId: number) {
+  return adsRpc<any>('sponsorship_request', [requestId])
+}
+
+/**
+ * Estimate the fee of a signed extrinsic and whether an ad can sponsor it
+ */
+export async function estimateSponsorship(extrinsicHex: string, adId: number) {
+  return adsRpc<{
+    adId: number
+    fee: string
+    feeAmount: string
+    maxSponsoredFee: string
+    remainingBudget: string | null
+    adActive: boolean
+    sponsorable: boolean
+  }>('sponsorship_estimate', [extrinsicHex, adId])
 }
 
 /**
@@ -226,41 +274,32 @@ export function getAdVideoUrl(ipfsCid: string, useCrust: boolean = true): string
  * Get all active ads with remaining budget
  */
 export async function getAllActiveAds() {
-  const api = await getApi()
-  
   try {
     const ads: any[] = []
-    
-    // Query all ads (simplified - in production, use proper iteration)
-    for (let i = 0; i < 100; i++) {
-      try {
-        const ad = await api.query.ads.ads(i)
-        if (ad.isSome) {
-          const adData = ad.unwrap()
-          if (adData.active) {
-            // Check if ad has remaining budget
-            const remainingBudget = adData.remaining_budget.toString()
-            if (parseInt(remainingBudget) > 0) {
-              const ipfsCid = Buffer.from(adData.ipfs_cid).toString('utf-8')
-              ads.push({
-                adId: i,
-                name: Buffer.from(adData.name).toString('utf-8'),
-                description: Buffer.from(adData.description).toString('utf-8'),
-                ipfsCid,
-                advertiser: adData.advertiser.toString(),
-                funding: adData.funding.toString(),
-                remainingBudget,
-                videoUrl: getAdVideoUrl(ipfsCid),
-              })
-            }
-          }
+
+    for (let start = 0; ; ) {
+      const page = await adsRpc<any[]>('ads_activeAds', [start, ADS_PAGE_SIZE])
+      for (const ad of page) {
+        // Skip ads whose budget is used up
+        if (BigInt(ad.remainingBudget) > BigInt(0)) {
+          ads.push({
+            adId: ad.adId,
+            name: ad.name,
+            description: ad.description,
+            ipfsCid: ad.ipfsCid,
+            // Length of the video in milliseconds, 0 if unknown
+            creativeDuration: ad.creativeDuration,
+            advertiser: ad.advertiser,
+            funding: ad.funding,
+            remainingBudget: ad.remainingBudget,
+            videoUrl: getAdVideoUrl(ad.ipfsCid),
+          })
         }
-      } catch (e) {
-        // Ad doesn't exist, continue
-        break
       }
+      if (page.length < ADS_PAGE_SIZE) break
+      start = page[page.length - 1].adId + 1
     }
-    
+
     return ads
   } catch (error) {
     console.error('Error fetching active ads:', error)